@@ -0,0 +1,180 @@
+//! `remaining_accounts` for the instructions that take a variable number of
+//! accounts, in the order the program reads them.
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use payermint_p::state::AssetType;
+use payermint_p::{MemberData, PayoutData};
+
+use crate::instructions::Token;
+use crate::pda;
+
+/// The uninitialized member PDA of each entry, in `members_data` order
+pub fn bulk_add_members_accounts(vault: &Pubkey, members_data: &[MemberData]) -> Vec<AccountMeta> {
+    members_data
+        .iter()
+        .map(|member_data| AccountMeta::new(pda::member(vault, &member_data.wallet), false))
+        .collect()
+}
+
+/// Per payout `[member, receipt (mut), member_wallet (mut)]` for SOL legs and
+/// `[member, receipt (mut), mint, vault_token_account (mut),
+/// member_token_account (mut), treasury_token_account (mut)]` for SPL legs.
+/// SPL legs expect the member and treasury ATAs to exist already.
+pub fn bulk_process_payouts_accounts(
+    vault: &Pubkey,
+    batch_id: u64,
+    treasury: &Pubkey,
+    token_program: &Pubkey,
+    payout_data: &[PayoutData],
+) -> Vec<AccountMeta> {
+    let payroll_batch = pda::payroll_batch(vault, batch_id);
+    let mut accounts = Vec::new();
+
+    for payout in payout_data {
+        let member = pda::member(vault, &payout.member);
+        accounts.push(AccountMeta::new_readonly(member, false));
+        accounts.push(AccountMeta::new(
+            pda::batch_receipt(&payroll_batch, &member),
+            false,
+        ));
+
+        match payout.asset_type {
+            AssetType::SOL => accounts.push(AccountMeta::new(payout.member, false)),
+            AssetType::SPLToken { mint } => {
+                let token = Token {
+                    mint,
+                    program: *token_program,
+                };
+                accounts.extend([
+                    AccountMeta::new_readonly(mint, false),
+                    AccountMeta::new(token.account(vault), false),
+                    AccountMeta::new(token.account(&payout.member), false),
+                    AccountMeta::new(token.account(treasury), false),
+                ]);
+            }
+        }
+    }
+
+    accounts
+}
+
+/// The member PDA (mut) of each wallet being force-closed, then
+/// `[mint, token_program, vault_token_account (mut), owner_token_account (mut)]`
+/// per token
+pub fn close_vault_accounts(
+    vault: &Pubkey,
+    owner: &Pubkey,
+    member_wallets: &[Pubkey],
+    tokens: &[Token],
+) -> Vec<AccountMeta> {
+    let members = member_wallets
+        .iter()
+        .map(|wallet| AccountMeta::new(pda::member(vault, wallet), false));
+    let tokens = tokens.iter().flat_map(|token| {
+        [
+            AccountMeta::new_readonly(token.mint, false),
+            AccountMeta::new_readonly(token.program, false),
+            AccountMeta::new(token.account(vault), false),
+            AccountMeta::new(token.account(owner), false),
+        ]
+    });
+    members.chain(tokens).collect()
+}
+
+/// The vault ATA of each token to resync
+pub fn sync_balances_accounts(vault: &Pubkey, tokens: &[Token]) -> Vec<AccountMeta> {
+    tokens
+        .iter()
+        .map(|token| AccountMeta::new_readonly(token.account(vault), false))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bulk_payout_legs_follow_program_layout() {
+        let vault = Pubkey::new_unique();
+        let treasury = Pubkey::new_unique();
+        let token_program = anchor_spl::token::ID;
+        let mint = Pubkey::new_unique();
+        let sol_wallet = Pubkey::new_unique();
+        let spl_wallet = Pubkey::new_unique();
+        let payouts = [
+            PayoutData {
+                member: sol_wallet,
+                amount: 1,
+                asset_type: AssetType::SOL,
+            },
+            PayoutData {
+                member: spl_wallet,
+                amount: 1,
+                asset_type: AssetType::SPLToken { mint },
+            },
+        ];
+
+        let accounts =
+            bulk_process_payouts_accounts(&vault, 7, &treasury, &token_program, &payouts);
+        let keys: Vec<Pubkey> = accounts.iter().map(|meta| meta.pubkey).collect();
+        let batch = pda::payroll_batch(&vault, 7);
+        let sol_member = pda::member(&vault, &sol_wallet);
+        let spl_member = pda::member(&vault, &spl_wallet);
+        let token = Token {
+            mint,
+            program: token_program,
+        };
+
+        assert_eq!(
+            keys,
+            vec![
+                sol_member,
+                pda::batch_receipt(&batch, &sol_member),
+                sol_wallet,
+                spl_member,
+                pda::batch_receipt(&batch, &spl_member),
+                mint,
+                token.account(&vault),
+                token.account(&spl_wallet),
+                token.account(&treasury),
+            ]
+        );
+        let writable: Vec<bool> = accounts.iter().map(|meta| meta.is_writable).collect();
+        assert_eq!(
+            writable,
+            vec![false, true, true, false, true, false, true, true, true]
+        );
+        assert!(accounts.iter().all(|meta| !meta.is_signer));
+    }
+
+    #[test]
+    fn close_vault_accounts_are_members_then_token_groups() {
+        let vault = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        // Mints of both token programs can be swept in one close
+        let tokens = [
+            Token {
+                mint: Pubkey::new_unique(),
+                program: anchor_spl::token::ID,
+            },
+            Token {
+                mint: Pubkey::new_unique(),
+                program: anchor_spl::token_2022::ID,
+            },
+        ];
+
+        let accounts = close_vault_accounts(&vault, &owner, &[wallet], &tokens);
+
+        assert_eq!(accounts.len(), 9);
+        assert_eq!(accounts[0].pubkey, pda::member(&vault, &wallet));
+        assert!(accounts[0].is_writable);
+        for (group, token) in accounts[1..].chunks_exact(4).zip(&tokens) {
+            assert_eq!(group[0].pubkey, token.mint);
+            assert_eq!(group[1].pubkey, token.program);
+            assert!(!group[1].is_writable);
+            assert_eq!(group[2].pubkey, token.account(&vault));
+            assert_eq!(group[3].pubkey, token.account(&owner));
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Fetching and decoding of program accounts.
+//!
+//! The crate does not pick an RPC client; callers implement `AccountSource`
+//! over whichever one their backend already uses.
+
+use std::fmt;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use payermint_p::state::{GlobalConfig, Member, PayrollBatch, VaultAccount};
+
+use crate::pda;
+
+/// Raw account data lookup, e.g. `RpcClient::get_account` mapped to its data
+pub trait AccountSource {
+    type Error;
+
+    /// Returns `None` when no account exists at `address`
+    fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, Self::Error>;
+}
+
+#[derive(Debug)]
+pub enum FetchError<E> {
+    Source(E),
+    AccountNotFound(Pubkey),
+    Decode(Pubkey, anchor_lang::error::Error),
+}
+
+impl<E: fmt::Display> fmt::Display for FetchError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Source(err) => write!(f, "account source error: {err}"),
+            FetchError::AccountNotFound(address) => write!(f, "account {address} not found"),
+            FetchError::Decode(address, err) => {
+                write!(f, "failed to decode account {address}: {err}")
+            }
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for FetchError<E> {}
+
+/// Decodes account data, checking the Anchor discriminator first
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> anchor_lang::Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+pub fn fetch<T, S>(source: &S, address: &Pubkey) -> Result<T, FetchError<S::Error>>
+where
+    T: AccountDeserialize,
+    S: AccountSource,
+{
+    let data = source
+        .account_data(address)
+        .map_err(FetchError::Source)?
+        .ok_or(FetchError::AccountNotFound(*address))?;
+    decode(&data).map_err(|err| FetchError::Decode(*address, err))
+}
+
+pub fn fetch_global_config<S: AccountSource>(
+    source: &S,
+) -> Result<GlobalConfig, FetchError<S::Error>> {
+    fetch(source, &pda::global_config())
+}
+
+pub fn fetch_vault<S: AccountSource>(
+    source: &S,
+    vault: &Pubkey,
+) -> Result<VaultAccount, FetchError<S::Error>> {
+    fetch(source, vault)
+}
+
+pub fn fetch_member<S: AccountSource>(
+    source: &S,
+    vault: &Pubkey,
+    wallet: &Pubkey,
+) -> Result<Member, FetchError<S::Error>> {
+    fetch(source, &pda::member(vault, wallet))
+}
+
+pub fn fetch_payroll_batch<S: AccountSource>(
+    source: &S,
+    vault: &Pubkey,
+    batch_id: u64,
+) -> Result<PayrollBatch, FetchError<S::Error>> {
+    fetch(source, &pda::payroll_batch(vault, batch_id))
+}
@@ -0,0 +1,1433 @@
+//! One builder per program instruction, in the order of `lib.rs`. Builders
+//! derive every PDA and ATA they need; callers supply wallets, ids and args.
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token;
+use payermint_p::state::{
+    AssetType, ClaimCode, FeeLimit, FeeMode, FeeTier, OwnerAction, PayoutCycle, PayoutSchedule,
+};
+use payermint_p::{accounts, instruction as args, MemberData, PayoutData};
+
+use crate::{bulk, pda};
+
+/// Signer of an instruction that a vault delegate may also sign
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Authority {
+    Owner(Pubkey),
+    /// Wallet holding a `VaultDelegate` with the required permission
+    Delegate(Pubkey),
+}
+
+impl Authority {
+    pub fn key(&self) -> Pubkey {
+        match self {
+            Authority::Owner(key) | Authority::Delegate(key) => *key,
+        }
+    }
+
+    fn delegate(&self, vault: &Pubkey) -> Option<Pubkey> {
+        match self {
+            Authority::Owner(_) => None,
+            Authority::Delegate(key) => Some(pda::delegate(vault, key)),
+        }
+    }
+}
+
+/// SPL mint together with the token program that owns it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub mint: Pubkey,
+    pub program: Pubkey,
+}
+
+impl Token {
+    /// Associated token account of `owner` for this mint
+    pub fn account(&self, owner: &Pubkey) -> Pubkey {
+        pda::token_account(owner, &self.mint, &self.program)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Asset {
+    Sol,
+    Spl(Token),
+}
+
+impl Asset {
+    pub fn asset_type(&self) -> AssetType {
+        match self {
+            Asset::Sol => AssetType::SOL,
+            Asset::Spl(token) => AssetType::SPLToken { mint: token.mint },
+        }
+    }
+
+    fn vault_token_account(&self, vault: &Pubkey) -> Option<Pubkey> {
+        match self {
+            Asset::Sol => None,
+            Asset::Spl(token) => Some(token.account(vault)),
+        }
+    }
+}
+
+fn build(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: payermint_p::ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+fn build_with_remaining(
+    accounts: impl ToAccountMetas,
+    args: impl InstructionData,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut ix = build(accounts, args);
+    ix.accounts.extend(remaining_accounts);
+    ix
+}
+
+// GLOBAL CONFIG
+
+pub fn initialize_global_config(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    treasury: &Pubkey,
+    default_fee_bps: u16,
+) -> Instruction {
+    build(
+        accounts::InitializeGlobalConfig {
+            payer: *payer,
+            owner: *owner,
+            global_config: pda::global_config(),
+            treasury: *treasury,
+            system_program: system_program::ID,
+        },
+        args::InitializeGlobalConfig { default_fee_bps },
+    )
+}
+
+fn update_global_config(owner: &Pubkey) -> accounts::UpdateGlobalConfig {
+    accounts::UpdateGlobalConfig {
+        global_config: pda::global_config(),
+        owner: *owner,
+    }
+}
+
+/// Queues the change; it takes effect through `apply_config_change`
+pub fn update_treasury(owner: &Pubkey, new_treasury: &Pubkey) -> Instruction {
+    build(
+        update_global_config(owner),
+        args::UpdateTreasury {
+            new_treasury: *new_treasury,
+        },
+    )
+}
+
+/// Queues the change; it takes effect through `apply_config_change`
+pub fn update_default_fee(owner: &Pubkey, new_fee_bps: u16) -> Instruction {
+    build(
+        update_global_config(owner),
+        args::UpdateDefaultFee { new_fee_bps },
+    )
+}
+
+pub fn apply_config_change() -> Instruction {
+    build(
+        accounts::ApplyConfigChange {
+            global_config: pda::global_config(),
+        },
+        args::ApplyConfigChange {},
+    )
+}
+
+pub fn update_fee_limit(owner: &Pubkey, fee_limit: FeeLimit) -> Instruction {
+    build(
+        update_global_config(owner),
+        args::UpdateFeeLimit { fee_limit },
+    )
+}
+
+pub fn cancel_config_change(owner: &Pubkey) -> Instruction {
+    build(update_global_config(owner), args::CancelConfigChange {})
+}
+
+pub fn propose_config_owner(owner: &Pubkey, new_owner: &Pubkey) -> Instruction {
+    build(
+        update_global_config(owner),
+        args::ProposeConfigOwner {
+            new_owner: *new_owner,
+        },
+    )
+}
+
+pub fn cancel_config_owner_proposal(owner: &Pubkey) -> Instruction {
+    build(
+        update_global_config(owner),
+        args::CancelConfigOwnerProposal {},
+    )
+}
+
+pub fn set_protocol_paused(owner: &Pubkey, paused: bool) -> Instruction {
+    build(
+        update_global_config(owner),
+        args::SetProtocolPaused { paused },
+    )
+}
+
+pub fn set_fee_tiers(owner: &Pubkey, fee_tiers: Vec<FeeTier>) -> Instruction {
+    build(update_global_config(owner), args::SetFeeTiers { fee_tiers })
+}
+
+pub fn set_vault_fee_override(
+    owner: &Pubkey,
+    vault: &Pubkey,
+    fee_override_bps: Option<u16>,
+) -> Instruction {
+    build(
+        accounts::SetVaultFeeOverride {
+            global_config: pda::global_config(),
+            vault_account: *vault,
+            owner: *owner,
+        },
+        args::SetVaultFeeOverride { fee_override_bps },
+    )
+}
+
+pub fn accept_config_owner(new_owner: &Pubkey) -> Instruction {
+    build(
+        accounts::AcceptConfigOwner {
+            global_config: pda::global_config(),
+            new_owner: *new_owner,
+        },
+        args::AcceptConfigOwner {},
+    )
+}
+
+// VAULT MANAGEMENT
+
+/// `vault_id` must be the current `GlobalConfig::next_company_id`
+pub fn create_vault(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    vault_id: u64,
+    vault_args: args::CreateVault,
+) -> Instruction {
+    build(
+        accounts::CreateVault {
+            payer: *payer,
+            owner: *owner,
+            global_config: pda::global_config(),
+            vault_account: pda::vault(owner, vault_id),
+            system_program: system_program::ID,
+        },
+        vault_args,
+    )
+}
+
+/// `member_wallets` lists every remaining member when `force` is set, and `tokens`
+/// every whitelisted or tracked SPL mint with the token program that owns it. Needs
+/// an approved `OwnerAction::CloseVault` once the vault has approvers.
+pub fn close_vault(
+    owner: &Pubkey,
+    vault: &Pubkey,
+    force: bool,
+    member_wallets: &[Pubkey],
+    tokens: &[Token],
+) -> Instruction {
+    build_with_remaining(
+        accounts::CloseVault {
+            vault_account: *vault,
+            approval_config: pda::approval_config(vault),
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        args::CloseVault { force },
+        bulk::close_vault_accounts(vault, owner, member_wallets, tokens),
+    )
+}
+
+fn update_vault(owner: &Pubkey, vault: &Pubkey) -> accounts::UpdateVault {
+    accounts::UpdateVault {
+        vault_account: *vault,
+        owner: *owner,
+    }
+}
+
+pub fn propose_vault_owner(owner: &Pubkey, vault: &Pubkey, new_owner: &Pubkey) -> Instruction {
+    build(
+        update_vault(owner, vault),
+        args::ProposeVaultOwner {
+            new_owner: *new_owner,
+        },
+    )
+}
+
+pub fn cancel_vault_owner_proposal(owner: &Pubkey, vault: &Pubkey) -> Instruction {
+    build(
+        update_vault(owner, vault),
+        args::CancelVaultOwnerProposal {},
+    )
+}
+
+pub fn accept_vault_owner(new_owner: &Pubkey, vault: &Pubkey) -> Instruction {
+    build(
+        accounts::AcceptVaultOwner {
+            vault_account: *vault,
+            new_owner: *new_owner,
+        },
+        args::AcceptVaultOwner {},
+    )
+}
+
+fn update_vault_schedule(authority: &Authority, vault: &Pubkey) -> accounts::UpdateVaultSchedule {
+    accounts::UpdateVaultSchedule {
+        vault_account: *vault,
+        authority: authority.key(),
+        delegate: authority.delegate(vault),
+    }
+}
+
+pub fn update_payout_schedule(
+    authority: &Authority,
+    vault: &Pubkey,
+    schedule: Option<PayoutSchedule>,
+) -> Instruction {
+    build(
+        update_vault_schedule(authority, vault),
+        args::UpdatePayoutSchedule { schedule },
+    )
+}
+
+fn update_vault_payouts(owner: &Pubkey, vault: &Pubkey) -> accounts::UpdateVaultPayouts {
+    accounts::UpdateVaultPayouts {
+        vault_account: *vault,
+        approval_config: pda::approval_config(vault),
+        owner: *owner,
+    }
+}
+
+/// Needs an approved `OwnerAction::SetClaimCode` once the vault has approvers
+pub fn set_claim_code(
+    owner: &Pubkey,
+    vault: &Pubkey,
+    code_claim: Option<ClaimCode>,
+) -> Instruction {
+    build(
+        update_vault_payouts(owner, vault),
+        args::SetClaimCode { code_claim },
+    )
+}
+
+pub fn add_whitelisted_asset(owner: &Pubkey, vault: &Pubkey, asset: AssetType) -> Instruction {
+    build(
+        update_vault(owner, vault),
+        args::AddWhitelistedAsset { asset },
+    )
+}
+
+pub fn remove_whitelisted_asset(owner: &Pubkey, vault: &Pubkey, asset: AssetType) -> Instruction {
+    build(
+        update_vault(owner, vault),
+        args::RemoveWhitelistedAsset { asset },
+    )
+}
+
+pub fn set_fee_mode(owner: &Pubkey, vault: &Pubkey, fee_mode: FeeMode) -> Instruction {
+    build(update_vault(owner, vault), args::SetFeeMode { fee_mode })
+}
+
+pub fn set_vault_paused(owner: &Pubkey, vault: &Pubkey, paused: bool) -> Instruction {
+    build(update_vault(owner, vault), args::SetVaultPaused { paused })
+}
+
+/// Needs an approved `OwnerAction::UpdateKeeperReward` once the vault has approvers
+pub fn update_keeper_reward(owner: &Pubkey, vault: &Pubkey, keeper_reward: u64) -> Instruction {
+    build(
+        update_vault_payouts(owner, vault),
+        args::UpdateKeeperReward { keeper_reward },
+    )
+}
+
+pub fn update_batch_retention(
+    owner: &Pubkey,
+    vault: &Pubkey,
+    batch_retention_secs: i64,
+) -> Instruction {
+    build(
+        update_vault(owner, vault),
+        args::UpdateBatchRetention {
+            batch_retention_secs,
+        },
+    )
+}
+
+// MEMBER MANAGEMENT
+
+pub fn add_member(
+    payer: &Pubkey,
+    authority: &Authority,
+    vault: &Pubkey,
+    wallet: &Pubkey,
+    member_args: args::AddMember,
+) -> Instruction {
+    build(
+        accounts::AddMember {
+            payer: *payer,
+            vault_account: *vault,
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+            member: pda::member(vault, wallet),
+            wallet: *wallet,
+            system_program: system_program::ID,
+        },
+        member_args,
+    )
+}
+
+fn update_member(authority: &Authority, vault: &Pubkey, wallet: &Pubkey) -> accounts::UpdateMember {
+    accounts::UpdateMember {
+        vault_account: *vault,
+        member: pda::member(vault, wallet),
+        authority: authority.key(),
+        delegate: authority.delegate(vault),
+    }
+}
+
+pub fn update_member_allocation(
+    authority: &Authority,
+    vault: &Pubkey,
+    wallet: &Pubkey,
+    allocation_bps: Option<u16>,
+) -> Instruction {
+    build(
+        update_member(authority, vault, wallet),
+        args::UpdateMemberAllocation { allocation_bps },
+    )
+}
+
+pub fn update_member_payment_allocations(
+    authority: &Authority,
+    vault: &Pubkey,
+    wallet: &Pubkey,
+    sol_allocation: Option<u64>,
+    spl_allocation: Option<u64>,
+) -> Instruction {
+    build(
+        update_member(authority, vault, wallet),
+        args::UpdateMemberPaymentAllocations {
+            sol_allocation,
+            spl_allocation,
+        },
+    )
+}
+
+pub fn toggle_member_active_status(
+    authority: &Authority,
+    vault: &Pubkey,
+    wallet: &Pubkey,
+) -> Instruction {
+    build(
+        update_member(authority, vault, wallet),
+        args::ToggleMemberActiveStatus {},
+    )
+}
+
+/// The member rent is refunded to the vault `owner`
+pub fn remove_member(
+    authority: &Authority,
+    owner: &Pubkey,
+    vault: &Pubkey,
+    wallet: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::RemoveMember {
+            vault_account: *vault,
+            member: pda::member(vault, wallet),
+            owner: *owner,
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+        },
+        args::RemoveMember {},
+    )
+}
+
+// DEPOSITS
+
+pub fn deposit_sol(depositor: &Pubkey, vault: &Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::DepositSol {
+            vault_account: *vault,
+            global_config: pda::global_config(),
+            depositor: *depositor,
+            system_program: system_program::ID,
+        },
+        args::DepositSol { amount },
+    )
+}
+
+pub fn deposit_spl_token(
+    depositor: &Pubkey,
+    vault: &Pubkey,
+    token: &Token,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::DepositSplToken {
+            vault_account: *vault,
+            global_config: pda::global_config(),
+            depositor: *depositor,
+            mint: token.mint,
+            depositor_token_account: token.account(depositor),
+            vault_token_account: token.account(vault),
+            token_program: token.program,
+            associated_token_program: associated_token::ID,
+        },
+        args::DepositSplToken { amount },
+    )
+}
+
+// WITHDRAWALS
+
+/// Needs an approved `OwnerAction::WithdrawSol` once the vault has approvers
+pub fn withdraw_sol(
+    authority: &Authority,
+    owner: &Pubkey,
+    vault: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::WithdrawSol {
+            vault_account: *vault,
+            approval_config: pda::approval_config(vault),
+            owner: *owner,
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+            system_program: system_program::ID,
+        },
+        args::WithdrawSol { amount },
+    )
+}
+
+/// `authority` pays for the owner's ATA if it does not exist yet. Needs an approved
+/// `OwnerAction::WithdrawSpl` once the vault has approvers.
+pub fn withdraw_spl_token(
+    authority: &Authority,
+    owner: &Pubkey,
+    vault: &Pubkey,
+    token: &Token,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::WithdrawSplToken {
+            vault_account: *vault,
+            approval_config: pda::approval_config(vault),
+            owner: *owner,
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+            mint: token.mint,
+            vault_token_account: token.account(vault),
+            owner_token_account: token.account(owner),
+            token_program: token.program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        args::WithdrawSplToken { amount },
+    )
+}
+
+/// Permissionless; resyncs SOL and the vault ATA of each of `tokens`
+pub fn sync_balances(vault: &Pubkey, tokens: &[Token]) -> Instruction {
+    build_with_remaining(
+        accounts::SyncBalances {
+            vault_account: *vault,
+        },
+        args::SyncBalances {},
+        bulk::sync_balances_accounts(vault, tokens),
+    )
+}
+
+// PAYROLL PROCESSING
+
+pub fn create_payroll_batch(
+    payer: &Pubkey,
+    authority: &Authority,
+    vault: &Pubkey,
+    batch_id: u64,
+    total_amount: u64,
+) -> Instruction {
+    build(
+        accounts::CreatePayrollBatch {
+            payer: *payer,
+            vault_account: *vault,
+            global_config: pda::global_config(),
+            payroll_batch: pda::payroll_batch(vault, batch_id),
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+            system_program: system_program::ID,
+        },
+        args::CreatePayrollBatch {
+            batch_id,
+            total_amount,
+        },
+    )
+}
+
+pub fn process_sol_payout(
+    payer: &Pubkey,
+    authority: &Authority,
+    vault: &Pubkey,
+    batch_id: u64,
+    member_wallet: &Pubkey,
+    treasury: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let payroll_batch = pda::payroll_batch(vault, batch_id);
+    let member = pda::member(vault, member_wallet);
+    build(
+        accounts::ProcessSolPayout {
+            payer: *payer,
+            vault_account: *vault,
+            payroll_batch,
+            approval_config: pda::approval_config(vault),
+            member,
+            receipt: pda::batch_receipt(&payroll_batch, &member),
+            global_config: pda::global_config(),
+            treasury: *treasury,
+            member_wallet: *member_wallet,
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+            system_program: system_program::ID,
+        },
+        args::ProcessSolPayout { amount },
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_spl_payout(
+    payer: &Pubkey,
+    authority: &Authority,
+    vault: &Pubkey,
+    batch_id: u64,
+    member_wallet: &Pubkey,
+    treasury: &Pubkey,
+    token: &Token,
+    amount: u64,
+) -> Instruction {
+    let payroll_batch = pda::payroll_batch(vault, batch_id);
+    let member = pda::member(vault, member_wallet);
+    build(
+        accounts::ProcessSplPayout {
+            payer: *payer,
+            vault_account: *vault,
+            payroll_batch,
+            approval_config: pda::approval_config(vault),
+            member,
+            receipt: pda::batch_receipt(&payroll_batch, &member),
+            global_config: pda::global_config(),
+            mint: token.mint,
+            vault_token_account: token.account(vault),
+            treasury: *treasury,
+            treasury_token_account: token.account(treasury),
+            member_wallet: *member_wallet,
+            member_token_account: token.account(member_wallet),
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+            token_program: token.program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        args::ProcessSplPayout { amount },
+    )
+}
+
+// PULL-BASED CLAIMS
+
+pub fn allocate_claim(
+    payer: &Pubkey,
+    authority: &Authority,
+    vault: &Pubkey,
+    batch_id: u64,
+    member_wallet: &Pubkey,
+    asset: &Asset,
+    amount: u64,
+) -> Instruction {
+    let payroll_batch = pda::payroll_batch(vault, batch_id);
+    let member = pda::member(vault, member_wallet);
+    build(
+        accounts::AllocateClaim {
+            payer: *payer,
+            vault_account: *vault,
+            payroll_batch,
+            approval_config: pda::approval_config(vault),
+            member,
+            receipt: pda::batch_receipt(&payroll_batch, &member),
+            global_config: pda::global_config(),
+            vault_token_account: asset.vault_token_account(vault),
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+            system_program: system_program::ID,
+        },
+        args::AllocateClaim {
+            amount,
+            asset_type: asset.asset_type(),
+        },
+    )
+}
+
+pub fn claim_sol_payment(
+    claimant: &Pubkey,
+    vault: &Pubkey,
+    batch_id: u64,
+    treasury: &Pubkey,
+) -> Instruction {
+    let member = pda::member(vault, claimant);
+    build(
+        accounts::ClaimSolPayment {
+            claimant: *claimant,
+            vault_account: *vault,
+            receipt: pda::batch_receipt(&pda::payroll_batch(vault, batch_id), &member),
+            global_config: pda::global_config(),
+            treasury: *treasury,
+            system_program: system_program::ID,
+        },
+        args::ClaimSolPayment {},
+    )
+}
+
+pub fn claim_spl_payment(
+    claimant: &Pubkey,
+    vault: &Pubkey,
+    batch_id: u64,
+    treasury: &Pubkey,
+    token: &Token,
+) -> Instruction {
+    let member = pda::member(vault, claimant);
+    build(
+        accounts::ClaimSplPayment {
+            claimant: *claimant,
+            vault_account: *vault,
+            receipt: pda::batch_receipt(&pda::payroll_batch(vault, batch_id), &member),
+            global_config: pda::global_config(),
+            mint: token.mint,
+            vault_token_account: token.account(vault),
+            treasury: *treasury,
+            treasury_token_account: token.account(treasury),
+            claimant_token_account: token.account(claimant),
+            token_program: token.program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        args::ClaimSplPayment {},
+    )
+}
+
+/// `payer` paid the rent of the unclaimed receipt and gets it back
+pub fn cancel_claim(
+    owner: &Pubkey,
+    vault: &Pubkey,
+    batch_id: u64,
+    member_wallet: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    let member = pda::member(vault, member_wallet);
+    build(
+        accounts::CancelClaim {
+            vault_account: *vault,
+            receipt: pda::batch_receipt(&pda::payroll_batch(vault, batch_id), &member),
+            payer: *payer,
+            owner: *owner,
+        },
+        args::CancelClaim {},
+    )
+}
+
+pub fn claim_with_code(
+    claimant: &Pubkey,
+    vault: &Pubkey,
+    treasury: &Pubkey,
+    code: &str,
+) -> Instruction {
+    build(
+        accounts::ClaimWithCode {
+            claimant: *claimant,
+            vault_account: *vault,
+            redemption: pda::redemption(vault, code, claimant),
+            global_config: pda::global_config(),
+            treasury: *treasury,
+            system_program: system_program::ID,
+        },
+        args::ClaimWithCode {
+            code: code.to_string(),
+        },
+    )
+}
+
+pub fn claim_spl_with_code(
+    claimant: &Pubkey,
+    vault: &Pubkey,
+    treasury: &Pubkey,
+    token: &Token,
+    code: &str,
+) -> Instruction {
+    build(
+        accounts::ClaimSplWithCode {
+            claimant: *claimant,
+            vault_account: *vault,
+            redemption: pda::redemption(vault, code, claimant),
+            global_config: pda::global_config(),
+            mint: token.mint,
+            vault_token_account: token.account(vault),
+            treasury: *treasury,
+            treasury_token_account: token.account(treasury),
+            claimant_token_account: token.account(claimant),
+            token_program: token.program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        args::ClaimSplWithCode {
+            code: code.to_string(),
+        },
+    )
+}
+
+/// `payer` paid the receipt rent when `member_wallet` was paid in `cycle`
+pub fn close_receipt(
+    payer: &Pubkey,
+    vault: &Pubkey,
+    member_wallet: &Pubkey,
+    cycle: &PayoutCycle,
+) -> Instruction {
+    let member = pda::member(vault, member_wallet);
+    let (receipt, payroll_batch) = match cycle {
+        PayoutCycle::Batch { batch_id } => {
+            let payroll_batch = pda::payroll_batch(vault, *batch_id);
+            (
+                pda::batch_receipt(&payroll_batch, &member),
+                Some(payroll_batch),
+            )
+        }
+        PayoutCycle::Period { start_ts } => (pda::period_receipt(vault, *start_ts, &member), None),
+    };
+    build(
+        accounts::CloseReceipt {
+            receipt,
+            vault_account: *vault,
+            payroll_batch,
+            payer: *payer,
+        },
+        args::CloseReceipt {},
+    )
+}
+
+pub fn close_redemption(claimant: &Pubkey, vault: &Pubkey, code: &str) -> Instruction {
+    build(
+        accounts::CloseRedemption {
+            redemption: pda::redemption(vault, code, claimant),
+            vault_account: *vault,
+            claimant: *claimant,
+        },
+        args::CloseRedemption {},
+    )
+}
+
+// STREAMING PAYROLL
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_stream(
+    payer: &Pubkey,
+    authority: &Authority,
+    vault: &Pubkey,
+    member_wallet: &Pubkey,
+    asset: &Asset,
+    rate_per_second: u64,
+    start_ts: i64,
+    end_ts: Option<i64>,
+    deposit: u64,
+) -> Instruction {
+    let member = pda::member(vault, member_wallet);
+    build(
+        accounts::CreateStream {
+            payer: *payer,
+            vault_account: *vault,
+            member,
+            global_config: pda::global_config(),
+            stream: pda::stream(&member),
+            vault_token_account: asset.vault_token_account(vault),
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+            system_program: system_program::ID,
+        },
+        args::CreateStream {
+            asset_type: asset.asset_type(),
+            rate_per_second,
+            start_ts,
+            end_ts,
+            deposit,
+        },
+    )
+}
+
+fn update_stream(
+    authority: &Authority,
+    vault: &Pubkey,
+    member_wallet: &Pubkey,
+    vault_token_account: Option<Pubkey>,
+) -> accounts::UpdateStream {
+    accounts::UpdateStream {
+        vault_account: *vault,
+        stream: pda::stream(&pda::member(vault, member_wallet)),
+        global_config: pda::global_config(),
+        vault_token_account,
+        authority: authority.key(),
+        delegate: authority.delegate(vault),
+    }
+}
+
+pub fn top_up_stream(
+    authority: &Authority,
+    vault: &Pubkey,
+    member_wallet: &Pubkey,
+    asset: &Asset,
+    amount: u64,
+) -> Instruction {
+    build(
+        update_stream(
+            authority,
+            vault,
+            member_wallet,
+            asset.vault_token_account(vault),
+        ),
+        args::TopUpStream { amount },
+    )
+}
+
+pub fn pause_stream(authority: &Authority, vault: &Pubkey, member_wallet: &Pubkey) -> Instruction {
+    build(
+        update_stream(authority, vault, member_wallet, None),
+        args::PauseStream {},
+    )
+}
+
+pub fn resume_stream(authority: &Authority, vault: &Pubkey, member_wallet: &Pubkey) -> Instruction {
+    build(
+        update_stream(authority, vault, member_wallet, None),
+        args::ResumeStream {},
+    )
+}
+
+pub fn cancel_stream(authority: &Authority, vault: &Pubkey, member_wallet: &Pubkey) -> Instruction {
+    build(
+        update_stream(authority, vault, member_wallet, None),
+        args::CancelStream {},
+    )
+}
+
+/// `payer` is the account that paid the stream rent and gets it back
+pub fn close_stream(
+    authority: &Authority,
+    vault: &Pubkey,
+    member_wallet: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::CloseStream {
+            vault_account: *vault,
+            stream: pda::stream(&pda::member(vault, member_wallet)),
+            payer: *payer,
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+        },
+        args::CloseStream {},
+    )
+}
+
+pub fn withdraw_sol_stream(wallet: &Pubkey, vault: &Pubkey, treasury: &Pubkey) -> Instruction {
+    build(
+        accounts::WithdrawSolStream {
+            wallet: *wallet,
+            vault_account: *vault,
+            stream: pda::stream(&pda::member(vault, wallet)),
+            global_config: pda::global_config(),
+            treasury: *treasury,
+            system_program: system_program::ID,
+        },
+        args::WithdrawSolStream {},
+    )
+}
+
+pub fn withdraw_spl_stream(
+    wallet: &Pubkey,
+    vault: &Pubkey,
+    treasury: &Pubkey,
+    token: &Token,
+) -> Instruction {
+    build(
+        accounts::WithdrawSplStream {
+            wallet: *wallet,
+            vault_account: *vault,
+            stream: pda::stream(&pda::member(vault, wallet)),
+            global_config: pda::global_config(),
+            mint: token.mint,
+            vault_token_account: token.account(vault),
+            treasury: *treasury,
+            treasury_token_account: token.account(treasury),
+            wallet_token_account: token.account(wallet),
+            token_program: token.program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        args::WithdrawSplStream {},
+    )
+}
+
+// TOKEN VESTING
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_vesting(
+    payer: &Pubkey,
+    authority: &Authority,
+    vault: &Pubkey,
+    member_wallet: &Pubkey,
+    token: &Token,
+    start_ts: i64,
+    cliff_ts: i64,
+    duration: i64,
+    revocable: bool,
+) -> Instruction {
+    let member = pda::member(vault, member_wallet);
+    build(
+        accounts::CreateVesting {
+            payer: *payer,
+            vault_account: *vault,
+            member,
+            global_config: pda::global_config(),
+            vesting: pda::vesting(&member),
+            mint: token.mint,
+            vault_token_account: token.account(vault),
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+            token_program: token.program,
+            system_program: system_program::ID,
+        },
+        args::CreateVesting {
+            start_ts,
+            cliff_ts,
+            duration,
+            revocable,
+        },
+    )
+}
+
+pub fn revoke_vesting(
+    authority: &Authority,
+    vault: &Pubkey,
+    member_wallet: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::RevokeVesting {
+            vault_account: *vault,
+            vesting: pda::vesting(&pda::member(vault, member_wallet)),
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+        },
+        args::RevokeVesting {},
+    )
+}
+
+/// `payer` is the account that paid the schedule rent and gets it back
+pub fn close_vesting(
+    authority: &Authority,
+    vault: &Pubkey,
+    member_wallet: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::CloseVesting {
+            vault_account: *vault,
+            vesting: pda::vesting(&pda::member(vault, member_wallet)),
+            payer: *payer,
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+        },
+        args::CloseVesting {},
+    )
+}
+
+pub fn claim_vested(
+    wallet: &Pubkey,
+    vault: &Pubkey,
+    treasury: &Pubkey,
+    token: &Token,
+) -> Instruction {
+    build(
+        accounts::ClaimVested {
+            wallet: *wallet,
+            vault_account: *vault,
+            vesting: pda::vesting(&pda::member(vault, wallet)),
+            global_config: pda::global_config(),
+            mint: token.mint,
+            vault_token_account: token.account(vault),
+            treasury: *treasury,
+            treasury_token_account: token.account(treasury),
+            wallet_token_account: token.account(wallet),
+            token_program: token.program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        args::ClaimVested {},
+    )
+}
+
+// DELEGATES
+
+pub fn set_delegate(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    vault: &Pubkey,
+    delegate_wallet: &Pubkey,
+    permissions: u8,
+) -> Instruction {
+    build(
+        accounts::SetDelegate {
+            payer: *payer,
+            vault_account: *vault,
+            owner: *owner,
+            delegate: pda::delegate(vault, delegate_wallet),
+            delegate_wallet: *delegate_wallet,
+            system_program: system_program::ID,
+        },
+        args::SetDelegate { permissions },
+    )
+}
+
+pub fn remove_delegate(owner: &Pubkey, vault: &Pubkey, delegate_wallet: &Pubkey) -> Instruction {
+    build(
+        accounts::RemoveDelegate {
+            vault_account: *vault,
+            delegate: pda::delegate(vault, delegate_wallet),
+            owner: *owner,
+        },
+        args::RemoveDelegate {},
+    )
+}
+
+// MULTISIG APPROVALS
+
+pub fn initialize_approvers(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    vault: &Pubkey,
+    approvers: Vec<Pubkey>,
+    threshold: u8,
+) -> Instruction {
+    build(
+        accounts::InitializeApprovers {
+            payer: *payer,
+            vault_account: *vault,
+            approval_config: pda::approval_config(vault),
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        args::InitializeApprovers {
+            approvers,
+            threshold,
+        },
+    )
+}
+
+pub fn propose_approver_change(
+    owner: &Pubkey,
+    vault: &Pubkey,
+    approvers: Vec<Pubkey>,
+    threshold: u8,
+) -> Instruction {
+    build(
+        accounts::ProposeApproverChange {
+            vault_account: *vault,
+            approval_config: pda::approval_config(vault),
+            owner: *owner,
+        },
+        args::ProposeApproverChange {
+            approvers,
+            threshold,
+        },
+    )
+}
+
+pub fn approve_approver_change(approver: &Pubkey, vault: &Pubkey) -> Instruction {
+    build(
+        accounts::ApproveApproverChange {
+            approver: *approver,
+            vault_account: *vault,
+            approval_config: pda::approval_config(vault),
+        },
+        args::ApproveApproverChange {},
+    )
+}
+
+pub fn approve_batch(approver: &Pubkey, vault: &Pubkey, batch_id: u64) -> Instruction {
+    build(
+        accounts::ApproveBatch {
+            approver: *approver,
+            vault_account: *vault,
+            approval_config: pda::approval_config(vault),
+            payroll_batch: pda::payroll_batch(vault, batch_id),
+        },
+        args::ApproveBatch {},
+    )
+}
+
+/// Replaces any pending action; approvers then sign off with `approve_owner_action`
+pub fn propose_owner_action(owner: &Pubkey, vault: &Pubkey, action: OwnerAction) -> Instruction {
+    build(
+        accounts::ProposeOwnerAction {
+            vault_account: *vault,
+            approval_config: pda::approval_config(vault),
+            owner: *owner,
+        },
+        args::ProposeOwnerAction { action },
+    )
+}
+
+pub fn approve_owner_action(approver: &Pubkey, vault: &Pubkey) -> Instruction {
+    build(
+        accounts::ApproveOwnerAction {
+            approver: *approver,
+            vault_account: *vault,
+            approval_config: pda::approval_config(vault),
+        },
+        args::ApproveOwnerAction {},
+    )
+}
+
+pub fn finalize_payroll_batch(authority: &Authority, vault: &Pubkey, batch_id: u64) -> Instruction {
+    build(
+        accounts::FinalizePayrollBatch {
+            vault_account: *vault,
+            payroll_batch: pda::payroll_batch(vault, batch_id),
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+        },
+        args::FinalizePayrollBatch {},
+    )
+}
+
+/// `payer` must be the batch's original rent payer, `PayrollBatch::payer`
+pub fn close_payroll_batch(
+    authority: &Authority,
+    vault: &Pubkey,
+    batch_id: u64,
+    payer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ClosePayrollBatch {
+            vault_account: *vault,
+            payroll_batch: pda::payroll_batch(vault, batch_id),
+            payer: *payer,
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+        },
+        args::ClosePayrollBatch {},
+    )
+}
+
+// AUTOMATED PAYOUTS
+
+/// Permissionless; `keeper` receives the vault's keeper reward
+pub fn process_scheduled_payout(
+    keeper: &Pubkey,
+    vault: &Pubkey,
+    member_wallet: &Pubkey,
+    treasury: &Pubkey,
+    period: i64,
+) -> Instruction {
+    let member = pda::member(vault, member_wallet);
+    build(
+        accounts::ProcessScheduledPayout {
+            vault_account: *vault,
+            member,
+            receipt: pda::period_receipt(vault, period, &member),
+            global_config: pda::global_config(),
+            treasury: *treasury,
+            member_wallet: *member_wallet,
+            keeper: *keeper,
+            system_program: system_program::ID,
+        },
+        args::ProcessScheduledPayout { period },
+    )
+}
+
+// BULK OPERATIONS
+
+pub fn bulk_add_members(
+    payer: &Pubkey,
+    authority: &Authority,
+    vault: &Pubkey,
+    members_data: Vec<MemberData>,
+) -> Instruction {
+    let remaining_accounts = bulk::bulk_add_members_accounts(vault, &members_data);
+    build_with_remaining(
+        accounts::BulkAddMembers {
+            payer: *payer,
+            vault_account: *vault,
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+            system_program: system_program::ID,
+        },
+        args::BulkAddMembers { members_data },
+        remaining_accounts,
+    )
+}
+
+/// `token_program` owns every SPL mint in `payout_data` and is only passed
+/// when there is at least one SPL leg
+pub fn bulk_process_payouts(
+    payer: &Pubkey,
+    authority: &Authority,
+    vault: &Pubkey,
+    batch_id: u64,
+    treasury: &Pubkey,
+    token_program: &Pubkey,
+    payout_data: Vec<PayoutData>,
+) -> Instruction {
+    let has_spl_leg = payout_data
+        .iter()
+        .any(|payout| matches!(payout.asset_type, AssetType::SPLToken { .. }));
+    let remaining_accounts =
+        bulk::bulk_process_payouts_accounts(vault, batch_id, treasury, token_program, &payout_data);
+    build_with_remaining(
+        accounts::BulkProcessPayouts {
+            payer: *payer,
+            vault_account: *vault,
+            payroll_batch: pda::payroll_batch(vault, batch_id),
+            approval_config: pda::approval_config(vault),
+            global_config: pda::global_config(),
+            treasury: *treasury,
+            authority: authority.key(),
+            delegate: authority.delegate(vault),
+            token_program: has_spl_leg.then_some(*token_program),
+            system_program: system_program::ID,
+        },
+        args::BulkProcessPayouts { payout_data },
+        remaining_accounts,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(ix: &Instruction) -> Vec<Pubkey> {
+        ix.accounts.iter().map(|meta| meta.pubkey).collect()
+    }
+
+    #[test]
+    fn owner_authority_leaves_delegate_slot_empty() {
+        let (owner, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let ix = close_stream(
+            &Authority::Owner(owner),
+            &vault,
+            &Pubkey::new_unique(),
+            &owner,
+        );
+
+        // Anchor encodes a missing optional account as the program id, read-only
+        let delegate = &ix.accounts[4];
+        assert_eq!(ix.accounts[3].pubkey, owner);
+        assert!(ix.accounts[3].is_signer);
+        assert_eq!(delegate.pubkey, payermint_p::ID);
+        assert!(!delegate.is_writable && !delegate.is_signer);
+    }
+
+    #[test]
+    fn delegate_authority_passes_its_delegate_pda() {
+        let (wallet, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let ix = close_stream(
+            &Authority::Delegate(wallet),
+            &vault,
+            &Pubkey::new_unique(),
+            &wallet,
+        );
+
+        assert_eq!(ix.accounts[3].pubkey, wallet);
+        assert!(ix.accounts[3].is_signer);
+        assert_eq!(ix.accounts[4].pubkey, pda::delegate(&vault, &wallet));
+        assert!(!ix.accounts[4].is_signer);
+    }
+
+    #[test]
+    fn close_receipt_passes_the_batch_only_for_batch_receipts() {
+        let (payer, vault, wallet) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let member = pda::member(&vault, &wallet);
+        let batch = pda::payroll_batch(&vault, 7);
+
+        let ix = close_receipt(&payer, &vault, &wallet, &PayoutCycle::Batch { batch_id: 7 });
+        assert_eq!(
+            keys(&ix),
+            vec![pda::batch_receipt(&batch, &member), vault, batch, payer]
+        );
+
+        let ix = close_receipt(
+            &payer,
+            &vault,
+            &wallet,
+            &PayoutCycle::Period { start_ts: 1_000 },
+        );
+        assert_eq!(
+            keys(&ix),
+            vec![
+                pda::period_receipt(&vault, 1_000, &member),
+                vault,
+                payermint_p::ID,
+                payer
+            ]
+        );
+        assert!(ix.accounts[3].is_signer && ix.accounts[3].is_writable);
+    }
+
+    #[test]
+    fn spl_withdraw_uses_the_token_program_atas() {
+        let (owner, delegate, vault) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let token = Token {
+            mint: Pubkey::new_unique(),
+            program: anchor_spl::token_2022::ID,
+        };
+
+        let ix = withdraw_spl_token(&Authority::Delegate(delegate), &owner, &vault, &token, 5);
+
+        assert_eq!(
+            keys(&ix),
+            vec![
+                vault,
+                pda::approval_config(&vault),
+                owner,
+                delegate,
+                pda::delegate(&vault, &delegate),
+                token.mint,
+                pda::token_account(&vault, &token.mint, &token.program),
+                pda::token_account(&owner, &token.mint, &token.program),
+                token.program,
+                associated_token::ID,
+                system_program::ID,
+            ]
+        );
+        let signers: Vec<bool> = ix.accounts.iter().map(|meta| meta.is_signer).collect();
+        assert_eq!(
+            signers,
+            vec![false, false, false, true, false, false, false, false, false, false, false]
+        );
+    }
+}
@@ -0,0 +1,11 @@
+//! Rust client for the payermint-p program: PDA derivation, instruction
+//! builders, `remaining_accounts` assembly and account decoding.
+
+pub mod bulk;
+pub mod fetch;
+pub mod instructions;
+pub mod pda;
+
+pub use fetch::{AccountSource, FetchError};
+pub use instructions::{Asset, Authority, Token};
+pub use payermint_p;
@@ -0,0 +1,160 @@
+//! Program-derived addresses, built from the seeds in `payermint_p::constants`.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use payermint_p::claim_code_hash;
+use payermint_p::constants::{
+    APPROVAL_SEED, BATCH_SEED, DELEGATE_SEED, GLOBAL_CONFIG_SEED, MEMBER_SEED, RECEIPT_SEED,
+    REDEMPTION_SEED, STREAM_SEED, VAULT_SEED, VESTING_SEED,
+};
+
+fn find(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &payermint_p::ID).0
+}
+
+pub fn global_config() -> Pubkey {
+    find(&[GLOBAL_CONFIG_SEED])
+}
+
+/// `creator` is the vault's first owner; it stays in the seeds after ownership transfers
+pub fn vault(creator: &Pubkey, vault_id: u64) -> Pubkey {
+    find(&[VAULT_SEED, creator.as_ref(), &vault_id.to_le_bytes()])
+}
+
+pub fn member(vault: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    find(&[MEMBER_SEED, vault.as_ref(), wallet.as_ref()])
+}
+
+pub fn payroll_batch(vault: &Pubkey, batch_id: u64) -> Pubkey {
+    find(&[BATCH_SEED, vault.as_ref(), &batch_id.to_le_bytes()])
+}
+
+/// Receipt of a batch payout or claim allocation; `member` is the member PDA
+pub fn batch_receipt(payroll_batch: &Pubkey, member: &Pubkey) -> Pubkey {
+    find(&[RECEIPT_SEED, payroll_batch.as_ref(), member.as_ref()])
+}
+
+/// Receipt of a scheduled payout for the period starting at `period`
+pub fn period_receipt(vault: &Pubkey, period: i64, member: &Pubkey) -> Pubkey {
+    find(&[
+        RECEIPT_SEED,
+        vault.as_ref(),
+        &period.to_le_bytes(),
+        member.as_ref(),
+    ])
+}
+
+pub fn redemption(vault: &Pubkey, code: &str, claimant: &Pubkey) -> Pubkey {
+    find(&[
+        REDEMPTION_SEED,
+        vault.as_ref(),
+        &claim_code_hash(code, vault),
+        claimant.as_ref(),
+    ])
+}
+
+pub fn stream(member: &Pubkey) -> Pubkey {
+    find(&[STREAM_SEED, member.as_ref()])
+}
+
+pub fn vesting(member: &Pubkey) -> Pubkey {
+    find(&[VESTING_SEED, member.as_ref()])
+}
+
+pub fn approval_config(vault: &Pubkey) -> Pubkey {
+    find(&[APPROVAL_SEED, vault.as_ref()])
+}
+
+pub fn delegate(vault: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    find(&[DELEGATE_SEED, vault.as_ref(), wallet.as_ref()])
+}
+
+/// Associated token account of `owner` for `mint` under `token_program`
+pub fn token_account(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::associated_token;
+
+    /// Derives with the literal seeds of the program's `seeds = [...]` constraints, so a
+    /// renamed constant or reordered seed on either side shows up here
+    fn program_pda(seeds: &[&[u8]]) -> Pubkey {
+        Pubkey::find_program_address(seeds, &payermint_p::ID).0
+    }
+
+    #[test]
+    fn pdas_match_program_seeds() {
+        let (creator, wallet, vault) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let member = member(&vault, &wallet);
+        let batch = payroll_batch(&vault, 3);
+
+        assert_eq!(global_config(), program_pda(&[b"global_config"]));
+        assert_eq!(
+            super::vault(&creator, 9),
+            program_pda(&[b"vault", creator.as_ref(), &9u64.to_le_bytes()])
+        );
+        assert_eq!(
+            member,
+            program_pda(&[b"member", vault.as_ref(), wallet.as_ref()])
+        );
+        assert_eq!(
+            batch,
+            program_pda(&[b"batch", vault.as_ref(), &3u64.to_le_bytes()])
+        );
+        assert_eq!(
+            batch_receipt(&batch, &member),
+            program_pda(&[b"receipt", batch.as_ref(), member.as_ref()])
+        );
+        assert_eq!(
+            period_receipt(&vault, 1_000, &member),
+            program_pda(&[
+                b"receipt",
+                vault.as_ref(),
+                &1_000i64.to_le_bytes(),
+                member.as_ref(),
+            ])
+        );
+        assert_eq!(
+            redemption(&vault, "code", &wallet),
+            program_pda(&[
+                b"redemption",
+                vault.as_ref(),
+                &claim_code_hash("code", &vault),
+                wallet.as_ref(),
+            ])
+        );
+        assert_eq!(stream(&member), program_pda(&[b"stream", member.as_ref()]));
+        assert_eq!(
+            vesting(&member),
+            program_pda(&[b"vesting", member.as_ref()])
+        );
+        assert_eq!(
+            approval_config(&vault),
+            program_pda(&[b"approval", vault.as_ref()])
+        );
+        assert_eq!(
+            delegate(&vault, &wallet),
+            program_pda(&[b"delegate", vault.as_ref(), wallet.as_ref()])
+        );
+    }
+
+    #[test]
+    fn token_accounts_follow_the_token_program() {
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for token_program in [anchor_spl::token::ID, anchor_spl::token_2022::ID] {
+            let expected = Pubkey::find_program_address(
+                &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+                &associated_token::ID,
+            )
+            .0;
+            assert_eq!(token_account(&owner, &mint, &token_program), expected);
+        }
+    }
+}
@@ -2,5 +2,30 @@ pub const GLOBAL_CONFIG_SEED: &[u8] = b"global_config";
 pub const VAULT_SEED: &[u8] = b"vault";
 pub const MEMBER_SEED: &[u8] = b"member";
 pub const BATCH_SEED: &[u8] = b"batch";
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+pub const REDEMPTION_SEED: &[u8] = b"redemption";
+pub const STREAM_SEED: &[u8] = b"stream";
+pub const VESTING_SEED: &[u8] = b"vesting";
+pub const APPROVAL_SEED: &[u8] = b"approval";
+pub const DELEGATE_SEED: &[u8] = b"delegate";
+
+// Delay before a queued treasury or fee change can be applied
+pub const CONFIG_TIMELOCK_SECS: i64 = 2 * 24 * 60 * 60;
+
+// Fee tiers are measured over the payout volume of the last 30 days, kept in daily buckets
+pub const FEE_VOLUME_WINDOW_DAYS: usize = 30;
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+pub const MAX_FEE_TIERS: usize = 4;
+pub const MAX_FEE_LIMITS: usize = 8;
+
+// Bounds `whitelisted_assets` and, through it, `spl_balances`
+pub const MAX_WHITELISTED_ASSETS: usize = 10;
+
+// Longest a finalized batch can be kept before it may be closed
+pub const MAX_BATCH_RETENTION_SECS: i64 = 10 * 365 * SECONDS_PER_DAY;
+
+// Shortest claim code that can be redeemed. The stored hash only salts the code with the
+// public vault key, so a short code can be brute-forced offline from on-chain data.
+pub const MIN_CLAIM_CODE_LEN: usize = 16;
 
 
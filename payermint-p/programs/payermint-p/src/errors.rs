@@ -47,12 +47,186 @@ pub enum ErrorVault {
     #[msg("Total allocation exceeds 100%")]
     TotalAllocationExceeded,
 
-    // #[msg("Member already exists")]
-    // MemberAlreadyExists,
+    #[msg("Missing or unexpected remaining accounts")]
+    InvalidRemainingAccounts,
+
+    #[msg("Member account does not match the expected PDA")]
+    InvalidMemberAccount,
+
+    #[msg("Token account does not match the expected mint or owner")]
+    InvalidTokenAccount,
+
+    #[msg("Token program is required for SPL token payouts")]
+    TokenProgramRequired,
+
+    #[msg("Member already exists")]
+    MemberAlreadyExists,
+
+    #[msg("Duplicate member wallet in batch")]
+    DuplicateMemberWallet,
+
+    #[msg("Payout period does not match the current schedule period")]
+    InvalidPayoutPeriod,
+
+    #[msg("Receipt account does not match the expected PDA")]
+    InvalidReceiptAccount,
+
+    #[msg("Member has already been paid in this cycle")]
+    MemberAlreadyPaid,
+
+    #[msg("Payment has already been claimed")]
+    AlreadyClaimed,
+
+    #[msg("Signer is not the wallet this payment was allocated to")]
+    InvalidClaimant,
+
+    #[msg("Asset does not match the allocated payment")]
+    AssetMismatch,
+
+    #[msg("Invalid claim code")]
+    InvalidClaimCode,
+
+    #[msg("Claim code has expired")]
+    ClaimCodeExpired,
+
+    #[msg("Claim code has no uses left")]
+    ClaimCodeExhausted,
+
+    #[msg("Invalid stream configuration")]
+    InvalidStreamConfig,
+
+    #[msg("Stream is paused")]
+    StreamPaused,
+
+    #[msg("Stream is not paused")]
+    StreamNotPaused,
+
+    #[msg("Stream has been cancelled")]
+    StreamCancelled,
+
+    #[msg("Nothing has vested yet")]
+    NothingToWithdraw,
+
+    #[msg("Invalid vesting configuration")]
+    InvalidVestingConfig,
+
+    #[msg("Vesting schedule is not revocable")]
+    VestingNotRevocable,
+
+    #[msg("Vesting schedule has been revoked")]
+    VestingRevoked,
+
+    #[msg("Transfer would leave the vault below rent exemption")]
+    RentExemptionViolated,
+
+    #[msg("Vault still has members; remove them or force the close")]
+    MembersRemaining,
+
+    #[msg("Vault still has payroll batches that are not finalized")]
+    OpenBatchesRemaining,
+
+    #[msg("Vault still has funds reserved for members")]
+    OutstandingReservations,
+
+    #[msg("Every whitelisted or tracked mint must be swept before closing the vault")]
+    TokenAccountNotSwept,
+
+    #[msg("Payroll batch is not finalized")]
+    BatchNotFinalized,
+
+    #[msg("Batch retention period has not elapsed")]
+    RetentionPeriodActive,
+
+    #[msg("Retention period must be between zero and ten years")]
+    InvalidRetentionPeriod,
+
+    #[msg("Rent receiver does not match the batch payer")]
+    InvalidRentReceiver,
+
+    #[msg("Payout would exceed the batch total amount")]
+    BatchCapExceeded,
+
+    #[msg("Signer is not the pending vault owner")]
+    NotPendingOwner,
+
+    #[msg("Config change timelock has not elapsed")]
+    TimelockNotElapsed,
+
+    #[msg("No config change is pending")]
+    NoPendingChange,
+
+    #[msg("Invalid approver set or threshold")]
+    InvalidApproverConfig,
+
+    #[msg("Signer is not an approver for this vault")]
+    NotAnApprover,
+
+    #[msg("Approver has already approved")]
+    AlreadyApproved,
+
+    #[msg("Payroll batch does not have enough approvals")]
+    InsufficientApprovals,
+
+    #[msg("Signer is not the vault owner or a delegate with this permission")]
+    Unauthorized,
+
+    #[msg("Invalid delegate permissions")]
+    InvalidDelegatePermissions,
+
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+
+    #[msg("Vault is paused")]
+    VaultPaused,
+
+    #[msg("Fee tiers must be ascending by volume and at most 10000 bps")]
+    InvalidFeeTiers,
+
+    #[msg("Invalid fee limit")]
+    InvalidFeeLimit,
+
+    #[msg("Arithmetic overflow or underflow")]
+    MathOverflow,
+
+    #[msg("Payout schedule interval must be positive")]
+    InvalidPayoutSchedule,
+
+    #[msg("Vault already has the maximum number of whitelisted assets")]
+    TooManyWhitelistedAssets,
+
+    #[msg("Asset still has a tracked balance or reservation in the vault")]
+    AssetHasBalance,
+
+    #[msg("Stream still has funds to vest or withdraw")]
+    StreamNotSettled,
+
+    #[msg("Vesting schedule still has tokens to vest or claim")]
+    VestingNotSettled,
+
+    #[msg("Vault still has payroll batches, streams, vesting schedules or delegates; close them first")]
+    VaultAccountsRemaining,
+
+    #[msg("A payroll batch can only pay out one asset")]
+    BatchAssetMismatch,
+
+    #[msg("Claim code is shorter than the minimum length")]
+    ClaimCodeTooShort,
+
+    #[msg("Schedule would reopen a period that was already paid")]
+    PeriodAlreadyPaid,
+
+    #[msg("Receipt is still waiting to be claimed")]
+    ReceiptNotClaimed,
+
+    #[msg("The receipt's batch or period is still open")]
+    PayoutCycleOpen,
+
+    #[msg("Claim code can still be redeemed")]
+    ClaimCodeStillRedeemable,
+
+    #[msg("Approvers have not signed off on this operation")]
+    OwnerActionNotApproved,
 
     // #[msg("Invalid vault type")]
     // InvalidVaultType,
-
-    // #[msg("Unauthorized access")]
-    // Unauthorized,
 }
@@ -0,0 +1,417 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{
+    AssetType, ClaimCode, FeeLimit, FeeMode, FeeTier, OwnerAction, PayoutCycle, PayoutSchedule,
+    SplTokenBalance,
+};
+
+/// Flow that produced a `PayoutExecuted`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum PayoutSource {
+    Batch,
+    Scheduled,
+    Claim, // Pulled from an `allocate_claim` receipt
+    ClaimCode,
+    Stream,
+    Vesting,
+}
+
+/// Change made to an existing stream
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum StreamAction {
+    TopUp,
+    Pause,
+    Resume,
+    Cancel,
+    Close,
+}
+
+/// Vault setting changed by its owner
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum VaultSetting {
+    FeeMode { fee_mode: FeeMode },
+    KeeperReward { keeper_reward: u64 },
+    ClaimCode { code_claim: Option<ClaimCode> }, // `None` disables code claims
+    BatchRetention { batch_retention_secs: i64 },
+    AssetWhitelisted { asset: AssetType },
+    AssetRemoved { asset: AssetType },
+}
+
+/// Timelocked change to the global config
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ConfigChange {
+    Treasury { treasury: Pubkey },
+    DefaultFee { fee_bps: u16 },
+    FeeLimit { fee_limit: FeeLimit },
+}
+
+#[event]
+pub struct VaultCreated {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub vault_id: u64,
+    pub name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemberAdded {
+    pub vault: Pubkey,
+    pub member: Pubkey,
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemberRemoved {
+    pub vault: Pubkey,
+    pub member: Pubkey,
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepositMade {
+    pub vault: Pubkey,
+    pub depositor: Pubkey,
+    pub asset_type: AssetType,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalMade {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub asset_type: AssetType,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchCreated {
+    pub vault: Pubkey,
+    pub batch_id: u64,
+    pub total_amount: u64,
+    pub estimated_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchFinalized {
+    pub vault: Pubkey,
+    pub batch_id: u64,
+    pub paid_amount: u64,
+    pub estimated_fee: u64, // Estimated when the batch was created
+    pub fees_collected: u64,
+    pub fee_delta: i64, // `fees_collected - estimated_fee`
+    pub payout_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PayoutExecuted {
+    pub vault: Pubkey,
+    pub recipient: Pubkey, // Wallet that received `net_amount`
+    pub source: PayoutSource,
+    pub asset_type: AssetType,
+    pub amount: u64, // Gross amount before fees
+    pub service_fee: u64,
+    pub net_amount: u64,
+    pub batch_id: Option<u64>, // Set for payouts made against a payroll batch
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeCollected {
+    pub vault: Pubkey,
+    pub asset_type: AssetType,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScheduleAdvanced {
+    pub vault: Pubkey,
+    pub paid_period: i64, // Start of the period that was closed
+    pub next_payout_ts: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimAllocated {
+    pub vault: Pubkey,
+    pub member: Pubkey,
+    pub wallet: Pubkey,
+    pub batch_id: u64,
+    pub asset_type: AssetType,
+    pub amount: u64,
+    pub service_fee: u64,
+    pub net_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreamCreated {
+    pub vault: Pubkey,
+    pub stream: Pubkey,
+    pub wallet: Pubkey,
+    pub asset_type: AssetType,
+    pub rate_per_second: u64,
+    pub start_ts: i64,
+    pub end_ts: Option<i64>,
+    pub deposit: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreamUpdated {
+    pub vault: Pubkey,
+    pub stream: Pubkey,
+    pub action: StreamAction,
+    pub amount: u64, // Added by a top up or released by a cancel, otherwise 0
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingCreated {
+    pub vault: Pubkey,
+    pub vesting: Pubkey,
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    pub total_amount: u64,
+    pub start_ts: i64,
+    pub cliff_ts: i64,
+    pub duration: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingRevoked {
+    pub vault: Pubkey,
+    pub vesting: Pubkey,
+    pub wallet: Pubkey,
+    pub vested: u64,
+    pub released: u64, // Returned to the vault's spendable balance
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingClosed {
+    pub vault: Pubkey,
+    pub vesting: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BalancesSynced {
+    pub vault: Pubkey,
+    pub sol_balance: u64,
+    pub spl_balances: Vec<SplTokenBalance>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultClosed {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub forced: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchClosed {
+    pub vault: Pubkey,
+    pub batch_id: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimCancelled {
+    pub vault: Pubkey,
+    pub member: Pubkey,
+    pub wallet: Pubkey,
+    pub batch_id: Option<u64>,
+    pub asset_type: AssetType,
+    pub released: u64, // Reservation returned to the vault's spendable balance
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReceiptClosed {
+    pub vault: Pubkey,
+    pub member: Pubkey,
+    pub cycle: PayoutCycle,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RedemptionClosed {
+    pub vault: Pubkey,
+    pub claimant: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegateSet {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub permissions: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegateRemoved {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ApproverChangeProposed {
+    pub vault: Pubkey,
+    pub approvers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnerActionProposed {
+    pub vault: Pubkey,
+    pub action: OwnerAction,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnerActionApproved {
+    pub vault: Pubkey,
+    pub approver: Pubkey,
+    pub approvals: u16, // Bitmap over the vault's approvers
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ApproversUpdated {
+    pub vault: Pubkey,
+    pub approvers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub version: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchApproved {
+    pub vault: Pubkey,
+    pub batch_id: u64,
+    pub approver: Pubkey,
+    pub approvals: u16, // Bitmap over the vault's approvers
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultOwnerProposed {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub pending_owner: Option<Pubkey>, // `None` when the proposal was cancelled
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultOwnerAccepted {
+    pub vault: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultPaused {
+    pub vault: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultSettingUpdated {
+    pub vault: Pubkey,
+    pub setting: VaultSetting,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PayoutScheduleUpdated {
+    pub vault: Pubkey,
+    pub schedule: Option<PayoutSchedule>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemberUpdated {
+    pub vault: Pubkey,
+    pub member: Pubkey,
+    pub allocation_bps: Option<u16>,
+    pub sol_payment_allocation: Option<u64>,
+    pub spl_token_allocation: Option<u64>,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GlobalConfigInitialized {
+    pub owner: Pubkey,
+    pub treasury: Pubkey,
+    pub default_fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigChangeQueued {
+    pub change: ConfigChange,
+    pub eta: i64, // Earliest time `apply_config_change` takes it
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigChangeApplied {
+    pub change: ConfigChange,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigChangeCancelled {
+    pub change: ConfigChange,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigOwnerProposed {
+    pub owner: Pubkey,
+    pub pending_owner: Option<Pubkey>, // `None` when the proposal was cancelled
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigOwnerAccepted {
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolPaused {
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeTiersUpdated {
+    pub fee_tiers: Vec<FeeTier>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeOverrideSet {
+    pub vault: Pubkey,
+    pub fee_override_bps: Option<u16>,
+    pub timestamp: i64,
+}
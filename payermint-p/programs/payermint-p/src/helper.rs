@@ -1,7 +1,14 @@
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::system_program;
+use anchor_spl::token_interface::TokenAccount;
 
-use crate::AssetType;
+use crate::constants::MIN_CLAIM_CODE_LEN;
+use crate::errors::ErrorVault;
+use crate::events::{FeeCollected, PayoutExecuted};
+use crate::math::{checked_add, checked_sub, fee_for, increment, split_fee, FeeSplit};
+use crate::{AssetType, ClaimCode, GlobalConfig, VaultAccount, VaultDelegate};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct MemberData {
@@ -13,9 +20,363 @@ pub struct MemberData {
     pub metadata_uri: Option<String>,
 }
 
+/// Shared validation for `add_member` and `bulk_add_members`
+pub fn validate_member_fields(
+    role: &str,
+    allocation_bps: Option<u16>,
+    metadata_uri: &Option<String>,
+) -> Result<()> {
+    require!(role.len() <= 16, ErrorVault::RoleTooLong);
+    if let Some(ref uri) = metadata_uri {
+        require!(uri.len() <= 200, ErrorVault::MetadataUriTooLong);
+    }
+    if let Some(bps) = allocation_bps {
+        require!(bps <= 10000, ErrorVault::InvalidAllocationBps);
+    }
+    Ok(())
+}
+
+/// Creates a program-owned PDA passed through `remaining_accounts`, mirroring
+/// Anchor's `init` (a pre-funded address is topped up instead of failing).
+pub fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = target.lamports();
+    if current_lamports == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+                signer_seeds,
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        let top_up = rent.saturating_sub(current_lamports);
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: payer.clone(),
+                        to: target.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Allocate {
+                    account_to_allocate: target.clone(),
+                },
+                signer_seeds,
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Assign {
+                    account_to_assign: target.clone(),
+                },
+                signer_seeds,
+            ),
+            &crate::ID,
+        )?;
+    }
+    Ok(())
+}
+
+/// Reserves `amount` so other payouts cannot spend it. SPL reservations need the
+/// vault token account of the reserved mint to check the unreserved balance.
+pub fn reserve_funds(
+    vault_account: &mut VaultAccount,
+    asset_type: &AssetType,
+    amount: u64,
+    vault_token_account: Option<&TokenAccount>,
+) -> Result<()> {
+    match asset_type {
+        AssetType::SOL => {
+            let required_balance = checked_add(vault_account.required_balance, amount)?;
+            require!(
+                vault_account.total_balance >= required_balance,
+                ErrorVault::InsufficientVaultBalance
+            );
+            vault_account.required_balance = required_balance;
+        }
+        AssetType::SPLToken { mint } => {
+            require!(
+                vault_account.whitelisted_assets.contains(asset_type),
+                ErrorVault::AssetNotWhitelisted
+            );
+            let vault_token_account = vault_token_account.ok_or(ErrorVault::InvalidTokenAccount)?;
+            require_keys_eq!(
+                vault_token_account.mint,
+                *mint,
+                ErrorVault::InvalidTokenAccount
+            );
+            let reserved = checked_add(vault_account.spl_reserved(mint), amount)?;
+            require!(
+                vault_token_account.amount >= reserved,
+                ErrorVault::InsufficientVaultBalance
+            );
+            vault_account.reserve_spl(mint, amount)?;
+        }
+    }
+    Ok(())
+}
+
+/// Releases a reservation made with `reserve_funds`
+pub fn release_funds(
+    vault_account: &mut VaultAccount,
+    asset_type: &AssetType,
+    amount: u64,
+) -> Result<()> {
+    match asset_type {
+        AssetType::SOL => {
+            vault_account.required_balance = checked_sub(vault_account.required_balance, amount)?
+        }
+        AssetType::SPLToken { mint } => vault_account.release_spl(mint, amount)?,
+    }
+    Ok(())
+}
+
+/// Moves lamports out of a program-owned account, checked on both sides. `from`
+/// must keep the rent-exempt minimum for its current data length.
+pub fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(from.data_len());
+    require!(
+        from.lamports() >= checked_add(amount, rent_exempt_minimum)?,
+        ErrorVault::RentExemptionViolated
+    );
+    let from_lamports = checked_sub(from.lamports(), amount)?;
+    let to_lamports = checked_add(to.lamports(), amount)?;
+    **from.try_borrow_mut_lamports()? = from_lamports;
+    **to.try_borrow_mut_lamports()? = to_lamports;
+    Ok(())
+}
+
+/// Salted hash stored for claim codes: `sha256(code || vault)`
+pub fn claim_code_hash(code: &str, vault: &Pubkey) -> [u8; 32] {
+    hashv(&[code.as_bytes(), vault.as_ref()]).to_bytes()
+}
+
+/// Checks a claim code before it is stored on a vault
+pub fn validate_claim_code(claim_code: &ClaimCode, now: i64) -> Result<()> {
+    require!(
+        claim_code.amount > 0 && claim_code.max_uses > 0,
+        ErrorVault::InvalidClaimCode
+    );
+    if let Some(expires_at) = claim_code.expires_at {
+        require!(expires_at > now, ErrorVault::ClaimCodeExpired);
+    }
+    Ok(())
+}
+
+/// Verifies a revealed code against the vault's stored hash and consumes one use. Only the
+/// hash is known when a code is set, so the minimum length is enforced here instead.
+pub fn redeem_claim_code(
+    code_claim: &mut Option<ClaimCode>,
+    code: &str,
+    vault: &Pubkey,
+    now: i64,
+) -> Result<ClaimCode> {
+    require!(
+        code.len() >= MIN_CLAIM_CODE_LEN,
+        ErrorVault::ClaimCodeTooShort
+    );
+    let claim_code = code_claim.as_mut().ok_or(ErrorVault::InvalidClaimCode)?;
+    require!(
+        claim_code.code_hash == claim_code_hash(code, vault),
+        ErrorVault::InvalidClaimCode
+    );
+    if let Some(expires_at) = claim_code.expires_at {
+        require!(now < expires_at, ErrorVault::ClaimCodeExpired);
+    }
+    require!(
+        claim_code.uses < claim_code.max_uses,
+        ErrorVault::ClaimCodeExhausted
+    );
+    claim_code.uses = increment(claim_code.uses, 1)?;
+    Ok(claim_code.clone())
+}
+
+/// The vault owner can do anything; a delegate needs `permission`, granted by the current owner
+pub fn is_authorized(
+    vault_account: &VaultAccount,
+    authority: &Pubkey,
+    delegate: Option<&VaultDelegate>,
+    permission: u8,
+) -> bool {
+    if *authority == vault_account.owner {
+        return true;
+    }
+    delegate.is_some_and(|delegate| {
+        delegate.granted_by == vault_account.owner && delegate.has_permission(permission)
+    })
+}
+
+/// Fee rate for a payout from `vault_account`: the lowest of the default rate, the vault's
+/// override and any volume tier it has reached. Overrides and tiers only ever discount,
+/// so they can apply immediately without the config timelock.
+pub fn effective_fee_bps(
+    global_config: &GlobalConfig,
+    vault_account: &VaultAccount,
+) -> Result<u16> {
+    let mut fee_bps = global_config.default_fee_bps;
+    if let Some(override_bps) = vault_account.fee_override_bps {
+        fee_bps = fee_bps.min(override_bps);
+    }
+
+    let volume = vault_account.sol_volume_at(Clock::get()?.unix_timestamp);
+    for tier in &global_config.fee_tiers {
+        if volume >= tier.min_volume {
+            fee_bps = fee_bps.min(tier.fee_bps);
+        }
+    }
+
+    Ok(fee_bps)
+}
+
+/// Service fee for one payout at the effective rate, clamped to the asset's fee limit
+pub fn calculate_service_fee(
+    global_config: &GlobalConfig,
+    vault_account: &VaultAccount,
+    asset_type: &AssetType,
+    amount: u64,
+) -> Result<u64> {
+    let fee_bps = effective_fee_bps(global_config, vault_account)?;
+    fee_for(amount, fee_bps, global_config.fee_limit(asset_type))
+}
+
+/// Splits a push payout of `amount` according to the vault's `FeeMode`
+pub fn split_payout(
+    global_config: &GlobalConfig,
+    vault_account: &VaultAccount,
+    asset_type: &AssetType,
+    amount: u64,
+) -> Result<FeeSplit> {
+    let service_fee = calculate_service_fee(global_config, vault_account, asset_type, amount)?;
+    split_fee(amount, service_fee, vault_account.fee_mode)
+}
+
+/// Emits `PayoutExecuted`, preceded by `FeeCollected` when a fee was charged
+pub fn emit_payout(payout: PayoutExecuted) {
+    if payout.service_fee > 0 {
+        emit!(FeeCollected {
+            vault: payout.vault,
+            asset_type: payout.asset_type.clone(),
+            amount: payout.service_fee,
+            timestamp: payout.timestamp,
+        });
+    }
+    emit!(payout);
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct PayoutData {
     pub member: Pubkey,
     pub amount: u64,
     pub asset_type: AssetType,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+
+    struct RentStubs;
+
+    impl SyscallStubs for RentStubs {
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            0
+        }
+    }
+
+    const DATA_LEN: usize = 64;
+
+    fn debit(lamports: u64, amount: u64) -> (Result<()>, u64, u64) {
+        set_syscall_stubs(Box::new(RentStubs));
+        let (from_key, to_key, owner) = (Pubkey::new_unique(), Pubkey::new_unique(), crate::ID);
+        let (mut from_lamports, mut to_lamports) = (lamports, 0);
+        let (mut from_data, mut to_data) = (vec![0u8; DATA_LEN], vec![]);
+        let from = AccountInfo::new(
+            &from_key,
+            false,
+            true,
+            &mut from_lamports,
+            &mut from_data,
+            &owner,
+            false,
+            0,
+        );
+        let to = AccountInfo::new(
+            &to_key,
+            false,
+            true,
+            &mut to_lamports,
+            &mut to_data,
+            &owner,
+            false,
+            0,
+        );
+        let result = transfer_lamports(&from, &to, amount);
+        (result, from.lamports(), to.lamports())
+    }
+
+    #[test]
+    fn debit_down_to_rent_minimum_succeeds() {
+        let minimum = Rent::default().minimum_balance(DATA_LEN);
+        let (result, from, to) = debit(minimum + 1_000, 1_000);
+        assert!(result.is_ok());
+        assert_eq!(from, minimum);
+        assert_eq!(to, 1_000);
+    }
+
+    #[test]
+    fn debit_below_rent_minimum_is_rejected() {
+        let minimum = Rent::default().minimum_balance(DATA_LEN);
+        let (result, from, to) = debit(minimum + 1_000, 1_001);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorVault::RentExemptionViolated.into()
+        );
+        assert_eq!(from, minimum + 1_000);
+        assert_eq!(to, 0);
+    }
+
+    #[test]
+    fn claim_codes_below_minimum_length_are_rejected() {
+        let vault = Pubkey::new_unique();
+        let code = "x".repeat(MIN_CLAIM_CODE_LEN);
+        let mut code_claim = Some(ClaimCode {
+            code_hash: claim_code_hash(&code, &vault),
+            amount: 1,
+            asset_type: AssetType::SOL,
+            expires_at: None,
+            max_uses: 2,
+            uses: 0,
+        });
+
+        let short = &code[1..];
+        assert_eq!(
+            redeem_claim_code(&mut code_claim, short, &vault, 0).err(),
+            Some(ErrorVault::ClaimCodeTooShort.into())
+        );
+        assert!(redeem_claim_code(&mut code_claim, &code, &vault, 0).is_ok());
+        assert_eq!(code_claim.unwrap().uses, 1);
+    }
+}
@@ -0,0 +1,294 @@
+use crate::constants::APPROVAL_SEED;
+use crate::errors::ErrorVault;
+use crate::events::{
+    ApproverChangeProposed, ApproversUpdated, BatchApproved, OwnerActionApproved,
+    OwnerActionProposed,
+};
+use crate::math::increment;
+use crate::state::{
+    ApprovalConfig, ApproverChange, OwnerAction, PayrollBatch, VaultAccount, MAX_APPROVERS,
+};
+use anchor_lang::prelude::*;
+
+fn validate_approvers(approvers: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !approvers.is_empty() && approvers.len() <= MAX_APPROVERS,
+        ErrorVault::InvalidApproverConfig
+    );
+    require!(
+        threshold > 0 && threshold as usize <= approvers.len(),
+        ErrorVault::InvalidApproverConfig
+    );
+    for (index, approver) in approvers.iter().enumerate() {
+        require!(
+            !approvers[..index].contains(approver),
+            ErrorVault::InvalidApproverConfig
+        );
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeApprovers<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(has_one = owner)]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ApprovalConfig::INIT_SPACE,
+        seeds = [APPROVAL_SEED, vault_account.key().as_ref()],
+        bump
+    )]
+    pub approval_config: Account<'info, ApprovalConfig>,
+
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeApprovers<'info> {
+    /// Sets the first approver set; later changes go through `propose_approver_change`
+    pub fn initialize(&mut self, approvers: Vec<Pubkey>, threshold: u8, bump: u8) -> Result<()> {
+        validate_approvers(&approvers, threshold)?;
+
+        self.approval_config.set_inner(ApprovalConfig {
+            vault: self.vault_account.key(),
+            approvers: approvers.clone(),
+            threshold,
+            version: 1,
+            pending_change: None,
+            change_approvals: 0,
+            bump,
+            pending_action: None,
+            action_approvals: 0,
+        });
+
+        emit!(ApproversUpdated {
+            vault: self.vault_account.key(),
+            approvers,
+            threshold,
+            version: 1,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ProposeApproverChange<'info> {
+    #[account(has_one = owner)]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        seeds = [APPROVAL_SEED, vault_account.key().as_ref()],
+        bump = approval_config.bump
+    )]
+    pub approval_config: Account<'info, ApprovalConfig>,
+
+    pub owner: Signer<'info>,
+}
+
+impl<'info> ProposeApproverChange<'info> {
+    /// Replaces any pending change; the current approvers must approve it
+    pub fn propose(&mut self, approvers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        validate_approvers(&approvers, threshold)?;
+
+        self.approval_config.pending_change = Some(ApproverChange {
+            approvers: approvers.clone(),
+            threshold,
+        });
+        self.approval_config.change_approvals = 0;
+
+        emit!(ApproverChangeProposed {
+            vault: self.vault_account.key(),
+            approvers,
+            threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ApproveApproverChange<'info> {
+    pub approver: Signer<'info>,
+
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        seeds = [APPROVAL_SEED, vault_account.key().as_ref()],
+        bump = approval_config.bump
+    )]
+    pub approval_config: Account<'info, ApprovalConfig>,
+}
+
+impl<'info> ApproveApproverChange<'info> {
+    /// Records the approval and applies the change once the current threshold is met
+    pub fn approve(&mut self) -> Result<()> {
+        let config = &mut self.approval_config;
+        let change = config
+            .pending_change
+            .clone()
+            .ok_or(ErrorVault::NoPendingChange)?;
+
+        let index = config
+            .approver_index(&self.approver.key())
+            .ok_or(ErrorVault::NotAnApprover)?;
+        let bit = 1u16 << index;
+        require!(
+            config.change_approvals & bit == 0,
+            ErrorVault::AlreadyApproved
+        );
+        config.change_approvals |= bit;
+
+        if config.threshold_met(config.change_approvals) {
+            config.approvers = change.approvers;
+            config.threshold = change.threshold;
+            config.version = increment(config.version, 1)?;
+            config.pending_change = None;
+            config.change_approvals = 0;
+            // Approvals of a pending owner action were given by the old set
+            config.action_approvals = 0;
+
+            emit!(ApproversUpdated {
+                vault: self.vault_account.key(),
+                approvers: config.approvers.clone(),
+                threshold: config.threshold,
+                version: config.version,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ApproveBatch<'info> {
+    pub approver: Signer<'info>,
+
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        seeds = [APPROVAL_SEED, vault_account.key().as_ref()],
+        bump = approval_config.bump
+    )]
+    pub approval_config: Account<'info, ApprovalConfig>,
+
+    #[account(
+        mut,
+        constraint = payroll_batch.vault == vault_account.key(),
+        constraint = !payroll_batch.finalized @ ErrorVault::BatchAlreadyFinalized
+    )]
+    pub payroll_batch: Account<'info, PayrollBatch>,
+}
+
+impl<'info> ApproveBatch<'info> {
+    pub fn approve(&mut self) -> Result<()> {
+        let index = self
+            .approval_config
+            .approver_index(&self.approver.key())
+            .ok_or(ErrorVault::NotAnApprover)?;
+
+        // Approvals given under a previous approver set no longer count
+        if self.payroll_batch.approvals_version != self.approval_config.version {
+            self.payroll_batch.approvals = 0;
+            self.payroll_batch.approvals_version = self.approval_config.version;
+        }
+
+        let bit = 1u16 << index;
+        require!(
+            self.payroll_batch.approvals & bit == 0,
+            ErrorVault::AlreadyApproved
+        );
+        self.payroll_batch.approvals |= bit;
+
+        emit!(BatchApproved {
+            vault: self.vault_account.key(),
+            batch_id: self.payroll_batch.batch_id,
+            approver: self.approver.key(),
+            approvals: self.payroll_batch.approvals,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ProposeOwnerAction<'info> {
+    #[account(has_one = owner)]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        seeds = [APPROVAL_SEED, vault_account.key().as_ref()],
+        bump = approval_config.bump
+    )]
+    pub approval_config: Account<'info, ApprovalConfig>,
+
+    pub owner: Signer<'info>,
+}
+
+impl<'info> ProposeOwnerAction<'info> {
+    /// Replaces any pending action; the owner runs it once the threshold is met
+    pub fn propose(&mut self, action: OwnerAction) -> Result<()> {
+        self.approval_config.pending_action = Some(action.clone());
+        self.approval_config.action_approvals = 0;
+
+        emit!(OwnerActionProposed {
+            vault: self.vault_account.key(),
+            action,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ApproveOwnerAction<'info> {
+    pub approver: Signer<'info>,
+
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        seeds = [APPROVAL_SEED, vault_account.key().as_ref()],
+        bump = approval_config.bump
+    )]
+    pub approval_config: Account<'info, ApprovalConfig>,
+}
+
+impl<'info> ApproveOwnerAction<'info> {
+    pub fn approve(&mut self) -> Result<()> {
+        let config = &mut self.approval_config;
+        require!(config.pending_action.is_some(), ErrorVault::NoPendingChange);
+
+        let index = config
+            .approver_index(&self.approver.key())
+            .ok_or(ErrorVault::NotAnApprover)?;
+        let bit = 1u16 << index;
+        require!(
+            config.action_approvals & bit == 0,
+            ErrorVault::AlreadyApproved
+        );
+        config.action_approvals |= bit;
+
+        emit!(OwnerActionApproved {
+            vault: self.vault_account.key(),
+            approver: self.approver.key(),
+            approvals: config.action_approvals,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
@@ -1,25 +1,58 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
 
 use crate::errors::ErrorVault;
-use crate::state::{GlobalConfig, Member, PayrollBatch, VaultAccount};
+use crate::events::{MemberAdded, PayoutExecuted, PayoutSource};
+use crate::helper::{
+    create_pda_account, emit_payout, is_authorized, split_payout, transfer_lamports,
+    validate_member_fields,
+};
+use crate::math::{checked_add, increment, FeeSplit};
+use crate::state::{
+    AssetType, GlobalConfig, Member, PayoutCycle, PayoutReceipt, PayrollBatch, VaultAccount,
+    VaultDelegate,
+};
 use crate::{MemberData, PayoutData};
 
-use crate::constants::GLOBAL_CONFIG_SEED;
+use crate::constants::{
+    APPROVAL_SEED, DELEGATE_SEED, GLOBAL_CONFIG_SEED, MEMBER_SEED, RECEIPT_SEED, VAULT_SEED,
+};
 
 #[derive(Accounts)]
 pub struct BulkAddMembers<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    #[account(mut, has_one = owner)]
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::MANAGE_MEMBERS)
+            @ ErrorVault::Unauthorized
+    )]
     pub vault_account: Account<'info, VaultAccount>,
 
-    pub owner: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> BulkAddMembers<'info> {
-    pub fn bulk_add(&mut self, members_data: Vec<MemberData>) -> Result<()> {
+    /// Creates one `Member` per entry of `members_data`. `remaining_accounts` must
+    /// hold the uninitialized `[MEMBER_SEED, vault, wallet]` PDA (mut) of each entry,
+    /// in the same order. The failing entry index is logged on error.
+    pub fn bulk_add(
+        &mut self,
+        members_data: Vec<MemberData>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
         // Validate total allocation doesn't exceed 100%
         let total_allocation: u32 = members_data
             .iter()
@@ -30,24 +63,116 @@ impl<'info> BulkAddMembers<'info> {
             total_allocation <= 10000,
             ErrorVault::TotalAllocationExceeded
         );
+        require!(
+            remaining_accounts.len() == members_data.len(),
+            ErrorVault::InvalidRemainingAccounts
+        );
 
-        for member_data in members_data {
-            require!(member_data.role.len() <= 16, ErrorVault::RoleTooLong);
-            if let Some(ref uri) = member_data.metadata_uri {
-                require!(uri.len() <= 200, ErrorVault::MetadataUriTooLong);
-            }
-            if let Some(bps) = member_data.allocation_bps {
-                require!(bps <= 10000, ErrorVault::InvalidAllocationBps);
+        for (index, member_data) in members_data.iter().enumerate() {
+            if members_data[..index]
+                .iter()
+                .any(|m| m.wallet == member_data.wallet)
+            {
+                msg!("bulk_add_members: duplicate wallet at index {}", index);
+                return err!(ErrorVault::DuplicateMemberWallet);
             }
         }
 
+        let member_count = members_data.len() as u32;
+        for (index, (member_data, member_info)) in members_data
+            .into_iter()
+            .zip(remaining_accounts.iter())
+            .enumerate()
+        {
+            self.create_member(member_data, member_info)
+                .inspect_err(|_| msg!("bulk_add_members: failed at index {}", index))?;
+        }
+
+        self.vault_account.active_member_count =
+            increment(self.vault_account.active_member_count, member_count)?;
+        self.vault_account.member_count = increment(self.vault_account.member_count, member_count)?;
+
+        Ok(())
+    }
+
+    fn create_member(
+        &self,
+        member_data: MemberData,
+        member_info: &'info AccountInfo<'info>,
+    ) -> Result<()> {
+        validate_member_fields(
+            &member_data.role,
+            member_data.allocation_bps,
+            &member_data.metadata_uri,
+        )?;
+
+        let vault_key = self.vault_account.key();
+        let (expected, bump) = Pubkey::find_program_address(
+            &[MEMBER_SEED, vault_key.as_ref(), member_data.wallet.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            member_info.key(),
+            expected,
+            ErrorVault::InvalidMemberAccount
+        );
+        require!(
+            member_info.data_is_empty() && member_info.owner == &system_program::ID,
+            ErrorVault::MemberAlreadyExists
+        );
+
+        let seeds = &[
+            MEMBER_SEED,
+            vault_key.as_ref(),
+            member_data.wallet.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        create_pda_account(
+            &self.payer.to_account_info(),
+            member_info,
+            &self.system_program.to_account_info(),
+            Member::SPACE,
+            signer_seeds,
+        )?;
+
+        let member = Member {
+            vault: vault_key,
+            wallet: member_data.wallet,
+            allocation_bps: member_data.allocation_bps,
+            sol_payment_allocation: member_data.sol_payment_allocation,
+            spl_token_allocation: member_data.spl_token_allocation,
+            role: member_data.role,
+            is_active: true,
+            metadata_uri: member_data.metadata_uri,
+            bump,
+        };
+        let mut data = member_info.try_borrow_mut_data()?;
+        member.try_serialize(&mut &mut data[..])?;
+
+        emit!(MemberAdded {
+            vault: vault_key,
+            member: member_info.key(),
+            wallet: member.wallet,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
         Ok(())
     }
 }
 
 #[derive(Accounts)]
 pub struct BulkProcessPayouts<'info> {
-    #[account(mut, has_one = owner)]
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::EXECUTE_PAYOUTS)
+            @ ErrorVault::Unauthorized,
+        constraint = !vault_account.paused @ ErrorVault::VaultPaused
+    )]
     pub vault_account: Account<'info, VaultAccount>,
 
     #[account(
@@ -57,10 +182,15 @@ pub struct BulkProcessPayouts<'info> {
     )]
     pub payroll_batch: Account<'info, PayrollBatch>,
 
+    /// CHECK: The vault's approval config PDA; uninitialized while the vault has no approvers
+    #[account(seeds = [APPROVAL_SEED, vault_account.key().as_ref()], bump)]
+    pub approval_config: UncheckedAccount<'info>,
+
     #[account(
         mut,
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
@@ -71,40 +201,354 @@ pub struct BulkProcessPayouts<'info> {
     )]
     pub treasury: AccountInfo<'info>,
 
-    pub owner: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+
+    /// Only required when the batch contains SPL token payouts
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> BulkProcessPayouts<'info> {
-    pub fn bulk_process(&mut self, payout_data: Vec<PayoutData>) -> Result<()> {
-        let mut total_amount = 0u64;
-        let mut total_service_fee = 0u64;
+    /// Pays every entry of `payout_data` in order. `remaining_accounts` must hold,
+    /// per entry:
+    /// - SOL: `[member, receipt (mut), member_wallet (mut)]`
+    /// - SPL: `[member, receipt (mut), mint, vault_token_account (mut),
+    ///   member_token_account (mut), treasury_token_account (mut)]`
+    ///
+    /// `PayoutData.member` is the member wallet; the member account must be the
+    /// `[MEMBER_SEED, vault, wallet]` PDA and the receipt the uninitialized
+    /// `[RECEIPT_SEED, payroll_batch, member]` PDA. Any failing leg aborts the whole batch.
+    pub fn bulk_process(
+        &mut self,
+        payout_data: Vec<PayoutData>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        let mut total_sol_amount = 0u64;
+        let mut total_sol_cost = 0u64;
+        let mut total_sol_service_fee = 0u64;
 
         // Calculate totals first
+        let mut splits = Vec::with_capacity(payout_data.len());
         for payout in &payout_data {
-            let service_fee = (payout.amount * self.global_config.default_fee_bps as u64) / 10000;
-            total_amount += payout.amount;
-            total_service_fee += service_fee;
+            let split = split_payout(
+                &self.global_config,
+                &self.vault_account,
+                &payout.asset_type,
+                payout.amount,
+            )?;
+            // The whole batch call must fit under the batch cap
+            self.payroll_batch.record_payout(
+                &payout.asset_type,
+                payout.amount,
+                split.service_fee,
+            )?;
+            if payout.asset_type == AssetType::SOL {
+                total_sol_amount = checked_add(total_sol_amount, payout.amount)?;
+                total_sol_cost = checked_add(total_sol_cost, split.total_cost)?;
+                total_sol_service_fee = checked_add(total_sol_service_fee, split.service_fee)?;
+            }
+            splits.push(split);
         }
 
-        // Ensure vault has sufficient balance
+        self.payroll_batch.require_approved(&self.approval_config)?;
+
+        // Ensure vault has sufficient unreserved balance
         require!(
-            self.vault_account.total_balance >= total_amount,
+            self.vault_account.total_balance
+                >= checked_add(total_sol_cost, self.vault_account.required_balance)?,
             ErrorVault::InsufficientVaultBalance
         );
 
-        // Transfer total service fee to treasury
-        if total_service_fee > 0 {
-            **self
-                .vault_account
-                .to_account_info()
-                .try_borrow_mut_lamports()? -= total_service_fee;
-            **self.treasury.try_borrow_mut_lamports()? += total_service_fee;
+        let vault_key = self.vault_account.key();
+        let batch_id = self.payroll_batch.batch_id;
+        let now = Clock::get()?.unix_timestamp;
+        let mut accounts = remaining_accounts.iter();
+
+        for (payout, split) in payout_data.iter().zip(splits) {
+            let member_info = accounts
+                .next()
+                .ok_or(ErrorVault::InvalidRemainingAccounts)?;
+            let member = self.load_member(member_info, &vault_key, payout)?;
+
+            let FeeSplit {
+                service_fee,
+                net_amount,
+                ..
+            } = split;
+
+            let receipt_info = accounts
+                .next()
+                .ok_or(ErrorVault::InvalidRemainingAccounts)?;
+            self.create_receipt(
+                receipt_info,
+                member_info.key(),
+                payout,
+                service_fee,
+                net_amount,
+            )?;
+
+            match payout.asset_type {
+                AssetType::SOL => {
+                    let member_wallet = accounts
+                        .next()
+                        .ok_or(ErrorVault::InvalidRemainingAccounts)?;
+                    require_keys_eq!(
+                        member_wallet.key(),
+                        member.wallet,
+                        ErrorVault::InvalidMemberWallet
+                    );
+
+                    // Transfer net amount to member
+                    transfer_lamports(
+                        &self.vault_account.to_account_info(),
+                        member_wallet,
+                        net_amount,
+                    )?;
+                }
+                AssetType::SPLToken { mint } => {
+                    self.process_spl_leg(&mut accounts, &member, mint, net_amount, service_fee)?;
+                }
+            }
+
+            emit_payout(PayoutExecuted {
+                vault: vault_key,
+                recipient: member.wallet,
+                source: PayoutSource::Batch,
+                asset_type: payout.asset_type.clone(),
+                amount: payout.amount,
+                service_fee,
+                net_amount,
+                batch_id: Some(batch_id),
+                timestamp: now,
+            });
+        }
+
+        require!(
+            accounts.next().is_none(),
+            ErrorVault::InvalidRemainingAccounts
+        );
+
+        // Transfer total SOL service fee to treasury
+        if total_sol_service_fee > 0 {
+            transfer_lamports(
+                &self.vault_account.to_account_info(),
+                &self.treasury,
+                total_sol_service_fee,
+            )?;
         }
 
         // Update vault and batch state
-        self.vault_account.total_balance -= total_amount;
-        self.payroll_batch.payout_count += payout_data.len() as u32;
+        self.vault_account.debit_sol(total_sol_cost)?;
+        self.vault_account.record_sol_volume(total_sol_amount, now);
+        self.payroll_batch.payout_count =
+            increment(self.payroll_batch.payout_count, payout_data.len() as u32)?;
+
+        Ok(())
+    }
+
+    fn load_member(
+        &self,
+        member_info: &'info AccountInfo<'info>,
+        vault_key: &Pubkey,
+        payout: &PayoutData,
+    ) -> Result<Account<'info, Member>> {
+        let member = Account::<Member>::try_from(member_info)?;
+        let expected = Pubkey::create_program_address(
+            &[
+                MEMBER_SEED,
+                vault_key.as_ref(),
+                payout.member.as_ref(),
+                &[member.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorVault::InvalidMemberAccount)?;
+
+        require_keys_eq!(
+            member_info.key(),
+            expected,
+            ErrorVault::InvalidMemberAccount
+        );
+        require_keys_eq!(member.vault, *vault_key, ErrorVault::InvalidMemberAccount);
+        require_keys_eq!(
+            member.wallet,
+            payout.member,
+            ErrorVault::InvalidMemberAccount
+        );
+        require!(member.is_active, ErrorVault::MemberNotActive);
+
+        Ok(member)
+    }
+
+    fn create_receipt(
+        &self,
+        receipt_info: &'info AccountInfo<'info>,
+        member_key: Pubkey,
+        payout: &PayoutData,
+        service_fee: u64,
+        net_amount: u64,
+    ) -> Result<()> {
+        let batch_key = self.payroll_batch.key();
+        let (expected, bump) = Pubkey::find_program_address(
+            &[RECEIPT_SEED, batch_key.as_ref(), member_key.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            receipt_info.key(),
+            expected,
+            ErrorVault::InvalidReceiptAccount
+        );
+        // A receipt that already exists means the member was paid in this batch
+        require!(receipt_info.data_is_empty(), ErrorVault::MemberAlreadyPaid);
+
+        let seeds = &[
+            RECEIPT_SEED,
+            batch_key.as_ref(),
+            member_key.as_ref(),
+            &[bump],
+        ];
+        create_pda_account(
+            &self.payer.to_account_info(),
+            receipt_info,
+            &self.system_program.to_account_info(),
+            PayoutReceipt::SPACE,
+            &[&seeds[..]],
+        )?;
+
+        let receipt = PayoutReceipt {
+            vault: self.vault_account.key(),
+            member: member_key,
+            wallet: payout.member,
+            cycle: PayoutCycle::Batch {
+                batch_id: self.payroll_batch.batch_id,
+            },
+            asset_type: payout.asset_type.clone(),
+            amount: payout.amount,
+            service_fee,
+            net_amount,
+            paid_at: Clock::get()?.unix_timestamp,
+            claimed: true,
+            bump,
+            payer: self.payer.key(),
+        };
+        let mut data = receipt_info.try_borrow_mut_data()?;
+        receipt.try_serialize(&mut &mut data[..])?;
+
+        Ok(())
+    }
+
+    fn process_spl_leg(
+        &mut self,
+        accounts: &mut std::slice::Iter<'info, AccountInfo<'info>>,
+        member: &Account<'info, Member>,
+        mint_key: Pubkey,
+        net_amount: u64,
+        service_fee: u64,
+    ) -> Result<()> {
+        let token_program = self
+            .token_program
+            .as_ref()
+            .ok_or(ErrorVault::TokenProgramRequired)?;
+
+        require!(
+            self.vault_account
+                .whitelisted_assets
+                .contains(&AssetType::SPLToken { mint: mint_key }),
+            ErrorVault::AssetNotWhitelisted
+        );
+
+        let mut next = || accounts.next().ok_or(ErrorVault::InvalidRemainingAccounts);
+        let mint_info = next()?;
+        let vault_token_info = next()?;
+        let member_token_info = next()?;
+        let treasury_token_info = next()?;
+
+        require_keys_eq!(mint_info.key(), mint_key, ErrorVault::InvalidTokenAccount);
+        require_keys_eq!(
+            *mint_info.owner,
+            token_program.key(),
+            ErrorVault::InvalidTokenAccount
+        );
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+
+        let vault_token_account = InterfaceAccount::<TokenAccount>::try_from(vault_token_info)?;
+        let member_token_account = InterfaceAccount::<TokenAccount>::try_from(member_token_info)?;
+        let treasury_token_account =
+            InterfaceAccount::<TokenAccount>::try_from(treasury_token_info)?;
+
+        require!(
+            vault_token_account.mint == mint_key
+                && vault_token_account.owner == self.vault_account.key(),
+            ErrorVault::InvalidTokenAccount
+        );
+        require!(
+            member_token_account.mint == mint_key && member_token_account.owner == member.wallet,
+            ErrorVault::InvalidTokenAccount
+        );
+        require!(
+            treasury_token_account.mint == mint_key
+                && treasury_token_account.owner == self.global_config.treasury,
+            ErrorVault::InvalidTokenAccount
+        );
+        require!(
+            vault_token_account.amount
+                >= checked_add(
+                    checked_add(net_amount, service_fee)?,
+                    self.vault_account.spl_reserved(&mint_key)
+                )?,
+            ErrorVault::InsufficientVaultBalance
+        );
+
+        let creator_key = self.vault_account.creator;
+        let vault_id = self.vault_account.vault_id.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            vault_id.as_ref(),
+            &[self.vault_account.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // Transfer service fee to treasury token account
+        if service_fee > 0 {
+            let fee_accounts = TransferChecked {
+                from: vault_token_info.clone(),
+                mint: mint_info.clone(),
+                to: treasury_token_info.clone(),
+                authority: self.vault_account.to_account_info(),
+            };
+            let ctx = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                fee_accounts,
+                signer_seeds,
+            );
+            transfer_checked(ctx, service_fee, mint.decimals)?;
+        }
+
+        // Transfer net amount to member token account
+        let payout_accounts = TransferChecked {
+            from: vault_token_info.clone(),
+            mint: mint_info.clone(),
+            to: member_token_info.clone(),
+            authority: self.vault_account.to_account_info(),
+        };
+        let ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            payout_accounts,
+            signer_seeds,
+        );
+        transfer_checked(ctx, net_amount, mint.decimals)?;
+
+        self.vault_account
+            .debit_spl(&mint_key, checked_add(net_amount, service_fee)?)?;
 
         Ok(())
     }
@@ -1,20 +1,711 @@
-// // use crate::errors::VaultError;
-// // use crate::state::{MemberSplit, VaultAccount};
-// // use anchor_lang::prelude::*;
-
-
-// // // #[derive(Accounts)]
-// // // pub struct Claim<'info> {
-// // //     #[account(mut)]
-// // //     pub vault: Account<'info, VaultAccount>,
-// // //     pub claimant: Signer<'info>,
-// // //     
-// // // }
-
-// // // pub fn claim(ctx: Context<Claim>, code: String) -> Result<()> {
-// // //     let vault = &mut ctx.accounts.vault;
-// // //     require!(vault.code_claim == Some(code.clone()), VaultError::InvalidClaimCode);
-// // //     // Check eligibility and transfer to claimant
-// // //     vault.code_claim = None; // Reset after use
-// // //     Ok(())
-// // // }
\ No newline at end of file
+use crate::constants::APPROVAL_SEED;
+use crate::constants::DELEGATE_SEED;
+use crate::constants::GLOBAL_CONFIG_SEED;
+use crate::constants::RECEIPT_SEED;
+use crate::constants::REDEMPTION_SEED;
+use crate::constants::VAULT_SEED;
+use crate::errors::ErrorVault;
+use crate::events::{ClaimAllocated, ClaimCancelled, PayoutExecuted, PayoutSource};
+use crate::helper::{
+    claim_code_hash, emit_payout, is_authorized, redeem_claim_code, release_funds, reserve_funds,
+    split_payout, transfer_lamports,
+};
+use crate::math::{checked_add, increment, FeeSplit};
+use crate::state::{
+    AssetType, CodeRedemption, GlobalConfig, Member, PayoutCycle, PayoutReceipt, PayrollBatch,
+    VaultAccount, VaultDelegate,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+pub struct AllocateClaim<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::EXECUTE_PAYOUTS)
+            @ ErrorVault::Unauthorized,
+        constraint = !vault_account.paused @ ErrorVault::VaultPaused
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        mut,
+        constraint = payroll_batch.vault == vault_account.key(),
+        constraint = !payroll_batch.finalized @ ErrorVault::BatchAlreadyFinalized
+    )]
+    pub payroll_batch: Account<'info, PayrollBatch>,
+
+    /// CHECK: The vault's approval config PDA; uninitialized while the vault has no approvers
+    #[account(seeds = [APPROVAL_SEED, vault_account.key().as_ref()], bump)]
+    pub approval_config: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = member.vault == vault_account.key(),
+        constraint = member.is_active @ ErrorVault::MemberNotActive
+    )]
+    pub member: Account<'info, Member>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PayoutReceipt::SPACE,
+        seeds = [RECEIPT_SEED, payroll_batch.key().as_ref(), member.key().as_ref()],
+        bump
+    )]
+    pub receipt: Account<'info, PayoutReceipt>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// Vault token account for the allocated mint, required for SPL allocations
+    #[account(
+        constraint = vault_token_account.owner == vault_account.key() @ ErrorVault::InvalidTokenAccount
+    )]
+    pub vault_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AllocateClaim<'info> {
+    pub fn allocate(&mut self, amount: u64, asset_type: AssetType, receipt_bump: u8) -> Result<()> {
+        // Fee and fee mode are fixed at allocation time so later config changes do not
+        // alter what is owed
+        let FeeSplit {
+            service_fee,
+            net_amount,
+            total_cost,
+        } = split_payout(
+            &self.global_config,
+            &self.vault_account,
+            &asset_type,
+            amount,
+        )?;
+
+        // Reserve the full cost so other payouts cannot spend it before the member claims
+        reserve_funds(
+            &mut self.vault_account,
+            &asset_type,
+            total_cost,
+            self.vault_token_account
+                .as_deref()
+                .map(|account| &**account),
+        )?;
+
+        self.payroll_batch.require_approved(&self.approval_config)?;
+        self.payroll_batch
+            .record_payout(&asset_type, amount, service_fee)?;
+        self.payroll_batch.payout_count = increment(self.payroll_batch.payout_count, 1)?;
+
+        // Allocated SOL counts towards fee tiers when it is set aside, not when claimed
+        if asset_type == AssetType::SOL {
+            self.vault_account
+                .record_sol_volume(amount, Clock::get()?.unix_timestamp);
+        }
+
+        self.receipt.set_inner(PayoutReceipt {
+            vault: self.vault_account.key(),
+            member: self.member.key(),
+            wallet: self.member.wallet,
+            cycle: PayoutCycle::Batch {
+                batch_id: self.payroll_batch.batch_id,
+            },
+            asset_type: asset_type.clone(),
+            amount,
+            service_fee,
+            net_amount,
+            paid_at: Clock::get()?.unix_timestamp,
+            claimed: false,
+            bump: receipt_bump,
+            payer: self.payer.key(),
+        });
+
+        emit!(ClaimAllocated {
+            vault: self.vault_account.key(),
+            member: self.member.key(),
+            wallet: self.member.wallet,
+            batch_id: self.payroll_batch.batch_id,
+            asset_type,
+            amount,
+            service_fee,
+            net_amount,
+            timestamp: self.receipt.paid_at,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimSolPayment<'info> {
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    #[account(mut, constraint = !vault_account.paused @ ErrorVault::VaultPaused)]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        constraint = receipt.vault == vault_account.key() @ ErrorVault::InvalidReceiptAccount,
+        constraint = receipt.wallet == claimant.key() @ ErrorVault::InvalidClaimant,
+        constraint = !receipt.claimed @ ErrorVault::AlreadyClaimed,
+        constraint = receipt.asset_type == AssetType::SOL @ ErrorVault::AssetMismatch
+    )]
+    pub receipt: Account<'info, PayoutReceipt>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: Treasury account for fee collection
+    #[account(
+        mut,
+        constraint = treasury.key() == global_config.treasury @ ErrorVault::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimSolPayment<'info> {
+    pub fn claim(&mut self) -> Result<()> {
+        let amount = self.receipt.amount;
+        let service_fee = self.receipt.service_fee;
+        let net_amount = self.receipt.net_amount;
+        let total_cost = checked_add(net_amount, service_fee)?;
+
+        // Transfer service fee to treasury
+        if service_fee > 0 {
+            transfer_lamports(
+                &self.vault_account.to_account_info(),
+                &self.treasury,
+                service_fee,
+            )?;
+        }
+
+        // Transfer net amount to the claimant
+        transfer_lamports(
+            &self.vault_account.to_account_info(),
+            &self.claimant,
+            net_amount,
+        )?;
+
+        // Release the reservation
+        self.vault_account.debit_sol(total_cost)?;
+        release_funds(&mut self.vault_account, &AssetType::SOL, total_cost)?;
+
+        self.receipt.claimed = true;
+        self.receipt.paid_at = Clock::get()?.unix_timestamp;
+
+        emit_payout(PayoutExecuted {
+            vault: self.vault_account.key(),
+            recipient: self.claimant.key(),
+            source: PayoutSource::Claim,
+            asset_type: self.receipt.asset_type.clone(),
+            amount,
+            service_fee,
+            net_amount,
+            batch_id: self.receipt.cycle.batch_id(),
+            timestamp: self.receipt.paid_at,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimSplPayment<'info> {
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    #[account(mut, constraint = !vault_account.paused @ ErrorVault::VaultPaused)]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        mut,
+        constraint = receipt.vault == vault_account.key() @ ErrorVault::InvalidReceiptAccount,
+        constraint = receipt.wallet == claimant.key() @ ErrorVault::InvalidClaimant,
+        constraint = !receipt.claimed @ ErrorVault::AlreadyClaimed
+    )]
+    pub receipt: Box<Account<'info, PayoutReceipt>>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault_account,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Treasury account for fee collection
+    #[account(
+        constraint = treasury.key() == global_config.treasury @ ErrorVault::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        associated_token::mint = mint,
+        associated_token::authority = claimant,
+        associated_token::token_program = token_program,
+    )]
+    pub claimant_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimSplPayment<'info> {
+    pub fn claim(&mut self) -> Result<()> {
+        require!(
+            self.receipt.asset_type
+                == AssetType::SPLToken {
+                    mint: self.mint.key()
+                },
+            ErrorVault::AssetMismatch
+        );
+
+        let amount = self.receipt.amount;
+        let service_fee = self.receipt.service_fee;
+        let net_amount = self.receipt.net_amount;
+        let total_cost = checked_add(net_amount, service_fee)?;
+
+        let creator_key = self.vault_account.creator;
+        let vault_id = self.vault_account.vault_id.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            vault_id.as_ref(),
+            &[self.vault_account.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // Transfer service fee to treasury token account
+        if service_fee > 0 {
+            let fee_accounts = TransferChecked {
+                from: self.vault_token_account.to_account_info(),
+                mint: self.mint.to_account_info(),
+                to: self.treasury_token_account.to_account_info(),
+                authority: self.vault_account.to_account_info(),
+            };
+            let ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                fee_accounts,
+                signer_seeds,
+            );
+            transfer_checked(ctx, service_fee, self.mint.decimals)?;
+        }
+
+        // Transfer net amount to the claimant token account
+        let payout_accounts = TransferChecked {
+            from: self.vault_token_account.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.claimant_token_account.to_account_info(),
+            authority: self.vault_account.to_account_info(),
+        };
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            payout_accounts,
+            signer_seeds,
+        );
+        transfer_checked(ctx, net_amount, self.mint.decimals)?;
+        self.vault_account.debit_spl(&self.mint.key(), total_cost)?;
+
+        // Release the reservation
+        let asset_type = self.receipt.asset_type.clone();
+        release_funds(&mut self.vault_account, &asset_type, total_cost)?;
+
+        self.receipt.claimed = true;
+        self.receipt.paid_at = Clock::get()?.unix_timestamp;
+
+        emit_payout(PayoutExecuted {
+            vault: self.vault_account.key(),
+            recipient: self.claimant.key(),
+            source: PayoutSource::Claim,
+            asset_type: self.receipt.asset_type.clone(),
+            amount,
+            service_fee,
+            net_amount,
+            batch_id: self.receipt.cycle.batch_id(),
+            timestamp: self.receipt.paid_at,
+        });
+
+        Ok(())
+    }
+}
+
+/// Withdraws an allocation the member has not claimed yet: its reservation is
+/// released and the receipt closed to whoever paid its rent. The batch keeps
+/// counting the allocation in its totals, and an open batch may allocate to the
+/// member again.
+#[derive(Accounts)]
+pub struct CancelClaim<'info> {
+    #[account(mut, has_one = owner)]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        close = payer,
+        constraint = receipt.vault == vault_account.key() @ ErrorVault::InvalidReceiptAccount,
+        constraint = !receipt.claimed @ ErrorVault::AlreadyClaimed
+    )]
+    pub receipt: Account<'info, PayoutReceipt>,
+
+    /// CHECK: Original payer of the receipt rent, refunded on close
+    #[account(
+        mut,
+        constraint = payer.key() == receipt.payer @ ErrorVault::InvalidRentReceiver
+    )]
+    pub payer: AccountInfo<'info>,
+
+    pub owner: Signer<'info>,
+}
+
+impl<'info> CancelClaim<'info> {
+    pub fn cancel(&mut self) -> Result<()> {
+        let total_cost = checked_add(self.receipt.net_amount, self.receipt.service_fee)?;
+        release_funds(
+            &mut self.vault_account,
+            &self.receipt.asset_type,
+            total_cost,
+        )?;
+
+        emit!(ClaimCancelled {
+            vault: self.vault_account.key(),
+            member: self.receipt.member,
+            wallet: self.receipt.wallet,
+            batch_id: self.receipt.cycle.batch_id(),
+            asset_type: self.receipt.asset_type.clone(),
+            released: total_cost,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct ClaimWithCode<'info> {
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    #[account(mut, constraint = !vault_account.paused @ ErrorVault::VaultPaused)]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        init,
+        payer = claimant,
+        space = CodeRedemption::SPACE,
+        seeds = [
+            REDEMPTION_SEED,
+            vault_account.key().as_ref(),
+            claim_code_hash(&code, &vault_account.key()).as_ref(),
+            claimant.key().as_ref()
+        ],
+        bump
+    )]
+    pub redemption: Account<'info, CodeRedemption>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: Treasury account for fee collection
+    #[account(
+        mut,
+        constraint = treasury.key() == global_config.treasury @ ErrorVault::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimWithCode<'info> {
+    pub fn claim(&mut self, code: String, redemption_bump: u8) -> Result<()> {
+        let vault_key = self.vault_account.key();
+        let now = Clock::get()?.unix_timestamp;
+        let claim_code =
+            redeem_claim_code(&mut self.vault_account.code_claim, &code, &vault_key, now)?;
+        require!(
+            claim_code.asset_type == AssetType::SOL,
+            ErrorVault::AssetMismatch
+        );
+
+        let amount = claim_code.amount;
+        let FeeSplit {
+            service_fee,
+            net_amount,
+            total_cost,
+        } = split_payout(
+            &self.global_config,
+            &self.vault_account,
+            &AssetType::SOL,
+            amount,
+        )?;
+
+        // Code claims may only spend unreserved funds
+        require!(
+            self.vault_account.total_balance
+                >= checked_add(total_cost, self.vault_account.required_balance)?,
+            ErrorVault::InsufficientVaultBalance
+        );
+
+        // Transfer service fee to treasury
+        if service_fee > 0 {
+            transfer_lamports(
+                &self.vault_account.to_account_info(),
+                &self.treasury,
+                service_fee,
+            )?;
+        }
+
+        // Transfer net amount to the claimant
+        transfer_lamports(
+            &self.vault_account.to_account_info(),
+            &self.claimant,
+            net_amount,
+        )?;
+
+        self.vault_account.debit_sol(total_cost)?;
+        self.vault_account.record_sol_volume(amount, now);
+
+        self.redemption.set_inner(CodeRedemption {
+            vault: vault_key,
+            claimant: self.claimant.key(),
+            code_hash: claim_code.code_hash,
+            amount,
+            redeemed_at: now,
+            bump: redemption_bump,
+        });
+
+        emit_payout(PayoutExecuted {
+            vault: vault_key,
+            recipient: self.claimant.key(),
+            source: PayoutSource::ClaimCode,
+            asset_type: AssetType::SOL,
+            amount,
+            service_fee,
+            net_amount,
+            batch_id: None,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct ClaimSplWithCode<'info> {
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    #[account(mut, constraint = !vault_account.paused @ ErrorVault::VaultPaused)]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        init,
+        payer = claimant,
+        space = CodeRedemption::SPACE,
+        seeds = [
+            REDEMPTION_SEED,
+            vault_account.key().as_ref(),
+            claim_code_hash(&code, &vault_account.key()).as_ref(),
+            claimant.key().as_ref()
+        ],
+        bump
+    )]
+    pub redemption: Box<Account<'info, CodeRedemption>>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault_account,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Treasury account for fee collection
+    #[account(
+        constraint = treasury.key() == global_config.treasury @ ErrorVault::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        associated_token::mint = mint,
+        associated_token::authority = claimant,
+        associated_token::token_program = token_program,
+    )]
+    pub claimant_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimSplWithCode<'info> {
+    pub fn claim(&mut self, code: String, redemption_bump: u8) -> Result<()> {
+        let vault_key = self.vault_account.key();
+        let now = Clock::get()?.unix_timestamp;
+        let claim_code =
+            redeem_claim_code(&mut self.vault_account.code_claim, &code, &vault_key, now)?;
+        require!(
+            claim_code.asset_type
+                == AssetType::SPLToken {
+                    mint: self.mint.key()
+                },
+            ErrorVault::AssetMismatch
+        );
+
+        let amount = claim_code.amount;
+        let FeeSplit {
+            service_fee,
+            net_amount,
+            total_cost,
+        } = split_payout(
+            &self.global_config,
+            &self.vault_account,
+            &claim_code.asset_type,
+            amount,
+        )?;
+
+        // Code claims may only spend unreserved funds
+        require!(
+            self.vault_token_account.amount
+                >= checked_add(
+                    total_cost,
+                    self.vault_account.spl_reserved(&self.mint.key())
+                )?,
+            ErrorVault::InsufficientVaultBalance
+        );
+
+        let creator_key = self.vault_account.creator;
+        let vault_id = self.vault_account.vault_id.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            vault_id.as_ref(),
+            &[self.vault_account.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // Transfer service fee to treasury token account
+        if service_fee > 0 {
+            let fee_accounts = TransferChecked {
+                from: self.vault_token_account.to_account_info(),
+                mint: self.mint.to_account_info(),
+                to: self.treasury_token_account.to_account_info(),
+                authority: self.vault_account.to_account_info(),
+            };
+            let ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                fee_accounts,
+                signer_seeds,
+            );
+            transfer_checked(ctx, service_fee, self.mint.decimals)?;
+        }
+
+        // Transfer net amount to the claimant token account
+        let payout_accounts = TransferChecked {
+            from: self.vault_token_account.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.claimant_token_account.to_account_info(),
+            authority: self.vault_account.to_account_info(),
+        };
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            payout_accounts,
+            signer_seeds,
+        );
+        transfer_checked(ctx, net_amount, self.mint.decimals)?;
+        self.vault_account.debit_spl(&self.mint.key(), total_cost)?;
+
+        self.redemption.set_inner(CodeRedemption {
+            vault: vault_key,
+            claimant: self.claimant.key(),
+            code_hash: claim_code.code_hash,
+            amount,
+            redeemed_at: now,
+            bump: redemption_bump,
+        });
+
+        emit_payout(PayoutExecuted {
+            vault: vault_key,
+            recipient: self.claimant.key(),
+            source: PayoutSource::ClaimCode,
+            asset_type: claim_code.asset_type,
+            amount,
+            service_fee,
+            net_amount,
+            batch_id: None,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
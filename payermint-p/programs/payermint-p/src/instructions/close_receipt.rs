@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::RECEIPT_SEED;
+use crate::errors::ErrorVault;
+use crate::events::{ReceiptClosed, RedemptionClosed};
+use crate::state::{CodeRedemption, PayoutCycle, PayoutReceipt, PayrollBatch, VaultAccount};
+
+/// Returns a receipt's rent to whoever paid it once the receipt no longer guards
+/// against a second payout: its batch is finalized or closed, its schedule period
+/// has ended, or its vault is closed. Unclaimed allocations stay open until they
+/// are claimed or cancelled.
+#[derive(Accounts)]
+pub struct CloseReceipt<'info> {
+    #[account(
+        mut,
+        close = payer,
+        constraint = receipt.claimed @ ErrorVault::ReceiptNotClaimed
+    )]
+    pub receipt: Account<'info, PayoutReceipt>,
+
+    /// CHECK: The receipt's vault, read for its schedule; may already be closed
+    #[account(address = receipt.vault @ ErrorVault::InvalidReceiptAccount)]
+    pub vault_account: UncheckedAccount<'info>,
+
+    /// CHECK: Batch the receipt was written for, required for batch receipts; may
+    /// already be closed. Matched against the receipt's own seeds.
+    pub payroll_batch: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        constraint = payer.key() == receipt.payer @ ErrorVault::InvalidRentReceiver
+    )]
+    pub payer: Signer<'info>,
+}
+
+impl<'info> CloseReceipt<'info> {
+    pub fn close(&mut self) -> Result<()> {
+        require!(self.cycle_has_ended()?, ErrorVault::PayoutCycleOpen);
+
+        emit!(ReceiptClosed {
+            vault: self.receipt.vault,
+            member: self.receipt.member,
+            cycle: self.receipt.cycle.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    fn cycle_has_ended(&self) -> Result<bool> {
+        if self.vault_account.data_is_empty() {
+            return Ok(true);
+        }
+        match self.receipt.cycle {
+            PayoutCycle::Batch { .. } => {
+                let payroll_batch = self
+                    .payroll_batch
+                    .as_ref()
+                    .ok_or(ErrorVault::InvalidReceiptAccount)?;
+                let batch_key = payroll_batch.key();
+                let expected = Pubkey::create_program_address(
+                    &[
+                        RECEIPT_SEED,
+                        batch_key.as_ref(),
+                        self.receipt.member.as_ref(),
+                        &[self.receipt.bump],
+                    ],
+                    &crate::ID,
+                )
+                .map_err(|_| error!(ErrorVault::InvalidReceiptAccount))?;
+                require_keys_eq!(
+                    self.receipt.key(),
+                    expected,
+                    ErrorVault::InvalidReceiptAccount
+                );
+                if payroll_batch.data_is_empty() {
+                    return Ok(true);
+                }
+                let batch =
+                    PayrollBatch::try_deserialize(&mut &payroll_batch.try_borrow_data()?[..])?;
+                Ok(batch.finalized)
+            }
+            // Schedule updates never reopen a period that was paid, so once the
+            // current period has moved past this one it cannot be paid again
+            PayoutCycle::Period { start_ts } => {
+                let vault =
+                    VaultAccount::try_deserialize(&mut &self.vault_account.try_borrow_data()?[..])?;
+                match vault.payout_schedule {
+                    Some(schedule) => {
+                        Ok(schedule.current_period(Clock::get()?.unix_timestamp)? > start_ts)
+                    }
+                    None => Ok(true),
+                }
+            }
+        }
+    }
+}
+
+/// Returns a code redemption's rent to the claimant once the code it blocks can no
+/// longer be redeemed: the code was replaced, removed, used up or has expired, or
+/// the vault is closed.
+#[derive(Accounts)]
+pub struct CloseRedemption<'info> {
+    #[account(
+        mut,
+        close = claimant,
+        has_one = claimant @ ErrorVault::InvalidClaimant
+    )]
+    pub redemption: Account<'info, CodeRedemption>,
+
+    /// CHECK: The redemption's vault, read for its claim code; may already be closed
+    #[account(address = redemption.vault @ ErrorVault::InvalidReceiptAccount)]
+    pub vault_account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+}
+
+impl<'info> CloseRedemption<'info> {
+    pub fn close(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if !self.vault_account.data_is_empty() {
+            let vault =
+                VaultAccount::try_deserialize(&mut &self.vault_account.try_borrow_data()?[..])?;
+            let still_redeemable = vault.code_claim.is_some_and(|claim_code| {
+                claim_code.code_hash == self.redemption.code_hash && claim_code.is_redeemable(now)
+            });
+            require!(!still_redeemable, ErrorVault::ClaimCodeStillRedeemable);
+        }
+
+        emit!(RedemptionClosed {
+            vault: self.redemption.vault,
+            claimant: self.redemption.claimant,
+            timestamp: now,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+
+use crate::constants::{APPROVAL_SEED, VAULT_SEED};
+use crate::errors::ErrorVault;
+use crate::events::VaultClosed;
+use crate::math::checked_add;
+use crate::state::{ApprovalConfig, AssetType, Member, OwnerAction, VaultAccount};
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(mut, has_one = owner, close = owner)]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    /// CHECK: The vault's approval config PDA, closed with the vault if it was initialized
+    #[account(
+        mut,
+        seeds = [APPROVAL_SEED, vault_account.key().as_ref()],
+        bump
+    )]
+    pub approval_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CloseVault<'info> {
+    /// Sweeps every SPL balance to the owner, closes the vault ATAs and the
+    /// approval config, and lets `close = owner` return the remaining SOL and
+    /// rent. Streams, vesting schedules and delegates must be closed first.
+    /// `remaining_accounts` starts with the vault's `member_count` member
+    /// accounts (mut) when `force` is set, which are closed to the owner too,
+    /// followed by `[mint, token_program, vault_token_account (mut),
+    /// owner_token_account (mut)]` for every whitelisted or tracked mint, where
+    /// `token_program` is the program that owns the mint; ATAs never created are
+    /// skipped. Payroll batches must be closed first as well.
+    pub fn close(
+        &mut self,
+        force: bool,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(
+            force || self.vault_account.member_count == 0,
+            ErrorVault::MembersRemaining
+        );
+        ApprovalConfig::consume_action(&self.approval_config, &OwnerAction::CloseVault { force })?;
+        require!(
+            self.vault_account.open_batch_count == 0,
+            ErrorVault::OpenBatchesRemaining
+        );
+        // These are keyed to the vault and would be orphaned by closing it
+        require!(
+            self.vault_account.batch_count == 0
+                && self.vault_account.stream_count == 0
+                && self.vault_account.vesting_count == 0
+                && self.vault_account.delegate_count == 0,
+            ErrorVault::VaultAccountsRemaining
+        );
+        // Reserved funds are owed to members and cannot be swept
+        require!(
+            self.vault_account.required_balance == 0 && !self.vault_account.has_spl_reservations(),
+            ErrorVault::OutstandingReservations
+        );
+
+        // Without `force` there are no members left, so nothing is taken here
+        let member_count = self.vault_account.member_count as usize;
+        require!(
+            remaining_accounts.len() >= member_count,
+            ErrorVault::InvalidRemainingAccounts
+        );
+        let (member_infos, token_infos) = remaining_accounts.split_at(member_count);
+        for member_info in member_infos {
+            self.close_member(member_info)?;
+        }
+
+        let groups = token_infos.chunks_exact(4);
+        require!(
+            groups.remainder().is_empty(),
+            ErrorVault::InvalidRemainingAccounts
+        );
+
+        let mut swept_mints = Vec::with_capacity(groups.len());
+        for accounts in groups {
+            let mint_key =
+                self.sweep_token_account(&accounts[0], &accounts[1], &accounts[2], &accounts[3])?;
+            swept_mints.push(mint_key);
+        }
+
+        // Tracked balances can outlive a mint's whitelisting, so check both lists
+        let whitelisted_mints = self
+            .vault_account
+            .whitelisted_assets
+            .iter()
+            .filter_map(|asset| match asset {
+                AssetType::SPLToken { mint } => Some(mint),
+                AssetType::SOL => None,
+            });
+        let tracked_mints = self
+            .vault_account
+            .spl_balances
+            .iter()
+            .map(|entry| &entry.mint);
+        for mint in whitelisted_mints.chain(tracked_mints) {
+            if !swept_mints.contains(mint) {
+                msg!("close_vault: token account for mint {} not swept", mint);
+                return err!(ErrorVault::TokenAccountNotSwept);
+            }
+        }
+
+        // Only this program can have allocated data at the approval config PDA
+        if !self.approval_config.data_is_empty() {
+            self.close_approval_config()?;
+        }
+
+        emit!(VaultClosed {
+            vault: self.vault_account.key(),
+            owner: self.owner.key(),
+            forced: force,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Closes one of the vault's member accounts, returning its rent to the owner
+    fn close_member(&self, member_info: &'info AccountInfo<'info>) -> Result<()> {
+        let member = Account::<Member>::try_from(member_info)?;
+        require_keys_eq!(
+            member.vault,
+            self.vault_account.key(),
+            ErrorVault::InvalidRemainingAccounts
+        );
+        member.close(self.owner.to_account_info())
+    }
+
+    /// Returns the approval config rent to the owner and hands the account back
+    /// to the system program
+    fn close_approval_config(&self) -> Result<()> {
+        let approval_config = self.approval_config.to_account_info();
+        let owner = self.owner.to_account_info();
+        let lamports = checked_add(owner.lamports(), approval_config.lamports())?;
+        **owner.try_borrow_mut_lamports()? = lamports;
+        **approval_config.try_borrow_mut_lamports()? = 0;
+        approval_config.assign(&system_program::ID);
+        approval_config.resize(0)?;
+        Ok(())
+    }
+
+    fn sweep_token_account(
+        &self,
+        mint_info: &'info AccountInfo<'info>,
+        token_program_info: &'info AccountInfo<'info>,
+        vault_token_info: &'info AccountInfo<'info>,
+        owner_token_info: &'info AccountInfo<'info>,
+    ) -> Result<Pubkey> {
+        // The ATA address depends on the token program, so it must be the mint's own
+        let token_program = Interface::<TokenInterface>::try_from(token_program_info)?;
+        require_keys_eq!(
+            *mint_info.owner,
+            token_program.key(),
+            ErrorVault::InvalidTokenAccount
+        );
+
+        let vault_key = self.vault_account.key();
+        require_keys_eq!(
+            vault_token_info.key(),
+            get_associated_token_address_with_program_id(
+                &vault_key,
+                &mint_info.key(),
+                &token_program.key()
+            ),
+            ErrorVault::InvalidTokenAccount
+        );
+
+        // Nothing to sweep for a mint that was never deposited
+        if vault_token_info.data_is_empty() {
+            return Ok(mint_info.key());
+        }
+
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        let vault_token_account = InterfaceAccount::<TokenAccount>::try_from(vault_token_info)?;
+        let owner_token_account = InterfaceAccount::<TokenAccount>::try_from(owner_token_info)?;
+        require!(
+            owner_token_account.mint == mint.key() && owner_token_account.owner == self.owner.key(),
+            ErrorVault::InvalidTokenAccount
+        );
+
+        let creator_key = self.vault_account.creator;
+        let vault_id = self.vault_account.vault_id.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            vault_id.as_ref(),
+            &[self.vault_account.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // Transfer the whole balance to the owner
+        if vault_token_account.amount > 0 {
+            let transfer_accounts = TransferChecked {
+                from: vault_token_info.clone(),
+                mint: mint_info.clone(),
+                to: owner_token_info.clone(),
+                authority: self.vault_account.to_account_info(),
+            };
+            let ctx = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                transfer_accounts,
+                signer_seeds,
+            );
+            transfer_checked(ctx, vault_token_account.amount, mint.decimals)?;
+        }
+
+        // Close the emptied ATA, returning its rent to the owner
+        let close_accounts = CloseAccount {
+            account: vault_token_info.clone(),
+            destination: self.owner.to_account_info(),
+            authority: self.vault_account.to_account_info(),
+        };
+        let ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            close_accounts,
+            signer_seeds,
+        );
+        close_account(ctx)?;
+
+        Ok(mint.key())
+    }
+}
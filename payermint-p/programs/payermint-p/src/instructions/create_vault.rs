@@ -1,11 +1,20 @@
 use anchor_lang::prelude::*;
 // use anchor_spl::token::{Mint, Token, TokenAccount};
 
+use crate::constants::APPROVAL_SEED;
+use crate::constants::DELEGATE_SEED;
 use crate::constants::GLOBAL_CONFIG_SEED;
+use crate::constants::MAX_BATCH_RETENTION_SECS;
+use crate::constants::MAX_WHITELISTED_ASSETS;
 use crate::constants::VAULT_SEED;
 use crate::errors::ErrorVault;
-use crate::state::{AllocationType, GlobalConfig, PayoutSchedule};
-use crate::state::{AssetType, VaultAccount, VaultType};
+use crate::events::{PayoutScheduleUpdated, VaultCreated, VaultSetting, VaultSettingUpdated};
+use crate::events::{VaultOwnerAccepted, VaultOwnerProposed, VaultPaused};
+use crate::helper::{is_authorized, validate_claim_code};
+use crate::math::{checked_add, checked_add_secs};
+use crate::state::{AllocationType, ClaimCode, FeeMode, GlobalConfig, PayoutSchedule};
+use crate::state::{ApprovalConfig, OwnerAction};
+use crate::state::{AssetType, VaultAccount, VaultDelegate, VaultType, VolumeWindow};
 
 #[derive(Accounts)]
 #[instruction(name: String)]
@@ -25,7 +34,7 @@ pub struct CreateVault<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + 32 + 4 + 32 + 4 + 10 * 33 + 1 + 20 + 8 + 8 + 8 + 8 + 1 + 1 + 204 + 1 + 14 + 1, // discriminator + owner (Pubkey) + treasury (Pubkey) + default_fee_bps (u16) + next_company_id + bump (u8)
+        space = VaultAccount::SPACE,
         seeds = [VAULT_SEED, owner.key().as_ref(), &global_config.next_company_id.to_le_bytes()],
         bump
     )]
@@ -35,6 +44,7 @@ pub struct CreateVault<'info> {
 }
 
 impl<'info> CreateVault<'info> {
+    #[allow(clippy::too_many_arguments)]
     pub fn create(
         &mut self,
         name: String,
@@ -43,44 +53,102 @@ impl<'info> CreateVault<'info> {
         payout_schedule: Option<PayoutSchedule>,
         allocation_type: AllocationType,
         metadata_uri: Option<String>,
-        code_claim: Option<String>,
+        code_claim: Option<ClaimCode>,
         bump: u8,
     ) -> Result<()> {
         require!(name.len() <= 32, ErrorVault::NameTooLong);
         if let Some(ref uri) = metadata_uri {
             require!(uri.len() <= 200, ErrorVault::MetadataUriTooLong);
         }
-        if let Some(ref encrypted_code) = code_claim {
-            // Encrypted code is passed from the client
-            let encrypted_code_claim = encrypted_code.clone();
-
-            self.vault_account.code_claim = Some(encrypted_code_claim);
-        } else {
-            // No code_claim provided
-            self.vault_account.code_claim = None;
+        if let Some(ref claim_code) = code_claim {
+            validate_claim_code(claim_code, Clock::get()?.unix_timestamp)?;
         }
+        if let Some(ref schedule) = payout_schedule {
+            schedule.validate()?;
+        }
+        require!(
+            whitelisted_assets.len() <= MAX_WHITELISTED_ASSETS,
+            ErrorVault::TooManyWhitelistedAssets
+        );
 
         self.vault_account.set_inner(VaultAccount {
             owner: self.owner.key(),
+            vault_id: self.global_config.next_company_id,
             name,
             vault_type,
             whitelisted_assets,
             payout_schedule,
-            total_balance: 0,        // Start with 0 total balance
-            required_balance: 0,     // No required balance initially
-            required_spl_balance: 0, // No required SPL token balance initially
+            total_balance: 0,    // Start with 0 total balance
+            required_balance: 0, // No required balance initially
             last_deposit_ts: Clock::get()?.unix_timestamp, // Current timestamp for the last deposit
             allocation_type,
             metadata_uri,
-            code_claim: self.vault_account.code_claim.clone(),
+            code_claim: code_claim.map(|claim_code| ClaimCode {
+                uses: 0,
+                ..claim_code
+            }),
             bump,
             spl_balances: Vec::new(), // Start with an empty list of SPL token balances
             sol_balance: 0,           // Start with 0 SOL balance
+            keeper_reward: 0,
+            active_member_count: 0,
+            period_paid_count: 0,
+            period_start_balance: 0,
+            paid_through: 0,
+            member_count: 0,
+            open_batch_count: 0,
+            batch_count: 0,
+            stream_count: 0,
+            vesting_count: 0,
+            delegate_count: 0,
+            batch_retention_secs: 0,
+            creator: self.owner.key(),
+            pending_owner: None,
+            paused: false,
+            fee_override_bps: None,
+            sol_volume: VolumeWindow::default(),
+            fee_mode: FeeMode::Deduct,
         });
 
         // Increment next company ID
-        self.global_config.next_company_id += 1;
+        self.global_config.next_company_id = checked_add(self.global_config.next_company_id, 1)?;
+
+        emit!(VaultCreated {
+            vault: self.vault_account.key(),
+            owner: self.owner.key(),
+            vault_id: self.vault_account.vault_id,
+            name: self.vault_account.name.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct AcceptVaultOwner<'info> {
+    #[account(
+        mut,
+        constraint = vault_account.pending_owner == Some(new_owner.key()) @ ErrorVault::NotPendingOwner
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
 
+    pub new_owner: Signer<'info>,
+}
+
+impl<'info> AcceptVaultOwner<'info> {
+    /// Only `owner` changes; the PDA stays derived from `creator`
+    pub fn accept(&mut self) -> Result<()> {
+        let previous_owner = self.vault_account.owner;
+        self.vault_account.owner = self.new_owner.key();
+        self.vault_account.pending_owner = None;
+
+        emit!(VaultOwnerAccepted {
+            vault: self.vault_account.key(),
+            previous_owner,
+            new_owner: self.new_owner.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 }
@@ -93,23 +161,211 @@ pub struct UpdateVault<'info> {
     pub owner: Signer<'info>,
 }
 
+/// Owner settings that pay vault funds to whoever holds a claim code or cranks a
+/// schedule; vaults with approvers need an approved `OwnerAction` for each change
+#[derive(Accounts)]
+pub struct UpdateVaultPayouts<'info> {
+    #[account(mut, has_one = owner)]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    /// CHECK: The vault's approval config PDA; uninitialized while the vault has no approvers
+    #[account(mut, seeds = [APPROVAL_SEED, vault_account.key().as_ref()], bump)]
+    pub approval_config: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+}
+
+impl<'info> UpdateVaultPayouts<'info> {
+    /// Replaces the vault claim code; `None` disables code claims
+    pub fn set_claim_code(&mut self, code_claim: Option<ClaimCode>) -> Result<()> {
+        if let Some(ref claim_code) = code_claim {
+            validate_claim_code(claim_code, Clock::get()?.unix_timestamp)?;
+        }
+        ApprovalConfig::consume_action(
+            &self.approval_config,
+            &OwnerAction::SetClaimCode {
+                code_claim: code_claim.clone(),
+            },
+        )?;
+        self.vault_account.code_claim = code_claim.map(|claim_code| ClaimCode {
+            uses: 0,
+            ..claim_code
+        });
+        emit_setting(
+            &self.vault_account,
+            VaultSetting::ClaimCode {
+                code_claim: self.vault_account.code_claim.clone(),
+            },
+        )
+    }
+
+    /// The reward is paid out of vault funds on every crank
+    pub fn update_keeper_reward(&mut self, keeper_reward: u64) -> Result<()> {
+        ApprovalConfig::consume_action(
+            &self.approval_config,
+            &OwnerAction::UpdateKeeperReward { keeper_reward },
+        )?;
+        self.vault_account.keeper_reward = keeper_reward;
+        emit_setting(
+            &self.vault_account,
+            VaultSetting::KeeperReward { keeper_reward },
+        )
+    }
+}
+
+fn emit_setting(vault_account: &Account<VaultAccount>, setting: VaultSetting) -> Result<()> {
+    emit!(VaultSettingUpdated {
+        vault: vault_account.key(),
+        setting,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
 impl<'info> UpdateVault<'info> {
-    pub fn update_payout_schedule(&mut self, schedule: Option<PayoutSchedule>) -> Result<()> {
-        self.vault_account.payout_schedule = schedule;
+    /// First step of an ownership transfer; the proposed owner must accept it
+    pub fn propose_vault_owner(&mut self, new_owner: Pubkey) -> Result<()> {
+        self.vault_account.pending_owner = Some(new_owner);
+        self.emit_owner_proposal()
+    }
+
+    pub fn cancel_vault_owner_proposal(&mut self) -> Result<()> {
+        self.vault_account.pending_owner = None;
+        self.emit_owner_proposal()
+    }
+
+    fn emit_owner_proposal(&self) -> Result<()> {
+        emit!(VaultOwnerProposed {
+            vault: self.vault_account.key(),
+            owner: self.vault_account.owner,
+            pending_owner: self.vault_account.pending_owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn update_batch_retention(&mut self, batch_retention_secs: i64) -> Result<()> {
+        require!(
+            (0..=MAX_BATCH_RETENTION_SECS).contains(&batch_retention_secs),
+            ErrorVault::InvalidRetentionPeriod
+        );
+        self.vault_account.batch_retention_secs = batch_retention_secs;
+        emit_setting(
+            &self.vault_account,
+            VaultSetting::BatchRetention {
+                batch_retention_secs,
+            },
+        )
+    }
+
+    pub fn set_fee_mode(&mut self, fee_mode: FeeMode) -> Result<()> {
+        self.vault_account.fee_mode = fee_mode;
+        emit_setting(&self.vault_account, VaultSetting::FeeMode { fee_mode })
+    }
+
+    pub fn set_vault_paused(&mut self, paused: bool) -> Result<()> {
+        self.vault_account.paused = paused;
+
+        emit!(VaultPaused {
+            vault: self.vault_account.key(),
+            paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
     pub fn add_whitelisted_asset(&mut self, asset: AssetType) -> Result<()> {
         if !self.vault_account.whitelisted_assets.contains(&asset) {
-            self.vault_account.whitelisted_assets.push(asset);
+            require!(
+                self.vault_account.whitelisted_assets.len() < MAX_WHITELISTED_ASSETS,
+                ErrorVault::TooManyWhitelistedAssets
+            );
+            // A delisted mint still tracked by `sync_balances` already holds its slot
+            if let AssetType::SPLToken { mint } = asset {
+                require!(
+                    self.vault_account.tracks_spl(&mint)
+                        || self.vault_account.spl_slots_used() < MAX_WHITELISTED_ASSETS,
+                    ErrorVault::TooManyWhitelistedAssets
+                );
+            }
+            self.vault_account.whitelisted_assets.push(asset.clone());
         }
-        Ok(())
+        emit_setting(
+            &self.vault_account,
+            VaultSetting::AssetWhitelisted { asset },
+        )
     }
 
+    /// A mint can only be removed once the vault holds and owes none of it
     pub fn remove_whitelisted_asset(&mut self, asset: AssetType) -> Result<()> {
+        if let AssetType::SPLToken { mint } = asset {
+            self.vault_account.untrack_spl(&mint)?;
+        }
         self.vault_account
             .whitelisted_assets
             .retain(|a| a != &asset);
+        emit_setting(&self.vault_account, VaultSetting::AssetRemoved { asset })
+    }
+}
+
+/// Schedule settings a delegate with `UPDATE_SCHEDULE` may change
+#[derive(Accounts)]
+pub struct UpdateVaultSchedule<'info> {
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::UPDATE_SCHEDULE)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+}
+
+impl<'info> UpdateVaultSchedule<'info> {
+    pub fn update_payout_schedule(&mut self, schedule: Option<PayoutSchedule>) -> Result<()> {
+        if let Some(ref schedule) = schedule {
+            schedule.validate()?;
+        }
+        // Receipts are keyed by period start, so the count only restarts with a new period.
+        // A stored period that has already ended is not kept open by reusing its start.
+        let now = Clock::get()?.unix_timestamp;
+        let same_period = match (&self.vault_account.payout_schedule, &schedule) {
+            (Some(current), Some(new)) => {
+                current.next_payout_ts == new.next_payout_ts
+                    && current.current_period(now)? == current.next_payout_ts
+            }
+            _ => false,
+        };
+        // A period that opens before the paid one ends would pay members twice for the
+        // same stretch of time, so schedules may only move forward past it
+        if let Some(ref new) = schedule {
+            let opens_next_period = if same_period {
+                checked_add_secs(new.next_payout_ts, new.interval)?
+            } else {
+                new.next_payout_ts
+            };
+            require!(
+                opens_next_period >= self.vault_account.paid_through,
+                ErrorVault::PeriodAlreadyPaid
+            );
+        }
+        if !same_period {
+            self.vault_account.period_paid_count = 0;
+        }
+        self.vault_account.payout_schedule = schedule.clone();
+
+        emit!(PayoutScheduleUpdated {
+            vault: self.vault_account.key(),
+            schedule,
+            timestamp: now,
+        });
         Ok(())
     }
 }
@@ -0,0 +1,95 @@
+use crate::constants::DELEGATE_SEED;
+use crate::errors::ErrorVault;
+use crate::events::{DelegateRemoved, DelegateSet};
+use crate::math::{decrement, increment};
+use crate::state::{VaultAccount, VaultDelegate};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetDelegate<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, has_one = owner)]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + VaultDelegate::INIT_SPACE,
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), delegate_wallet.key().as_ref()],
+        bump
+    )]
+    pub delegate: Account<'info, VaultDelegate>,
+
+    /// CHECK: The wallet being granted permissions
+    pub delegate_wallet: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetDelegate<'info> {
+    /// Grants or replaces the delegate's permissions
+    pub fn set(&mut self, permissions: u8, bump: u8) -> Result<()> {
+        require!(
+            permissions != 0 && permissions & !VaultDelegate::ALL_PERMISSIONS == 0,
+            ErrorVault::InvalidDelegatePermissions
+        );
+
+        // `init_if_needed` also lets the owner replace an existing delegate's permissions
+        if self.delegate.vault == Pubkey::default() {
+            self.vault_account.delegate_count = increment(self.vault_account.delegate_count, 1)?;
+        }
+        self.delegate.set_inner(VaultDelegate {
+            vault: self.vault_account.key(),
+            delegate: self.delegate_wallet.key(),
+            permissions,
+            granted_by: self.owner.key(),
+            bump,
+        });
+
+        emit!(DelegateSet {
+            vault: self.vault_account.key(),
+            delegate: self.delegate_wallet.key(),
+            permissions,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveDelegate<'info> {
+    #[account(mut, has_one = owner)]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        close = owner,
+        constraint = delegate.vault == vault_account.key(),
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), delegate.delegate.as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Account<'info, VaultDelegate>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+impl<'info> RemoveDelegate<'info> {
+    /// Revokes every permission; `close = owner` refunds the delegate rent
+    pub fn remove(&mut self) -> Result<()> {
+        self.vault_account.delegate_count = decrement(self.vault_account.delegate_count, 1)?;
+
+        emit!(DelegateRemoved {
+            vault: self.vault_account.key(),
+            delegate: self.delegate.delegate,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
@@ -1,5 +1,11 @@
+use crate::constants::{APPROVAL_SEED, DELEGATE_SEED, GLOBAL_CONFIG_SEED, VAULT_SEED};
 use crate::errors::ErrorVault;
-use crate::state::{AssetType, VaultAccount};
+use crate::events::{DepositMade, WithdrawalMade};
+use crate::helper::{is_authorized, transfer_lamports};
+use crate::math::checked_add;
+use crate::state::{
+    ApprovalConfig, AssetType, GlobalConfig, OwnerAction, VaultAccount, VaultDelegate,
+};
 
 use anchor_lang::prelude::*;
 use anchor_spl::{
@@ -12,6 +18,13 @@ pub struct DepositSol<'info> {
     #[account(mut)]
     pub vault_account: Account<'info, VaultAccount>,
 
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
     #[account(mut)]
     pub depositor: Signer<'info>,
 
@@ -45,9 +58,17 @@ impl<'info> DepositSol<'info> {
         )?;
 
         // Update vault balance
-        self.vault_account.total_balance += amount;
+        self.vault_account.credit_sol(amount)?;
         self.vault_account.last_deposit_ts = Clock::get()?.unix_timestamp;
 
+        emit!(DepositMade {
+            vault: self.vault_account.key(),
+            depositor: self.depositor.key(),
+            asset_type: AssetType::SOL,
+            amount,
+            timestamp: self.vault_account.last_deposit_ts,
+        });
+
         Ok(())
     }
 }
@@ -57,6 +78,13 @@ pub struct DepositSplToken<'info> {
     #[account(mut)]
     pub vault_account: Account<'info, VaultAccount>,
 
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
     #[account(mut)]
     pub depositor: Signer<'info>,
 
@@ -107,8 +135,188 @@ impl<'info> DepositSplToken<'info> {
         transfer_checked(ctx, amount, self.mint.decimals)?;
 
         // Update vault balance
+        self.vault_account.credit_spl(&self.mint.key(), amount)?;
         self.vault_account.last_deposit_ts = Clock::get()?.unix_timestamp;
 
+        emit!(DepositMade {
+            vault: self.vault_account.key(),
+            depositor: self.depositor.key(),
+            asset_type: asset,
+            amount,
+            timestamp: self.vault_account.last_deposit_ts,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSol<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::WITHDRAW)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    /// CHECK: The vault's approval config PDA; uninitialized while the vault has no approvers
+    #[account(mut, seeds = [APPROVAL_SEED, vault_account.key().as_ref()], bump)]
+    pub approval_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawSol<'info> {
+    pub fn withdraw(&mut self, amount: u64) -> Result<()> {
+        // Only unreserved funds can leave the vault
+        require!(
+            self.vault_account.total_balance
+                >= checked_add(amount, self.vault_account.required_balance)?,
+            ErrorVault::InsufficientVaultBalance
+        );
+        ApprovalConfig::consume_action(
+            &self.approval_config,
+            &OwnerAction::WithdrawSol { amount },
+        )?;
+
+        // Fails if the vault PDA would drop below rent exemption
+        transfer_lamports(&self.vault_account.to_account_info(), &self.owner, amount)?;
+
+        // Update vault balance
+        self.vault_account.debit_sol(amount)?;
+
+        emit!(WithdrawalMade {
+            vault: self.vault_account.key(),
+            owner: self.owner.key(),
+            asset_type: AssetType::SOL,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSplToken<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::WITHDRAW)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+
+    /// CHECK: The vault's approval config PDA; uninitialized while the vault has no approvers
+    #[account(mut, seeds = [APPROVAL_SEED, vault_account.key().as_ref()], bump)]
+    pub approval_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault_account,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub owner_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawSplToken<'info> {
+    pub fn withdraw(&mut self, amount: u64) -> Result<()> {
+        // Only unreserved tokens can leave the vault
+        require!(
+            self.vault_token_account.amount
+                >= checked_add(amount, self.vault_account.spl_reserved(&self.mint.key()))?,
+            ErrorVault::InsufficientVaultBalance
+        );
+        ApprovalConfig::consume_action(
+            &self.approval_config,
+            &OwnerAction::WithdrawSpl {
+                mint: self.mint.key(),
+                amount,
+            },
+        )?;
+
+        let creator_key = self.vault_account.creator;
+        let vault_id = self.vault_account.vault_id.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            vault_id.as_ref(),
+            &[self.vault_account.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // Transfer tokens from vault back to the owner
+        let transfer_accounts = TransferChecked {
+            from: self.vault_token_account.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.owner_token_account.to_account_info(),
+            authority: self.vault_account.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(ctx, amount, self.mint.decimals)?;
+
+        // Update vault balance
+        self.vault_account.debit_spl(&self.mint.key(), amount)?;
+
+        emit!(WithdrawalMade {
+            vault: self.vault_account.key(),
+            owner: self.owner.key(),
+            asset_type: AssetType::SPLToken {
+                mint: self.mint.key(),
+            },
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
         Ok(())
     }
-}
\ No newline at end of file
+}
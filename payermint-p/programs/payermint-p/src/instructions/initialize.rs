@@ -1,7 +1,11 @@
 use anchor_lang::prelude::*;
-use crate::constants::GLOBAL_CONFIG_SEED;
+use crate::constants::{CONFIG_TIMELOCK_SECS, GLOBAL_CONFIG_SEED, MAX_FEE_LIMITS, MAX_FEE_TIERS};
 use crate::errors::ErrorVault;
-use crate::state::GlobalConfig;
+use crate::events::{ConfigChange, ConfigChangeApplied, ConfigChangeCancelled, ConfigChangeQueued};
+use crate::events::{ConfigOwnerAccepted, ConfigOwnerProposed, FeeOverrideSet, FeeTiersUpdated};
+use crate::events::{GlobalConfigInitialized, ProtocolPaused};
+use crate::math::checked_add_secs;
+use crate::state::{FeeLimit, FeeTier, GlobalConfig, VaultAccount};
 
 #[derive(Accounts)]
 pub struct InitializeGlobalConfig<'info> {
@@ -13,7 +17,7 @@ pub struct InitializeGlobalConfig<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + 32 + 32 + 2 + 8 + 1, // discriminator + owner + treasury + fee_bps + next_company_id + bump
+        space = 8 + 32 + 32 + 2 + 8 + 1 + 33 + 33 + 8 + 3 + 8 + 1 + GlobalConfig::FEE_TIERS_SPACE + GlobalConfig::FEE_LIMITS_SPACE + 1 + FeeLimit::INIT_SPACE + 8, // discriminator + owner + treasury + fee_bps + next_company_id + bump + pending_owner + pending_treasury + treasury_eta + pending_fee_bps + fee_eta + paused + fee_tiers + fee_limits + pending_fee_limit + fee_limit_eta
         seeds = [GLOBAL_CONFIG_SEED],
         bump
     )]
@@ -33,6 +37,23 @@ impl<'info> InitializeGlobalConfig<'info> {
             default_fee_bps,
             next_company_id: 1,
             bump,
+            pending_owner: None,
+            pending_treasury: None,
+            treasury_eta: 0,
+            pending_fee_bps: None,
+            fee_eta: 0,
+            paused: false,
+            fee_tiers: Vec::new(),
+            fee_limits: Vec::new(),
+            pending_fee_limit: None,
+            fee_limit_eta: 0,
+        });
+
+        emit!(GlobalConfigInitialized {
+            owner: self.owner.key(),
+            treasury: self.treasury.key(),
+            default_fee_bps,
+            timestamp: Clock::get()?.unix_timestamp,
         });
         Ok(())
     }
@@ -47,14 +68,268 @@ pub struct UpdateGlobalConfig<'info> {
 }
 
 impl<'info> UpdateGlobalConfig<'info> {
+    /// Queues a treasury change; it takes effect through `apply_config_change`
+    /// once `CONFIG_TIMELOCK_SECS` have passed
     pub fn update_treasury(&mut self, new_treasury: Pubkey) -> Result<()> {
-        self.global_config.treasury = new_treasury;
-        Ok(())
+        self.global_config.pending_treasury = Some(new_treasury);
+        self.global_config.treasury_eta =
+            checked_add_secs(Clock::get()?.unix_timestamp, CONFIG_TIMELOCK_SECS)?;
+        emit_queued(
+            ConfigChange::Treasury {
+                treasury: new_treasury,
+            },
+            self.global_config.treasury_eta,
+        )
     }
 
+    /// Queues a fee change behind the same timelock as the treasury
     pub fn update_default_fee(&mut self, new_fee_bps: u16) -> Result<()> {
         require!(new_fee_bps <= 10000, ErrorVault::InvalidFeeBps);
-        self.global_config.default_fee_bps = new_fee_bps;
+        self.global_config.pending_fee_bps = Some(new_fee_bps);
+        self.global_config.fee_eta =
+            checked_add_secs(Clock::get()?.unix_timestamp, CONFIG_TIMELOCK_SECS)?;
+        emit_queued(
+            ConfigChange::DefaultFee {
+                fee_bps: new_fee_bps,
+            },
+            self.global_config.fee_eta,
+        )
+    }
+
+    /// Queues a fee limit for one asset; `min_fee` and `max_fee` of 0 remove it.
+    /// A minimum raises fees, so it goes through the timelock like the default fee.
+    pub fn update_fee_limit(&mut self, fee_limit: FeeLimit) -> Result<()> {
+        require!(
+            fee_limit.max_fee == 0 || fee_limit.min_fee <= fee_limit.max_fee,
+            ErrorVault::InvalidFeeLimit
+        );
+        require!(
+            self.global_config
+                .fee_limit(&fee_limit.asset_type)
+                .is_some()
+                || self.global_config.fee_limits.len() < MAX_FEE_LIMITS,
+            ErrorVault::InvalidFeeLimit
+        );
+        self.global_config.pending_fee_limit = Some(fee_limit.clone());
+        self.global_config.fee_limit_eta =
+            checked_add_secs(Clock::get()?.unix_timestamp, CONFIG_TIMELOCK_SECS)?;
+        emit_queued(
+            ConfigChange::FeeLimit { fee_limit },
+            self.global_config.fee_limit_eta,
+        )
+    }
+
+    /// Emits one `ConfigChangeCancelled` per queued change it drops
+    pub fn cancel_config_change(&mut self) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        if let Some(treasury) = self.global_config.pending_treasury.take() {
+            emit!(ConfigChangeCancelled {
+                change: ConfigChange::Treasury { treasury },
+                timestamp,
+            });
+        }
+        if let Some(fee_bps) = self.global_config.pending_fee_bps.take() {
+            emit!(ConfigChangeCancelled {
+                change: ConfigChange::DefaultFee { fee_bps },
+                timestamp,
+            });
+        }
+        if let Some(fee_limit) = self.global_config.pending_fee_limit.take() {
+            emit!(ConfigChangeCancelled {
+                change: ConfigChange::FeeLimit { fee_limit },
+                timestamp,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn propose_config_owner(&mut self, new_owner: Pubkey) -> Result<()> {
+        self.global_config.pending_owner = Some(new_owner);
+        self.emit_owner_proposal()
+    }
+
+    pub fn cancel_config_owner_proposal(&mut self) -> Result<()> {
+        self.global_config.pending_owner = None;
+        self.emit_owner_proposal()
+    }
+
+    fn emit_owner_proposal(&self) -> Result<()> {
+        emit!(ConfigOwnerProposed {
+            owner: self.global_config.owner,
+            pending_owner: self.global_config.pending_owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Emergency switch; takes effect immediately, unlike the timelocked changes
+    pub fn set_protocol_paused(&mut self, paused: bool) -> Result<()> {
+        self.global_config.paused = paused;
+
+        emit!(ProtocolPaused {
+            paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Tiers only lower the rate below `default_fee_bps`, so they are not timelocked.
+    /// Volume is measured in lamports of SOL payouts only; SPL payouts never count
+    /// toward a tier, so vaults that pay mostly in tokens stay on the default rate.
+    pub fn set_fee_tiers(&mut self, fee_tiers: Vec<FeeTier>) -> Result<()> {
+        require!(
+            fee_tiers.len() <= MAX_FEE_TIERS,
+            ErrorVault::InvalidFeeTiers
+        );
+        require!(
+            fee_tiers.iter().all(|tier| tier.fee_bps <= 10000),
+            ErrorVault::InvalidFeeTiers
+        );
+        require!(
+            fee_tiers
+                .windows(2)
+                .all(|pair| pair[0].min_volume < pair[1].min_volume),
+            ErrorVault::InvalidFeeTiers
+        );
+        self.global_config.fee_tiers = fee_tiers.clone();
+
+        emit!(FeeTiersUpdated {
+            fee_tiers,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+fn emit_queued(change: ConfigChange, eta: i64) -> Result<()> {
+    emit!(ConfigChangeQueued {
+        change,
+        eta,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVaultFeeOverride<'info> {
+    #[account(has_one = owner)]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    pub owner: Signer<'info>,
+}
+
+impl<'info> SetVaultFeeOverride<'info> {
+    /// Negotiated rate for one vault; `None` returns it to the default and tiers.
+    /// The override is a discount only: `effective_fee_bps` takes the lowest of the
+    /// default, the override and any tier, so a rate above the default has no effect.
+    pub fn set(&mut self, fee_override_bps: Option<u16>) -> Result<()> {
+        if let Some(fee_bps) = fee_override_bps {
+            require!(fee_bps <= 10000, ErrorVault::InvalidFeeBps);
+        }
+        self.vault_account.fee_override_bps = fee_override_bps;
+
+        emit!(FeeOverrideSet {
+            vault: self.vault_account.key(),
+            fee_override_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ApplyConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+impl<'info> ApplyConfigChange<'info> {
+    /// Applies every queued change whose timelock has elapsed. Permissionless,
+    /// since the owner already approved the change when queueing it.
+    pub fn apply(&mut self) -> Result<()> {
+        let config = &mut self.global_config;
+        require!(
+            config.pending_treasury.is_some()
+                || config.pending_fee_bps.is_some()
+                || config.pending_fee_limit.is_some(),
+            ErrorVault::NoPendingChange
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut applied = false;
+        if let Some(treasury) = config.pending_treasury {
+            if now >= config.treasury_eta {
+                config.treasury = treasury;
+                config.pending_treasury = None;
+                applied = true;
+                emit!(ConfigChangeApplied {
+                    change: ConfigChange::Treasury { treasury },
+                    timestamp: now,
+                });
+            }
+        }
+        if let Some(fee_bps) = config.pending_fee_bps {
+            if now >= config.fee_eta {
+                config.default_fee_bps = fee_bps;
+                config.pending_fee_bps = None;
+                applied = true;
+                emit!(ConfigChangeApplied {
+                    change: ConfigChange::DefaultFee { fee_bps },
+                    timestamp: now,
+                });
+            }
+        }
+        if config.pending_fee_limit.is_some() && now >= config.fee_limit_eta {
+            if let Some(fee_limit) = config.pending_fee_limit.take() {
+                config
+                    .fee_limits
+                    .retain(|limit| limit.asset_type != fee_limit.asset_type);
+                if fee_limit.min_fee > 0 || fee_limit.max_fee > 0 {
+                    config.fee_limits.push(fee_limit.clone());
+                }
+                applied = true;
+                emit!(ConfigChangeApplied {
+                    change: ConfigChange::FeeLimit { fee_limit },
+                    timestamp: now,
+                });
+            }
+        }
+        require!(applied, ErrorVault::TimelockNotElapsed);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct AcceptConfigOwner<'info> {
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.pending_owner == Some(new_owner.key()) @ ErrorVault::NotPendingOwner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub new_owner: Signer<'info>,
+}
+
+impl<'info> AcceptConfigOwner<'info> {
+    pub fn accept(&mut self) -> Result<()> {
+        let previous_owner = self.global_config.owner;
+        self.global_config.owner = self.new_owner.key();
+        self.global_config.pending_owner = None;
+
+        emit!(ConfigOwnerAccepted {
+            previous_owner,
+            new_owner: self.new_owner.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 }
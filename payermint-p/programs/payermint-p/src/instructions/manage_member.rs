@@ -0,0 +1,204 @@
+use crate::constants::{DELEGATE_SEED, MEMBER_SEED};
+use crate::errors::ErrorVault;
+use crate::events::{MemberAdded, MemberRemoved, MemberUpdated};
+use crate::helper::{is_authorized, validate_member_fields};
+use crate::math::{decrement, increment};
+use crate::state::{Member, VaultAccount, VaultDelegate};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(role: String)]
+pub struct AddMember<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::MANAGE_MEMBERS)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = Member::SPACE,
+        seeds = [MEMBER_SEED, vault_account.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+
+    /// CHECK: The wallet address of the member
+    pub wallet: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddMember<'info> {
+    pub fn add(
+        &mut self,
+        role: String,
+        allocation_bps: Option<u16>,
+        sol_payment_allocation: Option<u64>,
+        spl_token_allocation: Option<u64>,
+        metadata_uri: Option<String>,
+        bump: u8,
+    ) -> Result<()> {
+        validate_member_fields(&role, allocation_bps, &metadata_uri)?;
+
+        self.member.set_inner(Member {
+            vault: self.vault_account.key(),
+            wallet: self.wallet.key(),
+            allocation_bps,
+            sol_payment_allocation,
+            spl_token_allocation,
+            role,
+            is_active: true,
+            metadata_uri,
+            bump,
+        });
+
+        self.vault_account.active_member_count =
+            increment(self.vault_account.active_member_count, 1)?;
+        self.vault_account.member_count = increment(self.vault_account.member_count, 1)?;
+
+        emit!(MemberAdded {
+            vault: self.vault_account.key(),
+            member: self.member.key(),
+            wallet: self.wallet.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateMember<'info> {
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::MANAGE_MEMBERS)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        constraint = member.vault == vault_account.key(),
+        seeds = [MEMBER_SEED, vault_account.key().as_ref(), member.wallet.as_ref()],
+        bump = member.bump
+    )]
+    pub member: Account<'info, Member>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+}
+
+impl<'info> UpdateMember<'info> {
+    pub fn update_allocation(&mut self, allocation_bps: Option<u16>) -> Result<()> {
+        if let Some(bps) = allocation_bps {
+            require!(bps <= 10000, ErrorVault::InvalidAllocationBps);
+        }
+        self.member.allocation_bps = allocation_bps;
+        self.emit_update()
+    }
+
+    pub fn update_payment_allocations(
+        &mut self,
+        sol_allocation: Option<u64>,
+        spl_allocation: Option<u64>,
+    ) -> Result<()> {
+        self.member.sol_payment_allocation = sol_allocation;
+        self.member.spl_token_allocation = spl_allocation;
+        self.emit_update()
+    }
+
+    pub fn toggle_active_status(&mut self) -> Result<()> {
+        self.member.is_active = !self.member.is_active;
+        let active_member_count = self.vault_account.active_member_count;
+        self.vault_account.active_member_count = if self.member.is_active {
+            increment(active_member_count, 1)?
+        } else {
+            decrement(active_member_count, 1)?
+        };
+        self.emit_update()
+    }
+
+    fn emit_update(&self) -> Result<()> {
+        emit!(MemberUpdated {
+            vault: self.vault_account.key(),
+            member: self.member.key(),
+            allocation_bps: self.member.allocation_bps,
+            sol_payment_allocation: self.member.sol_payment_allocation,
+            spl_token_allocation: self.member.spl_token_allocation,
+            is_active: self.member.is_active,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveMember<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::MANAGE_MEMBERS)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        close = owner,
+        constraint = member.vault == vault_account.key(),
+        seeds = [MEMBER_SEED, vault_account.key().as_ref(), member.wallet.as_ref()],
+        bump = member.bump
+    )]
+    pub member: Account<'info, Member>,
+
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+}
+
+impl<'info> RemoveMember<'info> {
+    pub fn remove(&mut self) -> Result<()> {
+        if self.member.is_active {
+            self.vault_account.active_member_count =
+                decrement(self.vault_account.active_member_count, 1)?;
+        }
+        self.vault_account.member_count = decrement(self.vault_account.member_count, 1)?;
+
+        emit!(MemberRemoved {
+            vault: self.vault_account.key(),
+            member: self.member.key(),
+            wallet: self.member.wallet,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
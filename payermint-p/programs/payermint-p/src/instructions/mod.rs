@@ -1,15 +1,31 @@
 pub mod create_vault;
-pub mod member;
+pub mod manage_member;
 pub mod trigger_payout;
 pub mod deposit;
 pub mod initialize;
 pub mod process_scheduled;
 pub mod bulk_operation;
+pub mod claim_payment;
+pub mod stream;
+pub mod vesting;
+pub mod close_vault;
+pub mod approval;
+pub mod delegate;
+pub mod sync_balances;
+pub mod close_receipt;
 
 pub use create_vault::*;
-pub use member::*;
+pub use manage_member::*;
 pub use trigger_payout::*;
 pub use deposit::*;
 pub use initialize::*;
 pub use process_scheduled::*;
-pub use bulk_operation::*;
\ No newline at end of file
+pub use bulk_operation::*;
+pub use claim_payment::*;
+pub use stream::*;
+pub use vesting::*;
+pub use close_vault::*;
+pub use approval::*;
+pub use delegate::*;
+pub use sync_balances::*;
+pub use close_receipt::*;
\ No newline at end of file
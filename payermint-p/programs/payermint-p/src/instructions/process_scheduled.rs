@@ -1,10 +1,21 @@
 use anchor_lang::prelude::*;
-use crate::constants::GLOBAL_CONFIG_SEED;
-use crate::{ErrorVault, GlobalConfig, Member, VaultAccount};
-
+use crate::constants::{GLOBAL_CONFIG_SEED, RECEIPT_SEED};
+use crate::events::{PayoutExecuted, PayoutSource, ScheduleAdvanced};
+use crate::helper::{emit_payout, split_payout, transfer_lamports};
+use crate::math::{bps_of, checked_add, checked_add_secs, increment, FeeSplit};
+use crate::{
+    AssetType, ErrorVault, GlobalConfig, Member, PayoutCycle, PayoutReceipt, PayoutSchedule,
+    VaultAccount,
+};
+
+/// Permissionless crank: any keeper may pay a due member once per period and
+/// receives the vault's `keeper_reward`. `period` is the start timestamp of the
+/// period being paid; the keeper also pays the receipt rent. A period stays
+/// payable until the next one begins.
 #[derive(Accounts)]
+#[instruction(period: i64)]
 pub struct ProcessScheduledPayout<'info> {
-    #[account(mut, has_one = owner)]
+    #[account(mut, constraint = !vault_account.paused @ ErrorVault::VaultPaused)]
     pub vault_account: Account<'info, VaultAccount>,
 
     #[account(
@@ -13,10 +24,25 @@ pub struct ProcessScheduledPayout<'info> {
     )]
     pub member: Account<'info, Member>,
 
+    #[account(
+        init,
+        payer = keeper,
+        space = PayoutReceipt::SPACE,
+        seeds = [
+            RECEIPT_SEED,
+            vault_account.key().as_ref(),
+            &period.to_le_bytes(),
+            member.key().as_ref()
+        ],
+        bump
+    )]
+    pub receipt: Account<'info, PayoutReceipt>,
+
     #[account(
         mut,
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
@@ -34,71 +60,174 @@ pub struct ProcessScheduledPayout<'info> {
     )]
     pub member_wallet: AccountInfo<'info>,
 
-    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub keeper: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> ProcessScheduledPayout<'info> {
-    pub fn process_scheduled_payout(&mut self) -> Result<()> {
+    pub fn process_scheduled_payout(&mut self, period: i64, receipt_bump: u8) -> Result<()> {
         // Clone payout schedule so we don't hold an immutable borrow
-        let payout_schedule = self
+        let mut payout_schedule = self
             .vault_account
             .payout_schedule
             .clone()
             .ok_or(ErrorVault::PayoutScheduleNotActive)?;
 
         require!(payout_schedule.active, ErrorVault::PayoutScheduleNotActive);
+        let current_time = Clock::get()?.unix_timestamp;
+
+        // Periods close on time alone, so members that are deactivated, removed or
+        // have nothing to be paid never hold the schedule back
+        let current_period = payout_schedule.current_period(current_time)?;
+        if current_period != payout_schedule.next_payout_ts {
+            self.advance_schedule(&mut payout_schedule, current_period, current_time);
+        }
 
         // Check if it's time for the next payout
-        let current_time = Clock::get()?.unix_timestamp;
         require!(
             current_time >= payout_schedule.next_payout_ts,
             ErrorVault::PayoutTimeNotReached
         );
 
-        // Calculate payout amount based on member allocation
+        // Each member is paid exactly once per period; the receipt PDA enforces it
+        require!(
+            period == payout_schedule.next_payout_ts,
+            ErrorVault::InvalidPayoutPeriod
+        );
+
+        // Allocations are computed against the balance at the start of the period
+        if self.vault_account.period_paid_count == 0 {
+            self.vault_account.period_start_balance = self.vault_account.total_balance;
+        }
+
+        // Calculate payout amount based on member allocation. Members without one
+        // are skipped: the receipt is still written but nothing is transferred.
         let payout_amount = if let Some(allocation_bps) = self.member.allocation_bps {
-            (self.vault_account.total_balance * allocation_bps as u64) / 10000
-        } else if let Some(fixed_amount) = self.member.sol_payment_allocation {
-            fixed_amount
+            bps_of(self.vault_account.period_start_balance, allocation_bps)?
         } else {
-            return Err(ErrorVault::InvalidAllocationBps.into());
+            self.member.sol_payment_allocation.unwrap_or(0)
         };
 
-        // Calculate service fee
-        let service_fee = (payout_amount * self.global_config.default_fee_bps as u64) / 10000;
-        let net_amount = payout_amount - service_fee;
+        // Calculate service fee and who bears it. Skipping a member charges no fee
+        // and does not earn the keeper a reward.
+        let (
+            FeeSplit {
+                service_fee,
+                net_amount,
+                total_cost,
+            },
+            keeper_reward,
+        ) = if payout_amount > 0 {
+            (
+                split_payout(
+                    &self.global_config,
+                    &self.vault_account,
+                    &AssetType::SOL,
+                    payout_amount,
+                )?,
+                self.vault_account.keeper_reward,
+            )
+        } else {
+            (
+                FeeSplit {
+                    service_fee: 0,
+                    net_amount: 0,
+                    total_cost: 0,
+                },
+                0,
+            )
+        };
+        let total_debit = checked_add(total_cost, keeper_reward)?;
 
-        // Ensure vault has sufficient balance
+        // Ensure vault has sufficient unreserved balance
         require!(
-            self.vault_account.total_balance >= payout_amount,
+            self.vault_account.total_balance
+                >= checked_add(total_debit, self.vault_account.required_balance)?,
             ErrorVault::InsufficientVaultBalance
         );
 
         // Transfer service fee to treasury
         if service_fee > 0 {
-            **self
-                .vault_account
-                .to_account_info()
-                .try_borrow_mut_lamports()? -= service_fee;
-            **self.treasury.try_borrow_mut_lamports()? += service_fee;
+            transfer_lamports(
+                &self.vault_account.to_account_info(),
+                &self.treasury,
+                service_fee,
+            )?;
         }
 
         // Transfer net amount to member
-        **self
-            .vault_account
-            .to_account_info()
-            .try_borrow_mut_lamports()? -= net_amount;
-        **self.member_wallet.try_borrow_mut_lamports()? += net_amount;
+        if net_amount > 0 {
+            transfer_lamports(
+                &self.vault_account.to_account_info(),
+                &self.member_wallet,
+                net_amount,
+            )?;
+        }
+
+        // Reward the keeper for cranking
+        if keeper_reward > 0 {
+            transfer_lamports(
+                &self.vault_account.to_account_info(),
+                &self.keeper,
+                keeper_reward,
+            )?;
+        }
 
         // Update vault balance
-        self.vault_account.total_balance -= payout_amount;
+        self.vault_account.debit_sol(total_debit)?;
+        self.vault_account
+            .record_sol_volume(payout_amount, current_time);
+
+        self.receipt.set_inner(PayoutReceipt {
+            vault: self.vault_account.key(),
+            member: self.member.key(),
+            wallet: self.member.wallet,
+            cycle: PayoutCycle::Period { start_ts: period },
+            asset_type: AssetType::SOL,
+            amount: payout_amount,
+            service_fee,
+            net_amount,
+            paid_at: current_time,
+            claimed: true,
+            bump: receipt_bump,
+            payer: self.keeper.key(),
+        });
+
+        if payout_amount > 0 {
+            emit_payout(PayoutExecuted {
+                vault: self.vault_account.key(),
+                recipient: self.member.wallet,
+                source: PayoutSource::Scheduled,
+                asset_type: AssetType::SOL,
+                amount: payout_amount,
+                service_fee,
+                net_amount,
+                batch_id: None,
+                timestamp: current_time,
+            });
+        }
 
-        // Update next payout timestamp
-        let mut updated_schedule = payout_schedule;
-        updated_schedule.next_payout_ts += updated_schedule.interval;
-        self.vault_account.payout_schedule = Some(updated_schedule);
+        self.vault_account.period_paid_count = increment(self.vault_account.period_paid_count, 1)?;
+        self.vault_account.paid_through = self
+            .vault_account
+            .paid_through
+            .max(checked_add_secs(period, payout_schedule.interval)?);
+        self.vault_account.payout_schedule = Some(payout_schedule);
 
         Ok(())
     }
+
+    fn advance_schedule(&mut self, payout_schedule: &mut PayoutSchedule, period: i64, now: i64) {
+        let paid_period = payout_schedule.next_payout_ts;
+        payout_schedule.next_payout_ts = period;
+        self.vault_account.period_paid_count = 0;
+
+        emit!(ScheduleAdvanced {
+            vault: self.vault_account.key(),
+            paid_period,
+            next_payout_ts: payout_schedule.next_payout_ts,
+            timestamp: now,
+        });
+    }
 }
@@ -0,0 +1,552 @@
+use crate::constants::DELEGATE_SEED;
+use crate::constants::GLOBAL_CONFIG_SEED;
+use crate::constants::STREAM_SEED;
+use crate::constants::VAULT_SEED;
+use crate::errors::ErrorVault;
+use crate::events::{PayoutExecuted, PayoutSource, StreamAction, StreamCreated, StreamUpdated};
+use crate::helper::{
+    calculate_service_fee, emit_payout, is_authorized, release_funds, reserve_funds, split_payout,
+    transfer_lamports,
+};
+use crate::math::{
+    checked_add, checked_sub, decrement, increment, pro_rata, reserved_fee, split_fee, FeeSplit,
+};
+use crate::state::{AssetType, GlobalConfig, Member, PaymentStream, VaultAccount, VaultDelegate};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+pub struct CreateStream<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::EXECUTE_PAYOUTS)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        constraint = member.vault == vault_account.key(),
+        constraint = member.is_active @ ErrorVault::MemberNotActive
+    )]
+    pub member: Account<'info, Member>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PaymentStream::SPACE,
+        seeds = [STREAM_SEED, member.key().as_ref()],
+        bump
+    )]
+    pub stream: Account<'info, PaymentStream>,
+
+    /// Vault token account for the streamed mint, required for SPL streams
+    #[account(
+        constraint = vault_token_account.owner == vault_account.key() @ ErrorVault::InvalidTokenAccount
+    )]
+    pub vault_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateStream<'info> {
+    pub fn create(
+        &mut self,
+        asset_type: AssetType,
+        rate_per_second: u64,
+        start_ts: i64,
+        end_ts: Option<i64>,
+        deposit: u64,
+        bump: u8,
+    ) -> Result<()> {
+        require!(rate_per_second > 0, ErrorVault::InvalidStreamConfig);
+        if let Some(end_ts) = end_ts {
+            require!(end_ts > start_ts, ErrorVault::InvalidStreamConfig);
+        }
+
+        // The fee on the whole deposit is fixed now and reserved with it when grossed up
+        let FeeSplit {
+            service_fee,
+            total_cost,
+            ..
+        } = split_payout(
+            &self.global_config,
+            &self.vault_account,
+            &asset_type,
+            deposit,
+        )?;
+        reserve_funds(
+            &mut self.vault_account,
+            &asset_type,
+            total_cost,
+            self.vault_token_account
+                .as_deref()
+                .map(|account| &**account),
+        )?;
+
+        self.stream.set_inner(PaymentStream {
+            vault: self.vault_account.key(),
+            member: self.member.key(),
+            wallet: self.member.wallet,
+            payer: self.payer.key(),
+            asset_type: asset_type.clone(),
+            rate_per_second,
+            start_ts,
+            end_ts,
+            deposited: deposit,
+            withdrawn: 0,
+            accrued: 0,
+            checkpoint_ts: start_ts,
+            fee_mode: self.vault_account.fee_mode,
+            service_fee,
+            fees_paid: 0,
+            paused: false,
+            cancelled: false,
+            bump,
+        });
+
+        self.vault_account.stream_count = increment(self.vault_account.stream_count, 1)?;
+
+        emit!(StreamCreated {
+            vault: self.vault_account.key(),
+            stream: self.stream.key(),
+            wallet: self.member.wallet,
+            asset_type,
+            rate_per_second,
+            start_ts,
+            end_ts,
+            deposit,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateStream<'info> {
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::EXECUTE_PAYOUTS)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        mut,
+        constraint = stream.vault == vault_account.key(),
+        constraint = !stream.cancelled @ ErrorVault::StreamCancelled
+    )]
+    pub stream: Account<'info, PaymentStream>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// Vault token account for the streamed mint, required to top up SPL streams
+    #[account(
+        constraint = vault_token_account.owner == vault_account.key() @ ErrorVault::InvalidTokenAccount
+    )]
+    pub vault_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+}
+
+impl<'info> UpdateStream<'info> {
+    pub fn top_up(&mut self, amount: u64) -> Result<()> {
+        let asset_type = self.stream.asset_type.clone();
+        // Top ups keep the stream's fee mode; their fee is fixed like the deposit's
+        let service_fee = calculate_service_fee(
+            &self.global_config,
+            &self.vault_account,
+            &asset_type,
+            amount,
+        )?;
+        let FeeSplit {
+            service_fee,
+            total_cost,
+            ..
+        } = split_fee(amount, service_fee, self.stream.fee_mode)?;
+        reserve_funds(
+            &mut self.vault_account,
+            &asset_type,
+            total_cost,
+            self.vault_token_account
+                .as_deref()
+                .map(|account| &**account),
+        )?;
+
+        // Time spent fully vested does not accrue retroactively once topped up
+        let now = Clock::get()?.unix_timestamp;
+        self.stream.checkpoint(now);
+        self.stream.deposited = checked_add(self.stream.deposited, amount)?;
+        self.stream.service_fee = checked_add(self.stream.service_fee, service_fee)?;
+
+        self.emit_update(StreamAction::TopUp, amount, now);
+        Ok(())
+    }
+
+    pub fn pause(&mut self) -> Result<()> {
+        require!(!self.stream.paused, ErrorVault::StreamPaused);
+        let now = Clock::get()?.unix_timestamp;
+        self.stream.checkpoint(now);
+        self.stream.paused = true;
+
+        self.emit_update(StreamAction::Pause, 0, now);
+        Ok(())
+    }
+
+    pub fn resume(&mut self) -> Result<()> {
+        require!(self.stream.paused, ErrorVault::StreamNotPaused);
+        // Checkpoint while still paused so the paused time does not accrue
+        let now = Clock::get()?.unix_timestamp;
+        self.stream.checkpoint(now);
+        self.stream.paused = false;
+
+        self.emit_update(StreamAction::Resume, 0, now);
+        Ok(())
+    }
+
+    /// Stops accrual and returns the unvested part of the deposit to the vault.
+    /// The member can still withdraw whatever vested before cancellation.
+    pub fn cancel(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.stream.checkpoint(now);
+        self.stream.cancelled = true;
+
+        // Only the fee on what vested stays owed
+        let accrued = self.stream.accrued;
+        let service_fee = pro_rata(self.stream.service_fee, accrued, self.stream.deposited)?
+            .max(self.stream.fees_paid);
+        let unvested = checked_sub(self.stream.deposited, accrued)?;
+        let released_fee = reserved_fee(
+            checked_sub(self.stream.service_fee, service_fee)?,
+            self.stream.fee_mode,
+        );
+
+        let asset_type = self.stream.asset_type.clone();
+        let released = checked_add(unvested, released_fee)?;
+        release_funds(&mut self.vault_account, &asset_type, released)?;
+        self.stream.deposited = accrued;
+        self.stream.service_fee = service_fee;
+
+        self.emit_update(StreamAction::Cancel, released, now);
+        Ok(())
+    }
+
+    fn emit_update(&self, action: StreamAction, amount: u64, timestamp: i64) {
+        emit!(StreamUpdated {
+            vault: self.vault_account.key(),
+            stream: self.stream.key(),
+            action,
+            amount,
+            timestamp,
+        });
+    }
+}
+
+#[derive(Accounts)]
+pub struct CloseStream<'info> {
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::EXECUTE_PAYOUTS)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        mut,
+        close = payer,
+        constraint = stream.vault == vault_account.key(),
+        constraint = stream.is_settled() @ ErrorVault::StreamNotSettled,
+        seeds = [STREAM_SEED, stream.member.as_ref()],
+        bump = stream.bump
+    )]
+    pub stream: Account<'info, PaymentStream>,
+
+    /// CHECK: Original payer of the stream rent, refunded on close
+    #[account(
+        mut,
+        constraint = payer.key() == stream.payer @ ErrorVault::InvalidRentReceiver
+    )]
+    pub payer: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+}
+
+impl<'info> CloseStream<'info> {
+    /// Closes a stream once the member has withdrawn everything it will ever vest,
+    /// i.e. after cancellation or when the deposit ran out, freeing the member's
+    /// stream PDA for a new one. A settled stream holds no reservation.
+    pub fn close(&mut self) -> Result<()> {
+        self.vault_account.stream_count = decrement(self.vault_account.stream_count, 1)?;
+
+        emit!(StreamUpdated {
+            vault: self.vault_account.key(),
+            stream: self.stream.key(),
+            action: StreamAction::Close,
+            amount: 0,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSolStream<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(mut, constraint = !vault_account.paused @ ErrorVault::VaultPaused)]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        constraint = stream.vault == vault_account.key(),
+        constraint = stream.wallet == wallet.key() @ ErrorVault::InvalidClaimant,
+        constraint = stream.asset_type == AssetType::SOL @ ErrorVault::AssetMismatch
+    )]
+    pub stream: Account<'info, PaymentStream>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: Treasury account for fee collection
+    #[account(
+        mut,
+        constraint = treasury.key() == global_config.treasury @ ErrorVault::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawSolStream<'info> {
+    pub fn withdraw(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let vested = self.stream.vested_at(now);
+        let amount = checked_sub(vested, self.stream.withdrawn)?;
+        require!(amount > 0, ErrorVault::NothingToWithdraw);
+
+        let service_fee = self.stream.fee_due(vested)?;
+        let FeeSplit {
+            service_fee,
+            net_amount,
+            total_cost,
+        } = split_fee(amount, service_fee, self.stream.fee_mode)?;
+
+        // Transfer service fee to treasury
+        if service_fee > 0 {
+            transfer_lamports(
+                &self.vault_account.to_account_info(),
+                &self.treasury,
+                service_fee,
+            )?;
+        }
+
+        // Transfer net amount to the member wallet
+        transfer_lamports(
+            &self.vault_account.to_account_info(),
+            &self.wallet,
+            net_amount,
+        )?;
+
+        self.vault_account.debit_sol(total_cost)?;
+        self.vault_account.record_sol_volume(amount, now);
+        release_funds(&mut self.vault_account, &AssetType::SOL, total_cost)?;
+        self.stream.withdrawn = checked_add(self.stream.withdrawn, amount)?;
+        self.stream.fees_paid = checked_add(self.stream.fees_paid, service_fee)?;
+
+        emit_payout(PayoutExecuted {
+            vault: self.vault_account.key(),
+            recipient: self.wallet.key(),
+            source: PayoutSource::Stream,
+            asset_type: AssetType::SOL,
+            amount,
+            service_fee,
+            net_amount,
+            batch_id: None,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSplStream<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(mut, constraint = !vault_account.paused @ ErrorVault::VaultPaused)]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        mut,
+        constraint = stream.vault == vault_account.key(),
+        constraint = stream.wallet == wallet.key() @ ErrorVault::InvalidClaimant,
+        constraint = stream.asset_type == AssetType::SPLToken { mint: mint.key() } @ ErrorVault::AssetMismatch
+    )]
+    pub stream: Box<Account<'info, PaymentStream>>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault_account,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Treasury account for fee collection
+    #[account(
+        constraint = treasury.key() == global_config.treasury @ ErrorVault::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = wallet,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = wallet,
+        associated_token::mint = mint,
+        associated_token::authority = wallet,
+        associated_token::token_program = token_program,
+    )]
+    pub wallet_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawSplStream<'info> {
+    pub fn withdraw(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let vested = self.stream.vested_at(now);
+        let amount = checked_sub(vested, self.stream.withdrawn)?;
+        require!(amount > 0, ErrorVault::NothingToWithdraw);
+
+        let service_fee = self.stream.fee_due(vested)?;
+        let FeeSplit {
+            service_fee,
+            net_amount,
+            total_cost,
+        } = split_fee(amount, service_fee, self.stream.fee_mode)?;
+
+        let creator_key = self.vault_account.creator;
+        let vault_id = self.vault_account.vault_id.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            vault_id.as_ref(),
+            &[self.vault_account.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // Transfer service fee to treasury token account
+        if service_fee > 0 {
+            let fee_accounts = TransferChecked {
+                from: self.vault_token_account.to_account_info(),
+                mint: self.mint.to_account_info(),
+                to: self.treasury_token_account.to_account_info(),
+                authority: self.vault_account.to_account_info(),
+            };
+            let ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                fee_accounts,
+                signer_seeds,
+            );
+            transfer_checked(ctx, service_fee, self.mint.decimals)?;
+        }
+
+        // Transfer net amount to the member token account
+        let payout_accounts = TransferChecked {
+            from: self.vault_token_account.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.wallet_token_account.to_account_info(),
+            authority: self.vault_account.to_account_info(),
+        };
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            payout_accounts,
+            signer_seeds,
+        );
+        transfer_checked(ctx, net_amount, self.mint.decimals)?;
+        self.vault_account.debit_spl(&self.mint.key(), total_cost)?;
+
+        let asset_type = self.stream.asset_type.clone();
+        release_funds(&mut self.vault_account, &asset_type, total_cost)?;
+        self.stream.withdrawn = checked_add(self.stream.withdrawn, amount)?;
+        self.stream.fees_paid = checked_add(self.stream.fees_paid, service_fee)?;
+
+        emit_payout(PayoutExecuted {
+            vault: self.vault_account.key(),
+            recipient: self.wallet.key(),
+            source: PayoutSource::Stream,
+            asset_type,
+            amount,
+            service_fee,
+            net_amount,
+            batch_id: None,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::errors::ErrorVault;
+use crate::events::BalancesSynced;
+use crate::state::VaultAccount;
+
+/// Permissionless: anyone may bring a vault's tracked balances in line with what
+/// it actually holds, e.g. after SOL or tokens were sent to it directly.
+#[derive(Accounts)]
+pub struct SyncBalances<'info> {
+    #[account(mut)]
+    pub vault_account: Account<'info, VaultAccount>,
+}
+
+impl<'info> SyncBalances<'info> {
+    /// Sets `sol_balance` to the lamports held above rent exemption and, for each
+    /// vault ATA in `remaining_accounts`, that mint's entry in `spl_balances` to the
+    /// ATA amount, whether or not the mint is still whitelisted (see
+    /// `VaultAccount::sync_spl_balance`). Mints without an ATA in `remaining_accounts`
+    /// are left untouched.
+    pub fn sync(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let vault_info = self.vault_account.to_account_info();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+        self.vault_account
+            .set_sol_balance(vault_info.lamports().saturating_sub(rent_exempt_minimum));
+
+        let vault_key = self.vault_account.key();
+        for vault_token_info in remaining_accounts {
+            let vault_token_account = InterfaceAccount::<TokenAccount>::try_from(vault_token_info)?;
+            require_keys_eq!(
+                vault_token_info.key(),
+                get_associated_token_address_with_program_id(
+                    &vault_key,
+                    &vault_token_account.mint,
+                    vault_token_info.owner
+                ),
+                ErrorVault::InvalidTokenAccount
+            );
+            self.vault_account
+                .sync_spl_balance(&vault_token_account.mint, vault_token_account.amount)?;
+        }
+
+        emit!(BalancesSynced {
+            vault: vault_key,
+            sol_balance: self.vault_account.sol_balance,
+            spl_balances: self.vault_account.spl_balances.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
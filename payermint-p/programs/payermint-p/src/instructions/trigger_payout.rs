@@ -1,15 +1,35 @@
+use crate::constants::APPROVAL_SEED;
 use crate::constants::BATCH_SEED;
+use crate::constants::DELEGATE_SEED;
 use crate::constants::GLOBAL_CONFIG_SEED;
+use crate::constants::RECEIPT_SEED;
+use crate::constants::VAULT_SEED;
 use crate::errors::ErrorVault;
-use crate::state::{GlobalConfig, Member, PayrollBatch, VaultAccount};
+use crate::events::{BatchClosed, BatchCreated, BatchFinalized, PayoutExecuted, PayoutSource};
+use crate::helper::{
+    effective_fee_bps, emit_payout, is_authorized, split_payout, transfer_lamports,
+};
+use crate::math::{bps_of, checked_add, checked_add_secs, decrement, increment, FeeSplit};
+use crate::state::{
+    AssetType, GlobalConfig, Member, PayoutCycle, PayoutReceipt, PayrollBatch, VaultAccount,
+    VaultDelegate,
+};
 use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
 #[derive(Accounts)]
 #[instruction(batch_id: u64)]
 pub struct CreatePayrollBatch<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    #[account(mut, has_one = owner)]
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::CREATE_BATCHES)
+            @ ErrorVault::Unauthorized
+    )]
     pub vault_account: Account<'info, VaultAccount>,
 
     #[account(
@@ -22,19 +42,27 @@ pub struct CreatePayrollBatch<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + 32 + 8 + 8 + 8 + 8 + 4 + 1 + 1, // discriminator + vault + batch_id + timestamp + total_amount + service_fee + payout_count + finalized + bump
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 4 + 1 + 1 + 32 + 8 + 8 + 8 + 2 + 4 + 1 + AssetType::INIT_SPACE + 8, // discriminator + vault + batch_id + timestamp + total_amount + service_fee + payout_count + finalized + bump + payer + finalized_at + paid_amount + fees_collected + approvals + approvals_version + asset_type + fee_delta
         seeds = [BATCH_SEED, vault_account.key().as_ref(), &batch_id.to_le_bytes()],
         bump
     )]
     pub payroll_batch: Account<'info, PayrollBatch>,
 
-    pub owner: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> CreatePayrollBatch<'info> {
     pub fn create(&mut self, batch_id: u64, total_amount: u64, bump: u8) -> Result<()> {
-        let service_fee = (total_amount * self.global_config.default_fee_bps as u64) / 10000;
+        let fee_bps = effective_fee_bps(&self.global_config, &self.vault_account)?;
+        let service_fee = bps_of(total_amount, fee_bps)?;
 
         self.payroll_batch.set_inner(PayrollBatch {
             vault: self.vault_account.key(),
@@ -45,6 +73,25 @@ impl<'info> CreatePayrollBatch<'info> {
             payout_count: 0,
             finalized: false,
             bump,
+            payer: self.payer.key(),
+            finalized_at: 0,
+            paid_amount: 0,
+            fees_collected: 0,
+            approvals: 0,
+            approvals_version: 0, // Set by the first approval
+            asset_type: None,     // Set by the first payout
+            fee_delta: 0,         // Set on finalize
+        });
+
+        self.vault_account.open_batch_count = increment(self.vault_account.open_batch_count, 1)?;
+        self.vault_account.batch_count = increment(self.vault_account.batch_count, 1)?;
+
+        emit!(BatchCreated {
+            vault: self.vault_account.key(),
+            batch_id,
+            total_amount,
+            estimated_fee: service_fee,
+            timestamp: self.payroll_batch.timestamp,
         });
 
         Ok(())
@@ -53,7 +100,15 @@ impl<'info> CreatePayrollBatch<'info> {
 
 #[derive(Accounts)]
 pub struct ProcessSolPayout<'info> {
-    #[account(mut, has_one = owner)]
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::EXECUTE_PAYOUTS)
+            @ ErrorVault::Unauthorized,
+        constraint = !vault_account.paused @ ErrorVault::VaultPaused
+    )]
     pub vault_account: Account<'info, VaultAccount>,
 
     #[account(
@@ -63,16 +118,30 @@ pub struct ProcessSolPayout<'info> {
     )]
     pub payroll_batch: Account<'info, PayrollBatch>,
 
+    /// CHECK: The vault's approval config PDA; uninitialized while the vault has no approvers
+    #[account(seeds = [APPROVAL_SEED, vault_account.key().as_ref()], bump)]
+    pub approval_config: UncheckedAccount<'info>,
+
     #[account(
         constraint = member.vault == vault_account.key(),
         constraint = member.is_active @ ErrorVault::MemberNotActive
     )]
     pub member: Account<'info, Member>,
 
+    #[account(
+        init,
+        payer = payer,
+        space = PayoutReceipt::SPACE,
+        seeds = [RECEIPT_SEED, payroll_batch.key().as_ref(), member.key().as_ref()],
+        bump
+    )]
+    pub receipt: Account<'info, PayoutReceipt>,
+
     #[account(
         mut,
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
@@ -90,41 +159,299 @@ pub struct ProcessSolPayout<'info> {
     )]
     pub member_wallet: AccountInfo<'info>,
 
-    pub owner: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> ProcessSolPayout<'info> {
-    pub fn process(&mut self, amount: u64) -> Result<()> {
-        // Calculate service fee
-        let service_fee = (amount * self.global_config.default_fee_bps as u64) / 10000;
-        let net_amount = amount - service_fee;
+    pub fn process(&mut self, amount: u64, receipt_bump: u8) -> Result<()> {
+        // Calculate service fee and who bears it
+        let FeeSplit {
+            service_fee,
+            net_amount,
+            total_cost,
+        } = split_payout(
+            &self.global_config,
+            &self.vault_account,
+            &AssetType::SOL,
+            amount,
+        )?;
 
-        // Ensure vault has sufficient balance
+        // Ensure vault has sufficient unreserved balance
         require!(
-            self.vault_account.total_balance >= amount,
+            self.vault_account.total_balance
+                >= checked_add(total_cost, self.vault_account.required_balance)?,
             ErrorVault::InsufficientVaultBalance
         );
+        self.payroll_batch.require_approved(&self.approval_config)?;
+        self.payroll_batch
+            .record_payout(&AssetType::SOL, amount, service_fee)?;
 
         // Transfer service fee to treasury
         if service_fee > 0 {
-            **self
-                .vault_account
-                .to_account_info()
-                .try_borrow_mut_lamports()? -= service_fee;
-            **self.treasury.try_borrow_mut_lamports()? += service_fee;
+            transfer_lamports(
+                &self.vault_account.to_account_info(),
+                &self.treasury,
+                service_fee,
+            )?;
         }
 
         // Transfer net amount to member
-        **self
-            .vault_account
-            .to_account_info()
-            .try_borrow_mut_lamports()? -= net_amount;
-        **self.member_wallet.try_borrow_mut_lamports()? += net_amount;
+        transfer_lamports(
+            &self.vault_account.to_account_info(),
+            &self.member_wallet,
+            net_amount,
+        )?;
 
         // Update vault and batch state
-        self.vault_account.total_balance -= amount;
-        self.payroll_batch.payout_count += 1;
+        self.vault_account.debit_sol(total_cost)?;
+        self.vault_account
+            .record_sol_volume(amount, Clock::get()?.unix_timestamp);
+        self.payroll_batch.payout_count = increment(self.payroll_batch.payout_count, 1)?;
+
+        self.receipt.set_inner(PayoutReceipt {
+            vault: self.vault_account.key(),
+            member: self.member.key(),
+            wallet: self.member.wallet,
+            cycle: PayoutCycle::Batch {
+                batch_id: self.payroll_batch.batch_id,
+            },
+            asset_type: AssetType::SOL,
+            amount,
+            service_fee,
+            net_amount,
+            paid_at: Clock::get()?.unix_timestamp,
+            claimed: true,
+            bump: receipt_bump,
+            payer: self.payer.key(),
+        });
+
+        emit_payout(PayoutExecuted {
+            vault: self.vault_account.key(),
+            recipient: self.member.wallet,
+            source: PayoutSource::Batch,
+            asset_type: self.receipt.asset_type.clone(),
+            amount,
+            service_fee,
+            net_amount,
+            batch_id: Some(self.payroll_batch.batch_id),
+            timestamp: self.receipt.paid_at,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ProcessSplPayout<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::EXECUTE_PAYOUTS)
+            @ ErrorVault::Unauthorized,
+        constraint = !vault_account.paused @ ErrorVault::VaultPaused
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        mut,
+        constraint = payroll_batch.vault == vault_account.key(),
+        constraint = !payroll_batch.finalized @ ErrorVault::BatchAlreadyFinalized
+    )]
+    pub payroll_batch: Account<'info, PayrollBatch>,
+
+    /// CHECK: The vault's approval config PDA; uninitialized while the vault has no approvers
+    #[account(seeds = [APPROVAL_SEED, vault_account.key().as_ref()], bump)]
+    pub approval_config: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = member.vault == vault_account.key(),
+        constraint = member.is_active @ ErrorVault::MemberNotActive
+    )]
+    pub member: Account<'info, Member>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PayoutReceipt::SPACE,
+        seeds = [RECEIPT_SEED, payroll_batch.key().as_ref(), member.key().as_ref()],
+        bump
+    )]
+    pub receipt: Box<Account<'info, PayoutReceipt>>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault_account,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Treasury account for fee collection
+    #[account(
+        constraint = treasury.key() == global_config.treasury @ ErrorVault::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Member wallet that owns the receiving token account
+    #[account(
+        constraint = member_wallet.key() == member.wallet @ ErrorVault::InvalidMemberWallet
+    )]
+    pub member_wallet: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = member_wallet,
+        associated_token::token_program = token_program,
+    )]
+    pub member_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ProcessSplPayout<'info> {
+    pub fn process(&mut self, amount: u64, receipt_bump: u8) -> Result<()> {
+        // Verify token is whitelisted
+        let asset = AssetType::SPLToken {
+            mint: self.mint.key(),
+        };
+        require!(
+            self.vault_account.whitelisted_assets.contains(&asset),
+            ErrorVault::AssetNotWhitelisted
+        );
+
+        // Calculate service fee in the same mint and who bears it
+        let FeeSplit {
+            service_fee,
+            net_amount,
+            total_cost,
+        } = split_payout(&self.global_config, &self.vault_account, &asset, amount)?;
+
+        // Ensure vault token account has sufficient unreserved balance
+        require!(
+            self.vault_token_account.amount
+                >= checked_add(
+                    total_cost,
+                    self.vault_account.spl_reserved(&self.mint.key())
+                )?,
+            ErrorVault::InsufficientVaultBalance
+        );
+        self.payroll_batch.require_approved(&self.approval_config)?;
+        self.payroll_batch
+            .record_payout(&asset, amount, service_fee)?;
+
+        let creator_key = self.vault_account.creator;
+        let vault_id = self.vault_account.vault_id.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            vault_id.as_ref(),
+            &[self.vault_account.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // Transfer service fee to treasury token account
+        if service_fee > 0 {
+            let fee_accounts = TransferChecked {
+                from: self.vault_token_account.to_account_info(),
+                mint: self.mint.to_account_info(),
+                to: self.treasury_token_account.to_account_info(),
+                authority: self.vault_account.to_account_info(),
+            };
+            let ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                fee_accounts,
+                signer_seeds,
+            );
+            transfer_checked(ctx, service_fee, self.mint.decimals)?;
+        }
+
+        // Transfer net amount to member token account
+        let payout_accounts = TransferChecked {
+            from: self.vault_token_account.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.member_token_account.to_account_info(),
+            authority: self.vault_account.to_account_info(),
+        };
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            payout_accounts,
+            signer_seeds,
+        );
+        transfer_checked(ctx, net_amount, self.mint.decimals)?;
+
+        // Update vault and batch state
+        self.vault_account.debit_spl(&self.mint.key(), total_cost)?;
+        self.payroll_batch.payout_count = increment(self.payroll_batch.payout_count, 1)?;
+
+        self.receipt.set_inner(PayoutReceipt {
+            vault: self.vault_account.key(),
+            member: self.member.key(),
+            wallet: self.member.wallet,
+            cycle: PayoutCycle::Batch {
+                batch_id: self.payroll_batch.batch_id,
+            },
+            asset_type: asset,
+            amount,
+            service_fee,
+            net_amount,
+            paid_at: Clock::get()?.unix_timestamp,
+            claimed: true,
+            bump: receipt_bump,
+            payer: self.payer.key(),
+        });
+
+        emit_payout(PayoutExecuted {
+            vault: self.vault_account.key(),
+            recipient: self.member.wallet,
+            source: PayoutSource::Batch,
+            asset_type: self.receipt.asset_type.clone(),
+            amount,
+            service_fee,
+            net_amount,
+            batch_id: Some(self.payroll_batch.batch_id),
+            timestamp: self.receipt.paid_at,
+        });
 
         Ok(())
     }
@@ -132,7 +459,11 @@ impl<'info> ProcessSolPayout<'info> {
 
 #[derive(Accounts)]
 pub struct FinalizePayrollBatch<'info> {
-    #[account(mut, has_one = owner)]
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::CREATE_BATCHES)
+            @ ErrorVault::Unauthorized
+    )]
     pub vault_account: Account<'info, VaultAccount>,
 
     #[account(
@@ -142,12 +473,90 @@ pub struct FinalizePayrollBatch<'info> {
     )]
     pub payroll_batch: Account<'info, PayrollBatch>,
 
-    pub owner: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
 }
 
 impl<'info> FinalizePayrollBatch<'info> {
     pub fn finalize(&mut self) -> Result<()> {
+        // The creation-time estimate is kept next to what was actually charged
+        self.payroll_batch.fee_delta = self.payroll_batch.fee_delta()?;
         self.payroll_batch.finalized = true;
+        self.payroll_batch.finalized_at = Clock::get()?.unix_timestamp;
+        self.vault_account.open_batch_count = decrement(self.vault_account.open_batch_count, 1)?;
+
+        emit!(BatchFinalized {
+            vault: self.vault_account.key(),
+            batch_id: self.payroll_batch.batch_id,
+            paid_amount: self.payroll_batch.paid_amount,
+            estimated_fee: self.payroll_batch.service_fee,
+            fees_collected: self.payroll_batch.fees_collected,
+            fee_delta: self.payroll_batch.fee_delta,
+            payout_count: self.payroll_batch.payout_count,
+            timestamp: self.payroll_batch.finalized_at,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClosePayrollBatch<'info> {
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::CREATE_BATCHES)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        close = payer,
+        constraint = payroll_batch.vault == vault_account.key(),
+        constraint = payroll_batch.finalized @ ErrorVault::BatchNotFinalized
+    )]
+    pub payroll_batch: Account<'info, PayrollBatch>,
+
+    /// CHECK: Original payer of the batch rent, refunded on close
+    #[account(
+        mut,
+        constraint = payer.key() == payroll_batch.payer @ ErrorVault::InvalidRentReceiver
+    )]
+    pub payer: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+}
+
+impl<'info> ClosePayrollBatch<'info> {
+    pub fn close(&mut self) -> Result<()> {
+        // Keep finalized batches around for the vault's retention period
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= checked_add_secs(
+                self.payroll_batch.finalized_at,
+                self.vault_account.batch_retention_secs
+            )?,
+            ErrorVault::RetentionPeriodActive
+        );
+        self.vault_account.batch_count = decrement(self.vault_account.batch_count, 1)?;
+
+        emit!(BatchClosed {
+            vault: self.vault_account.key(),
+            batch_id: self.payroll_batch.batch_id,
+            timestamp: now,
+        });
         Ok(())
     }
 }
@@ -0,0 +1,408 @@
+use crate::constants::DELEGATE_SEED;
+use crate::constants::GLOBAL_CONFIG_SEED;
+use crate::constants::VAULT_SEED;
+use crate::constants::VESTING_SEED;
+use crate::errors::ErrorVault;
+use crate::events::{PayoutExecuted, PayoutSource, VestingClosed, VestingCreated, VestingRevoked};
+use crate::helper::{emit_payout, is_authorized, release_funds, reserve_funds, split_payout};
+use crate::math::{
+    checked_add, checked_sub, decrement, increment, pro_rata, reserved_fee, split_fee, FeeSplit,
+};
+use crate::state::{AssetType, GlobalConfig, Member, VaultAccount, VaultDelegate, VestingSchedule};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+pub struct CreateVesting<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::EXECUTE_PAYOUTS)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        constraint = member.vault == vault_account.key(),
+        constraint = member.is_active @ ErrorVault::MemberNotActive
+    )]
+    pub member: Account<'info, Member>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = VestingSchedule::SPACE,
+        seeds = [VESTING_SEED, member.key().as_ref()],
+        bump
+    )]
+    pub vesting: Account<'info, VestingSchedule>,
+
+    #[account(
+        mint::token_program = token_program,
+        constraint = vault_account.whitelisted_assets.contains(&AssetType::SPLToken { mint: mint.key() })
+            @ ErrorVault::AssetNotWhitelisted
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = mint,
+        associated_token::authority = vault_account,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateVesting<'info> {
+    pub fn create(
+        &mut self,
+        start_ts: i64,
+        cliff_ts: i64,
+        duration: i64,
+        revocable: bool,
+        bump: u8,
+    ) -> Result<()> {
+        // Vesting covers the member's SPL allocation
+        let total_amount = self
+            .member
+            .spl_token_allocation
+            .ok_or(ErrorVault::InvalidVestingConfig)?;
+        require!(
+            total_amount > 0 && duration > 0 && cliff_ts >= start_ts,
+            ErrorVault::InvalidVestingConfig
+        );
+
+        let asset_type = AssetType::SPLToken {
+            mint: self.mint.key(),
+        };
+        // The fee on the whole grant is fixed now and reserved with it when grossed up
+        let FeeSplit {
+            service_fee,
+            total_cost,
+            ..
+        } = split_payout(
+            &self.global_config,
+            &self.vault_account,
+            &asset_type,
+            total_amount,
+        )?;
+        reserve_funds(
+            &mut self.vault_account,
+            &asset_type,
+            total_cost,
+            Some(&self.vault_token_account),
+        )?;
+
+        self.vesting.set_inner(VestingSchedule {
+            vault: self.vault_account.key(),
+            member: self.member.key(),
+            wallet: self.member.wallet,
+            payer: self.payer.key(),
+            mint: self.mint.key(),
+            total_amount,
+            start_ts,
+            cliff_ts,
+            duration,
+            claimed: 0,
+            fee_mode: self.vault_account.fee_mode,
+            service_fee,
+            fees_paid: 0,
+            revocable,
+            revoked: false,
+            bump,
+        });
+
+        self.vault_account.vesting_count = increment(self.vault_account.vesting_count, 1)?;
+
+        emit!(VestingCreated {
+            vault: self.vault_account.key(),
+            vesting: self.vesting.key(),
+            wallet: self.member.wallet,
+            mint: self.mint.key(),
+            total_amount,
+            start_ts,
+            cliff_ts,
+            duration,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RevokeVesting<'info> {
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::EXECUTE_PAYOUTS)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        constraint = vesting.vault == vault_account.key(),
+        constraint = vesting.revocable @ ErrorVault::VestingNotRevocable,
+        constraint = !vesting.revoked @ ErrorVault::VestingRevoked
+    )]
+    pub vesting: Account<'info, VestingSchedule>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+}
+
+impl<'info> RevokeVesting<'info> {
+    /// Freezes vesting at the amount vested so far. The unvested remainder, and
+    /// any grossed-up fee reserved on it, is released back to the vault ATA's
+    /// spendable balance.
+    pub fn revoke(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let vested = self.vesting.vested_at(now);
+        let unvested = checked_sub(self.vesting.total_amount, vested)?;
+        let service_fee = pro_rata(self.vesting.service_fee, vested, self.vesting.total_amount)?
+            .max(self.vesting.fees_paid);
+        let released_fee = reserved_fee(
+            checked_sub(self.vesting.service_fee, service_fee)?,
+            self.vesting.fee_mode,
+        );
+
+        let asset_type = AssetType::SPLToken {
+            mint: self.vesting.mint,
+        };
+        let released = checked_add(unvested, released_fee)?;
+        release_funds(&mut self.vault_account, &asset_type, released)?;
+
+        self.vesting.total_amount = vested;
+        self.vesting.service_fee = service_fee;
+        self.vesting.revoked = true;
+
+        emit!(VestingRevoked {
+            vault: self.vault_account.key(),
+            vesting: self.vesting.key(),
+            wallet: self.vesting.wallet,
+            vested,
+            released,
+            timestamp: now,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CloseVesting<'info> {
+    #[account(
+        mut,
+        constraint = is_authorized(&vault_account, authority.key, delegate.as_deref(), VaultDelegate::EXECUTE_PAYOUTS)
+            @ ErrorVault::Unauthorized
+    )]
+    pub vault_account: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        close = payer,
+        constraint = vesting.vault == vault_account.key(),
+        constraint = vesting.is_settled() @ ErrorVault::VestingNotSettled,
+        seeds = [VESTING_SEED, vesting.member.as_ref()],
+        bump = vesting.bump
+    )]
+    pub vesting: Account<'info, VestingSchedule>,
+
+    /// CHECK: Original payer of the schedule rent, refunded on close
+    #[account(
+        mut,
+        constraint = payer.key() == vesting.payer @ ErrorVault::InvalidRentReceiver
+    )]
+    pub payer: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        seeds = [DELEGATE_SEED, vault_account.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, VaultDelegate>>,
+}
+
+impl<'info> CloseVesting<'info> {
+    /// Closes a schedule once the member has claimed everything it will ever vest,
+    /// after full vesting or a revoke, freeing the member's vesting PDA
+    pub fn close(&mut self) -> Result<()> {
+        self.vault_account.vesting_count = decrement(self.vault_account.vesting_count, 1)?;
+
+        emit!(VestingClosed {
+            vault: self.vault_account.key(),
+            vesting: self.vesting.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(mut, constraint = !vault_account.paused @ ErrorVault::VaultPaused)]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+
+    #[account(
+        mut,
+        constraint = vesting.vault == vault_account.key(),
+        constraint = vesting.wallet == wallet.key() @ ErrorVault::InvalidClaimant,
+        constraint = vesting.mint == mint.key() @ ErrorVault::AssetMismatch
+    )]
+    pub vesting: Box<Account<'info, VestingSchedule>>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = !global_config.paused @ ErrorVault::ProtocolPaused
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault_account,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Treasury account for fee collection
+    #[account(
+        constraint = treasury.key() == global_config.treasury @ ErrorVault::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = wallet,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = wallet,
+        associated_token::mint = mint,
+        associated_token::authority = wallet,
+        associated_token::token_program = token_program,
+    )]
+    pub wallet_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimVested<'info> {
+    pub fn claim(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let vested = self.vesting.vested_at(now);
+        let amount = checked_sub(vested, self.vesting.claimed)?;
+        require!(amount > 0, ErrorVault::NothingToWithdraw);
+
+        let asset_type = AssetType::SPLToken {
+            mint: self.mint.key(),
+        };
+        let service_fee = self.vesting.fee_due(vested)?;
+        let FeeSplit {
+            service_fee,
+            net_amount,
+            total_cost,
+        } = split_fee(amount, service_fee, self.vesting.fee_mode)?;
+
+        let creator_key = self.vault_account.creator;
+        let vault_id = self.vault_account.vault_id.to_le_bytes();
+        let seeds = &[
+            VAULT_SEED,
+            creator_key.as_ref(),
+            vault_id.as_ref(),
+            &[self.vault_account.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // Transfer service fee to treasury token account
+        if service_fee > 0 {
+            let fee_accounts = TransferChecked {
+                from: self.vault_token_account.to_account_info(),
+                mint: self.mint.to_account_info(),
+                to: self.treasury_token_account.to_account_info(),
+                authority: self.vault_account.to_account_info(),
+            };
+            let ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                fee_accounts,
+                signer_seeds,
+            );
+            transfer_checked(ctx, service_fee, self.mint.decimals)?;
+        }
+
+        // Transfer net vested amount to the member token account
+        let payout_accounts = TransferChecked {
+            from: self.vault_token_account.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.wallet_token_account.to_account_info(),
+            authority: self.vault_account.to_account_info(),
+        };
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            payout_accounts,
+            signer_seeds,
+        );
+        transfer_checked(ctx, net_amount, self.mint.decimals)?;
+        self.vault_account.debit_spl(&self.mint.key(), total_cost)?;
+
+        release_funds(&mut self.vault_account, &asset_type, total_cost)?;
+        self.vesting.claimed = checked_add(self.vesting.claimed, amount)?;
+        self.vesting.fees_paid = checked_add(self.vesting.fees_paid, service_fee)?;
+
+        emit_payout(PayoutExecuted {
+            vault: self.vault_account.key(),
+            recipient: self.wallet.key(),
+            source: PayoutSource::Vesting,
+            asset_type,
+            amount,
+            service_fee,
+            net_amount,
+            batch_id: None,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
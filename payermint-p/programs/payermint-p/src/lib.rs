@@ -1,3 +1,8 @@
+#![allow(unexpected_cfgs)]
+// Anchor 0.31's `#[program]` expands IDL handlers at the crate root that call the deprecated
+// `AccountInfo::realloc`; no narrower scope reaches them. Code in this crate uses `resize`.
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
 
 pub mod state;
@@ -9,12 +14,17 @@ pub use instructions::*;
 pub mod errors;
 pub use errors::*;
 
+pub mod events;
+pub use events::*;
+
 pub mod constants;
 pub use constants::*;
 
 pub mod helper;
 pub use helper::*;
 
+pub mod math;
+
 declare_id!("Bbto3swCVRD1XuqU6ZbfCXfhQqzE5iKnEuf6AtgRxQ7Y");
 
 #[program]
@@ -45,7 +55,47 @@ pub mod payermint_p {
         ctx.accounts.update_default_fee(new_fee_bps)
     }
 
+    pub fn apply_config_change(ctx: Context<ApplyConfigChange>) -> Result<()> {
+        ctx.accounts.apply()
+    }
+
+    pub fn update_fee_limit(ctx: Context<UpdateGlobalConfig>, fee_limit: FeeLimit) -> Result<()> {
+        ctx.accounts.update_fee_limit(fee_limit)
+    }
+
+    pub fn cancel_config_change(ctx: Context<UpdateGlobalConfig>) -> Result<()> {
+        ctx.accounts.cancel_config_change()
+    }
+
+    pub fn propose_config_owner(ctx: Context<UpdateGlobalConfig>, new_owner: Pubkey) -> Result<()> {
+        ctx.accounts.propose_config_owner(new_owner)
+    }
+
+    pub fn cancel_config_owner_proposal(ctx: Context<UpdateGlobalConfig>) -> Result<()> {
+        ctx.accounts.cancel_config_owner_proposal()
+    }
+
+    pub fn set_protocol_paused(ctx: Context<UpdateGlobalConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.set_protocol_paused(paused)
+    }
+
+    pub fn set_fee_tiers(ctx: Context<UpdateGlobalConfig>, fee_tiers: Vec<FeeTier>) -> Result<()> {
+        ctx.accounts.set_fee_tiers(fee_tiers)
+    }
+
+    pub fn set_vault_fee_override(
+        ctx: Context<SetVaultFeeOverride>,
+        fee_override_bps: Option<u16>,
+    ) -> Result<()> {
+        ctx.accounts.set(fee_override_bps)
+    }
+
+    pub fn accept_config_owner(ctx: Context<AcceptConfigOwner>) -> Result<()> {
+        ctx.accounts.accept()
+    }
+
     // VAULT MANAGEMENT
+    #[allow(clippy::too_many_arguments)]
     pub fn create_vault(
         ctx: Context<CreateVault>,
         name: String,
@@ -54,7 +104,7 @@ pub mod payermint_p {
         payout_schedule: Option<PayoutSchedule>,
         allocation_type: AllocationType,
         metadata_uri: Option<String>,
-        code_claim: Option<String>,
+        code_claim: Option<ClaimCode>,
     ) -> Result<()> {
         ctx.accounts.create(
             name,
@@ -68,13 +118,39 @@ pub mod payermint_p {
         )
     }
 
+    pub fn close_vault<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseVault<'info>>,
+        force: bool,
+    ) -> Result<()> {
+        ctx.accounts.close(force, ctx.remaining_accounts)
+    }
+
+    pub fn propose_vault_owner(ctx: Context<UpdateVault>, new_owner: Pubkey) -> Result<()> {
+        ctx.accounts.propose_vault_owner(new_owner)
+    }
+
+    pub fn cancel_vault_owner_proposal(ctx: Context<UpdateVault>) -> Result<()> {
+        ctx.accounts.cancel_vault_owner_proposal()
+    }
+
+    pub fn accept_vault_owner(ctx: Context<AcceptVaultOwner>) -> Result<()> {
+        ctx.accounts.accept()
+    }
+
     pub fn update_payout_schedule(
-        ctx: Context<UpdateVault>,
+        ctx: Context<UpdateVaultSchedule>,
         schedule: Option<PayoutSchedule>,
     ) -> Result<()> {
         ctx.accounts.update_payout_schedule(schedule)
     }
 
+    pub fn set_claim_code(
+        ctx: Context<UpdateVaultPayouts>,
+        code_claim: Option<ClaimCode>,
+    ) -> Result<()> {
+        ctx.accounts.set_claim_code(code_claim)
+    }
+
     pub fn add_whitelisted_asset(ctx: Context<UpdateVault>, asset: AssetType) -> Result<()> {
         ctx.accounts.add_whitelisted_asset(asset)
     }
@@ -83,6 +159,28 @@ pub mod payermint_p {
         ctx.accounts.remove_whitelisted_asset(asset)
     }
 
+    pub fn set_fee_mode(ctx: Context<UpdateVault>, fee_mode: FeeMode) -> Result<()> {
+        ctx.accounts.set_fee_mode(fee_mode)
+    }
+
+    pub fn set_vault_paused(ctx: Context<UpdateVault>, paused: bool) -> Result<()> {
+        ctx.accounts.set_vault_paused(paused)
+    }
+
+    pub fn update_keeper_reward(
+        ctx: Context<UpdateVaultPayouts>,
+        keeper_reward: u64,
+    ) -> Result<()> {
+        ctx.accounts.update_keeper_reward(keeper_reward)
+    }
+
+    pub fn update_batch_retention(
+        ctx: Context<UpdateVault>,
+        batch_retention_secs: i64,
+    ) -> Result<()> {
+        ctx.accounts.update_batch_retention(batch_retention_secs)
+    }
+
     // MEMBER MANAGEMENT
     pub fn add_member(
         ctx: Context<AddMember>,
@@ -123,8 +221,8 @@ pub mod payermint_p {
     }
 
     pub fn remove_member(ctx: Context<RemoveMember>) -> Result<()> {
-        // Account automatically closed due to close constraint
-        Ok(())
+        // The `close` constraint reclaims the account; `remove` updates the member counts
+        ctx.accounts.remove()
     }
 
     // DEPOSITS
@@ -136,6 +234,21 @@ pub mod payermint_p {
         ctx.accounts.deposit(amount)
     }
 
+    // WITHDRAWALS
+    pub fn withdraw_sol(ctx: Context<WithdrawSol>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw(amount)
+    }
+
+    pub fn withdraw_spl_token(ctx: Context<WithdrawSplToken>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw(amount)
+    }
+
+    pub fn sync_balances<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SyncBalances<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.sync(ctx.remaining_accounts)
+    }
+
     // PAYROLL PROCESSING
     pub fn create_payroll_batch(
         ctx: Context<CreatePayrollBatch>,
@@ -147,31 +260,198 @@ pub mod payermint_p {
     }
 
     pub fn process_sol_payout(ctx: Context<ProcessSolPayout>, amount: u64) -> Result<()> {
-        ctx.accounts.process(amount)
+        ctx.accounts.process(amount, ctx.bumps.receipt)
+    }
+
+    pub fn process_spl_payout(ctx: Context<ProcessSplPayout>, amount: u64) -> Result<()> {
+        ctx.accounts.process(amount, ctx.bumps.receipt)
+    }
+
+    // PULL-BASED CLAIMS
+    pub fn allocate_claim(
+        ctx: Context<AllocateClaim>,
+        amount: u64,
+        asset_type: AssetType,
+    ) -> Result<()> {
+        ctx.accounts.allocate(amount, asset_type, ctx.bumps.receipt)
+    }
+
+    pub fn claim_sol_payment(ctx: Context<ClaimSolPayment>) -> Result<()> {
+        ctx.accounts.claim()
+    }
+
+    pub fn claim_spl_payment(ctx: Context<ClaimSplPayment>) -> Result<()> {
+        ctx.accounts.claim()
+    }
+
+    pub fn cancel_claim(ctx: Context<CancelClaim>) -> Result<()> {
+        ctx.accounts.cancel()
+    }
+
+    pub fn claim_with_code(ctx: Context<ClaimWithCode>, code: String) -> Result<()> {
+        ctx.accounts.claim(code, ctx.bumps.redemption)
+    }
+
+    pub fn claim_spl_with_code(ctx: Context<ClaimSplWithCode>, code: String) -> Result<()> {
+        ctx.accounts.claim(code, ctx.bumps.redemption)
+    }
+
+    pub fn close_receipt(ctx: Context<CloseReceipt>) -> Result<()> {
+        ctx.accounts.close()
+    }
+
+    pub fn close_redemption(ctx: Context<CloseRedemption>) -> Result<()> {
+        ctx.accounts.close()
+    }
+
+    // STREAMING PAYROLL
+    pub fn create_stream(
+        ctx: Context<CreateStream>,
+        asset_type: AssetType,
+        rate_per_second: u64,
+        start_ts: i64,
+        end_ts: Option<i64>,
+        deposit: u64,
+    ) -> Result<()> {
+        ctx.accounts.create(
+            asset_type,
+            rate_per_second,
+            start_ts,
+            end_ts,
+            deposit,
+            ctx.bumps.stream,
+        )
+    }
+
+    pub fn top_up_stream(ctx: Context<UpdateStream>, amount: u64) -> Result<()> {
+        ctx.accounts.top_up(amount)
+    }
+
+    pub fn pause_stream(ctx: Context<UpdateStream>) -> Result<()> {
+        ctx.accounts.pause()
+    }
+
+    pub fn resume_stream(ctx: Context<UpdateStream>) -> Result<()> {
+        ctx.accounts.resume()
+    }
+
+    pub fn cancel_stream(ctx: Context<UpdateStream>) -> Result<()> {
+        ctx.accounts.cancel()
+    }
+
+    pub fn close_stream(ctx: Context<CloseStream>) -> Result<()> {
+        ctx.accounts.close()
+    }
+
+    pub fn withdraw_sol_stream(ctx: Context<WithdrawSolStream>) -> Result<()> {
+        ctx.accounts.withdraw()
+    }
+
+    pub fn withdraw_spl_stream(ctx: Context<WithdrawSplStream>) -> Result<()> {
+        ctx.accounts.withdraw()
+    }
+
+    // TOKEN VESTING
+    pub fn create_vesting(
+        ctx: Context<CreateVesting>,
+        start_ts: i64,
+        cliff_ts: i64,
+        duration: i64,
+        revocable: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .create(start_ts, cliff_ts, duration, revocable, ctx.bumps.vesting)
+    }
+
+    pub fn close_vesting(ctx: Context<CloseVesting>) -> Result<()> {
+        ctx.accounts.close()
+    }
+
+    pub fn revoke_vesting(ctx: Context<RevokeVesting>) -> Result<()> {
+        ctx.accounts.revoke()
+    }
+
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        ctx.accounts.claim()
+    }
+
+    // DELEGATES
+    pub fn set_delegate(ctx: Context<SetDelegate>, permissions: u8) -> Result<()> {
+        ctx.accounts.set(permissions, ctx.bumps.delegate)
+    }
+
+    pub fn remove_delegate(ctx: Context<RemoveDelegate>) -> Result<()> {
+        ctx.accounts.remove()
+    }
+
+    // MULTISIG APPROVALS
+    pub fn initialize_approvers(
+        ctx: Context<InitializeApprovers>,
+        approvers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        ctx.accounts
+            .initialize(approvers, threshold, ctx.bumps.approval_config)
+    }
+
+    pub fn propose_approver_change(
+        ctx: Context<ProposeApproverChange>,
+        approvers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        ctx.accounts.propose(approvers, threshold)
+    }
+
+    pub fn approve_approver_change(ctx: Context<ApproveApproverChange>) -> Result<()> {
+        ctx.accounts.approve()
+    }
+
+    pub fn approve_batch(ctx: Context<ApproveBatch>) -> Result<()> {
+        ctx.accounts.approve()
+    }
+
+    pub fn propose_owner_action(
+        ctx: Context<ProposeOwnerAction>,
+        action: OwnerAction,
+    ) -> Result<()> {
+        ctx.accounts.propose(action)
+    }
+
+    pub fn approve_owner_action(ctx: Context<ApproveOwnerAction>) -> Result<()> {
+        ctx.accounts.approve()
     }
 
     pub fn finalize_payroll_batch(ctx: Context<FinalizePayrollBatch>) -> Result<()> {
         ctx.accounts.finalize()
     }
 
+    pub fn close_payroll_batch(ctx: Context<ClosePayrollBatch>) -> Result<()> {
+        ctx.accounts.close()
+    }
+
     // AUTOMATED PAYOUTS
-    pub fn process_scheduled_payout(ctx: Context<ProcessScheduledPayout>) -> Result<()> {
-        ctx.accounts.process_scheduled_payout()
+    pub fn process_scheduled_payout(
+        ctx: Context<ProcessScheduledPayout>,
+        period: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .process_scheduled_payout(period, ctx.bumps.receipt)
     }
 
     // BULK OPERATIONS
-    pub fn bulk_add_members(
-        ctx: Context<BulkAddMembers>,
+    pub fn bulk_add_members<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BulkAddMembers<'info>>,
         members_data: Vec<MemberData>,
     ) -> Result<()> {
-        ctx.accounts.bulk_add(members_data)
+        ctx.accounts.bulk_add(members_data, ctx.remaining_accounts)
     }
 
-    pub fn bulk_process_payouts(
-        ctx: Context<BulkProcessPayouts>,
+    pub fn bulk_process_payouts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BulkProcessPayouts<'info>>,
         payout_data: Vec<PayoutData>,
     ) -> Result<()> {
-        ctx.accounts.bulk_process(payout_data)
+        ctx.accounts
+            .bulk_process(payout_data, ctx.remaining_accounts)
     }
 }
 
@@ -0,0 +1,210 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ErrorVault;
+use crate::state::{FeeLimit, FeeMode};
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+pub fn checked_add(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b)
+        .ok_or_else(|| error!(ErrorVault::MathOverflow))
+}
+
+pub fn checked_sub(a: u64, b: u64) -> Result<u64> {
+    a.checked_sub(b)
+        .ok_or_else(|| error!(ErrorVault::MathOverflow))
+}
+
+/// Raises a `u32` counter by `by`
+pub fn increment(count: u32, by: u32) -> Result<u32> {
+    count
+        .checked_add(by)
+        .ok_or_else(|| error!(ErrorVault::MathOverflow))
+}
+
+/// Lowers a `u32` counter by `by`
+pub fn decrement(count: u32, by: u32) -> Result<u32> {
+    count
+        .checked_sub(by)
+        .ok_or_else(|| error!(ErrorVault::MathOverflow))
+}
+
+/// Timestamp `secs` after `ts`
+pub fn checked_add_secs(ts: i64, secs: i64) -> Result<i64> {
+    ts.checked_add(secs)
+        .ok_or_else(|| error!(ErrorVault::MathOverflow))
+}
+
+/// `amount * bps / 10000` rounded down. The product is taken in u128, so it can only
+/// overflow for rates above 100%.
+pub fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    let value = amount as u128 * bps as u128 / BPS_DENOMINATOR as u128;
+    u64::try_from(value).map_err(|_| error!(ErrorVault::MathOverflow))
+}
+
+/// Service fee on `amount` at `fee_bps`, clamped to the asset's fee limit if it has one
+pub fn fee_for(amount: u64, fee_bps: u16, limit: Option<&FeeLimit>) -> Result<u64> {
+    let mut service_fee = bps_of(amount, fee_bps)?;
+    if let Some(limit) = limit {
+        service_fee = service_fee.max(limit.min_fee);
+        if limit.max_fee > 0 {
+            service_fee = service_fee.min(limit.max_fee);
+        }
+    }
+    Ok(service_fee)
+}
+
+/// `value * part / whole` rounded down, e.g. the share of a fixed fee owed once
+/// `part` of `whole` has been paid out. Zero when `whole` is zero.
+pub fn pro_rata(value: u64, part: u64, whole: u64) -> Result<u64> {
+    if whole == 0 {
+        return Ok(0);
+    }
+    let share = value as u128 * part as u128 / whole as u128;
+    u64::try_from(share).map_err(|_| error!(ErrorVault::MathOverflow))
+}
+
+pub struct FeeSplit {
+    pub service_fee: u64,
+    pub net_amount: u64, // Received by the member
+    pub total_cost: u64, // Leaves the vault, fee included
+}
+
+/// Splits a payout of `amount` carrying `service_fee` according to `fee_mode`
+pub fn split_fee(amount: u64, service_fee: u64, fee_mode: FeeMode) -> Result<FeeSplit> {
+    Ok(match fee_mode {
+        FeeMode::Deduct => {
+            // A minimum fee can never take more than the payout itself
+            let service_fee = service_fee.min(amount);
+            FeeSplit {
+                service_fee,
+                net_amount: checked_sub(amount, service_fee)?,
+                total_cost: amount,
+            }
+        }
+        FeeMode::GrossUp => FeeSplit {
+            service_fee,
+            net_amount: amount,
+            total_cost: checked_add(amount, service_fee)?,
+        },
+    })
+}
+
+/// Part of `service_fee` that has to be reserved on top of the payout amount
+pub fn reserved_fee(service_fee: u64, fee_mode: FeeMode) -> u64 {
+    match fee_mode {
+        FeeMode::Deduct => 0,
+        FeeMode::GrossUp => service_fee,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::AssetType;
+    use proptest::prelude::*;
+
+    fn sol_limit(min_fee: u64, max_fee: u64) -> FeeLimit {
+        FeeLimit {
+            asset_type: AssetType::SOL,
+            min_fee,
+            max_fee,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn bps_of_never_exceeds_amount(amount in any::<u64>(), bps in 0..=10_000u16) {
+            let value = bps_of(amount, bps).unwrap();
+            prop_assert!(value <= amount);
+            prop_assert_eq!(value as u128, amount as u128 * bps as u128 / 10_000);
+        }
+
+        #[test]
+        fn bps_of_is_monotonic(a in any::<u64>(), b in any::<u64>(), bps in 0..=10_000u16) {
+            let (low, high) = (a.min(b), a.max(b));
+            prop_assert!(bps_of(low, bps).unwrap() <= bps_of(high, bps).unwrap());
+        }
+
+        #[test]
+        fn bps_of_errors_instead_of_wrapping(amount in any::<u64>(), bps in any::<u16>()) {
+            let exact = amount as u128 * bps as u128 / 10_000;
+            match bps_of(amount, bps) {
+                Ok(value) => prop_assert_eq!(value as u128, exact),
+                Err(_) => prop_assert!(exact > u64::MAX as u128),
+            }
+        }
+
+        #[test]
+        fn allocations_never_exceed_balance(
+            balance in any::<u64>(),
+            allocations in prop::collection::vec(0..=2_500u16, 0..=4),
+        ) {
+            // At most 4 x 2500 bps, so the allocations sum to at most 100%
+            let mut total = 0u64;
+            for bps in allocations {
+                total = checked_add(total, bps_of(balance, bps).unwrap()).unwrap();
+            }
+            prop_assert!(total <= balance);
+        }
+
+        #[test]
+        fn fee_respects_limits(
+            amount in any::<u64>(),
+            fee_bps in 0..=10_000u16,
+            min_fee in any::<u64>(),
+            max_fee in any::<u64>(),
+        ) {
+            let max_fee = max_fee.max(min_fee);
+            let service_fee = fee_for(amount, fee_bps, Some(&sol_limit(min_fee, max_fee))).unwrap();
+            prop_assert!(service_fee >= min_fee);
+            prop_assert!(service_fee <= max_fee);
+
+            let uncapped = fee_for(amount, fee_bps, Some(&sol_limit(min_fee, 0))).unwrap();
+            prop_assert_eq!(uncapped, bps_of(amount, fee_bps).unwrap().max(min_fee));
+        }
+
+        #[test]
+        fn deduct_split_balances(amount in any::<u64>(), service_fee in any::<u64>()) {
+            let split = split_fee(amount, service_fee, FeeMode::Deduct).unwrap();
+            prop_assert!(split.service_fee <= amount);
+            prop_assert_eq!(split.total_cost, amount);
+            prop_assert_eq!(split.net_amount + split.service_fee, split.total_cost);
+        }
+
+        #[test]
+        fn gross_up_split_balances(amount in any::<u64>(), service_fee in any::<u64>()) {
+            match split_fee(amount, service_fee, FeeMode::GrossUp) {
+                Ok(split) => {
+                    prop_assert_eq!(split.net_amount, amount);
+                    prop_assert_eq!(split.service_fee, service_fee);
+                    prop_assert_eq!(split.net_amount + split.service_fee, split.total_cost);
+                }
+                Err(_) => prop_assert!(amount.checked_add(service_fee).is_none()),
+            }
+        }
+
+        #[test]
+        fn pro_rata_share_is_bounded(value in any::<u64>(), part in any::<u64>(), whole in 1..=u64::MAX) {
+            let part = part.min(whole);
+            prop_assert!(pro_rata(value, part, whole).unwrap() <= value);
+            prop_assert_eq!(pro_rata(value, whole, whole).unwrap(), value);
+            prop_assert_eq!(pro_rata(value, 0, whole).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn checked_ops_reject_wrapping() {
+        assert!(checked_add(u64::MAX, 1).is_err());
+        assert!(checked_sub(0, 1).is_err());
+        assert_eq!(checked_add(1, 2).unwrap(), 3);
+        assert_eq!(checked_sub(3, 2).unwrap(), 1);
+        assert_eq!(pro_rata(100, 1, 0).unwrap(), 0);
+        assert!(increment(u32::MAX, 1).is_err());
+        assert!(decrement(0, 1).is_err());
+        assert!(checked_add_secs(i64::MAX, 1).is_err());
+        assert_eq!(increment(1, 2).unwrap(), 3);
+        assert_eq!(decrement(3, 2).unwrap(), 1);
+    }
+}
@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ErrorVault;
+use crate::state::ClaimCode;
+
+/// Bitmaps are `u16`, so a vault can have at most this many approvers
+pub const MAX_APPROVERS: usize = 16;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ApproverChange {
+    #[max_len(MAX_APPROVERS)]
+    pub approvers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+/// Owner operation that moves funds out of a vault. Once a vault has approvers,
+/// each one runs only after they approve exactly these parameters.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum OwnerAction {
+    WithdrawSol { amount: u64 },
+    WithdrawSpl { mint: Pubkey, amount: u64 },
+    CloseVault { force: bool },
+    UpdateKeeperReward { keeper_reward: u64 },
+    SetClaimCode { code_claim: Option<ClaimCode> },
+}
+
+/// M-of-N approvers for a vault. Payouts made against a batch
+/// (`process_sol_payout`, `process_spl_payout`, `bulk_process_payouts` and
+/// `allocate_claim`) need the batch approved. Withdrawals, `close_vault`, keeper
+/// reward and claim code changes need an approved `OwnerAction`, which is used up
+/// when it runs. Streams, vesting and scheduled payouts stay under the owner and
+/// its delegates.
+/// Seeds: `[APPROVAL_SEED, vault]`
+#[account]
+#[derive(InitSpace)]
+pub struct ApprovalConfig {
+    pub vault: Pubkey,
+    #[max_len(MAX_APPROVERS)]
+    pub approvers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub version: u32, // Bumped on every approver change so older batch approvals lapse
+    pub pending_change: Option<ApproverChange>,
+    pub change_approvals: u16, // Bitmap over `approvers` for `pending_change`
+    pub bump: u8,
+    pub pending_action: Option<OwnerAction>, // Owner operation awaiting approval
+    pub action_approvals: u16,               // Bitmap over `approvers` for `pending_action`
+}
+
+impl ApprovalConfig {
+    pub fn approver_index(&self, approver: &Pubkey) -> Option<usize> {
+        self.approvers.iter().position(|key| key == approver)
+    }
+
+    /// Whether `bitmap` holds enough approvals from the current set
+    pub fn threshold_met(&self, bitmap: u16) -> bool {
+        bitmap.count_ones() >= self.threshold as u32
+    }
+
+    /// Checks that the approvers signed off on `action` and uses the approval up,
+    /// reading the vault's `ApprovalConfig` PDA. A vault whose PDA is uninitialized
+    /// has no approvers, so the owner acts alone.
+    pub fn consume_action(approval_config: &AccountInfo, action: &OwnerAction) -> Result<()> {
+        if approval_config.data_is_empty() {
+            return Ok(());
+        }
+        require_keys_eq!(
+            *approval_config.owner,
+            crate::ID,
+            ErrorVault::InvalidApproverConfig
+        );
+        let mut data = approval_config.try_borrow_mut_data()?;
+        let mut config = ApprovalConfig::try_deserialize(&mut &data[..])?;
+        require!(
+            config.pending_action.as_ref() == Some(action)
+                && config.threshold_met(config.action_approvals),
+            ErrorVault::OwnerActionNotApproved
+        );
+        config.pending_action = None;
+        config.action_approvals = 0;
+        config.try_serialize(&mut &mut data[..])
+    }
+}
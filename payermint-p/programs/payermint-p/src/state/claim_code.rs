@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::state::AssetType;
+
+/// Claim code stored on the vault. Only `sha256(code || vault)` is kept on-chain;
+/// the plaintext code is revealed by the claimant in `claim_with_code`.
+///
+/// A code is a bearer secret: it travels in plaintext in the claim transaction, so
+/// anyone who sees that transaction before it lands can redeem the code from their own
+/// wallet first. Hand codes out over a private channel, keep `max_uses` and `expires_at`
+/// tight, and use `allocate_claim` when a payout must go to a specific wallet. Codes
+/// shorter than `MIN_CLAIM_CODE_LEN` bytes are rejected on redemption.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct ClaimCode {
+    pub code_hash: [u8; 32],
+    pub amount: u64,
+    pub asset_type: AssetType,
+    pub expires_at: Option<i64>,
+    pub max_uses: u32, // 1 for a single-use code
+    pub uses: u32,
+}
+
+impl ClaimCode {
+    /// Whether the code has uses left and has not expired at `now`
+    pub fn is_redeemable(&self, now: i64) -> bool {
+        self.uses < self.max_uses && self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+}
+
+/// One per claimant and code, so a multi-use code cannot be drained by a single wallet.
+/// Seeds: `[REDEMPTION_SEED, vault, code_hash, claimant]`
+#[account]
+pub struct CodeRedemption {
+    pub vault: Pubkey,
+    pub claimant: Pubkey,
+    pub code_hash: [u8; 32],
+    pub amount: u64,
+    pub redeemed_at: i64,
+    pub bump: u8,
+}
+
+impl CodeRedemption {
+    // discriminator + vault + claimant + code_hash + amount + redeemed_at + bump
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 1;
+}
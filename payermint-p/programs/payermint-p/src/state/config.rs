@@ -1,5 +1,24 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::{MAX_FEE_LIMITS, MAX_FEE_TIERS};
+use crate::state::AssetType;
+
+/// Discounted rate for vaults whose SOL payout volume reaches `min_volume`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct FeeTier {
+    pub min_volume: u64, // Lamports paid out over the rolling volume window
+    pub fee_bps: u16,
+}
+
+/// Bounds on the service fee for one payout, in the asset's base units.
+/// A `max_fee` of 0 leaves the fee uncapped.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct FeeLimit {
+    pub asset_type: AssetType,
+    pub min_fee: u64,
+    pub max_fee: u64,
+}
+
 #[account]
 pub struct GlobalConfig {
     pub owner: Pubkey,
@@ -7,4 +26,25 @@ pub struct GlobalConfig {
     pub default_fee_bps: u16,
     pub next_company_id: u64,
     pub bump: u8,
+    pub pending_owner: Option<Pubkey>, // Proposed owner awaiting `accept_config_owner`
+    pub pending_treasury: Option<Pubkey>, // Treasury change queued behind the timelock
+    pub treasury_eta: i64,             // Earliest time the pending treasury can be applied
+    pub pending_fee_bps: Option<u16>,  // Fee change queued behind the timelock
+    pub fee_eta: i64,                  // Earliest time the pending fee can be applied
+    pub paused: bool,                  // Blocks payouts and deposits program-wide
+    pub fee_tiers: Vec<FeeTier>,       // Ascending by `min_volume`, at most `MAX_FEE_TIERS`
+    pub fee_limits: Vec<FeeLimit>,     // At most one per asset, at most `MAX_FEE_LIMITS`
+    pub pending_fee_limit: Option<FeeLimit>, // Fee limit change queued behind the timelock
+    pub fee_limit_eta: i64,            // Earliest time the pending fee limit can be applied
+}
+
+impl GlobalConfig {
+    pub const FEE_TIERS_SPACE: usize = 4 + MAX_FEE_TIERS * FeeTier::INIT_SPACE;
+    pub const FEE_LIMITS_SPACE: usize = 4 + MAX_FEE_LIMITS * FeeLimit::INIT_SPACE;
+
+    pub fn fee_limit(&self, asset_type: &AssetType) -> Option<&FeeLimit> {
+        self.fee_limits
+            .iter()
+            .find(|limit| &limit.asset_type == asset_type)
+    }
 }
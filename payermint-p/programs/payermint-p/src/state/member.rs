@@ -11,4 +11,9 @@ pub struct Member {
     pub is_active: bool,
     pub metadata_uri: Option<String>,
     pub bump: u8,
-}
\ No newline at end of file
+}
+
+impl Member {
+    // discriminator + vault + wallet + allocation_bps + sol_allocation + spl_allocation + role + is_active + metadata_uri + bump
+    pub const SPACE: usize = 8 + 32 + 32 + 3 + 9 + 9 + (4 + 16) + 1 + (1 + 4 + 200) + 1;
+}
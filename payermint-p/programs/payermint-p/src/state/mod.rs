@@ -2,8 +2,20 @@ pub mod member;
 pub mod payout_batch;
 pub mod vault_account;
 pub mod config;
+pub mod payout_receipt;
+pub mod claim_code;
+pub mod payment_stream;
+pub mod vesting_schedule;
+pub mod approval_config;
+pub mod vault_delegate;
 
 pub use member::*;
 pub use payout_batch::*;
 pub use vault_account::*;
 pub use config::*;
+pub use payout_receipt::*;
+pub use claim_code::*;
+pub use payment_stream::*;
+pub use vesting_schedule::*;
+pub use approval_config::*;
+pub use vault_delegate::*;
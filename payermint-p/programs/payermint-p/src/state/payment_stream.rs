@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+use crate::math::pro_rata;
+use crate::state::{AssetType, FeeMode};
+
+/// Continuous payroll for a member, accruing `rate_per_second` between `start_ts`
+/// and the optional `end_ts`. Funds stay in the vault and are reserved up to
+/// `deposited`; the member wallet withdraws whatever has vested.
+/// Seeds: `[STREAM_SEED, member]`
+#[account]
+pub struct PaymentStream {
+    pub vault: Pubkey,
+    pub member: Pubkey,
+    pub wallet: Pubkey,
+    pub payer: Pubkey, // Receives the rent back when the stream is closed
+    pub asset_type: AssetType,
+    pub rate_per_second: u64,
+    pub start_ts: i64,
+    pub end_ts: Option<i64>,
+    pub deposited: u64,     // Total reserved for the stream, caps what can vest
+    pub withdrawn: u64,     // Total already paid out to the member
+    pub accrued: u64,       // Amount vested up to `checkpoint_ts`
+    pub checkpoint_ts: i64, // Accrual restarts from here after a pause or top up
+    pub fee_mode: FeeMode,  // Fixed when the stream is created
+    pub service_fee: u64,   // Fee on `deposited`, fixed when reserved and charged pro rata
+    pub fees_paid: u64,
+    pub paused: bool,
+    pub cancelled: bool,
+    pub bump: u8,
+}
+
+impl PaymentStream {
+    // discriminator + vault + member + wallet + payer + asset_type + rate + start + end + deposited + withdrawn + accrued + checkpoint + fee_mode + service_fee + fees_paid + paused + cancelled + bump
+    pub const SPACE: usize = 8
+        + 32
+        + 32
+        + 32
+        + 32
+        + AssetType::INIT_SPACE
+        + 8
+        + 8
+        + 9
+        + 8
+        + 8
+        + 8
+        + 8
+        + 1
+        + 8
+        + 8
+        + 1
+        + 1
+        + 1;
+
+    /// Amount vested at `now`, never more than what has been deposited
+    pub fn vested_at(&self, now: i64) -> u64 {
+        if self.paused || self.cancelled {
+            return self.accrued;
+        }
+        let until = self.end_ts.map_or(now, |end_ts| now.min(end_ts));
+        let elapsed = until.saturating_sub(self.checkpoint_ts).max(0) as u64;
+        self.accrued
+            .saturating_add(elapsed.saturating_mul(self.rate_per_second))
+            .min(self.deposited)
+    }
+
+    /// Fee owed on a withdrawal that brings the total withdrawn to `vested`: the share of
+    /// `service_fee` vested so far, less what earlier withdrawals already paid
+    pub fn fee_due(&self, vested: u64) -> Result<u64> {
+        Ok(pro_rata(self.service_fee, vested, self.deposited)?.saturating_sub(self.fees_paid))
+    }
+
+    /// Nothing is left to vest or withdraw, so the stream holds no reservation
+    pub fn is_settled(&self) -> bool {
+        self.withdrawn == self.deposited
+    }
+
+    /// Folds accrual up to `now` into `accrued` so later changes only affect the future
+    pub fn checkpoint(&mut self, now: i64) {
+        self.accrued = self.vested_at(now);
+        self.checkpoint_ts = now.max(self.start_ts);
+    }
+}
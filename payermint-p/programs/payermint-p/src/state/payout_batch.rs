@@ -1,20 +1,147 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::ErrorVault;
+use crate::math::checked_add;
+use crate::state::{ApprovalConfig, AssetType};
+
 #[account]
 pub struct PayrollBatch {
     pub vault: Pubkey,
     pub batch_id: u64,
     pub timestamp: i64,
-    pub total_amount: u64,
-    pub service_fee: u64,
+    pub total_amount: u64, // Spending cap for payouts in this batch
+    pub service_fee: u64,  // Estimated at creation; compare with `fees_collected`
     pub payout_count: u32,
     pub finalized: bool,
     pub bump: u8,
+    pub payer: Pubkey,          // Receives the rent back when the batch is closed
+    pub finalized_at: i64,      // 0 until finalized
+    pub paid_amount: u64,       // Gross amount paid or allocated so far
+    pub fees_collected: u64,    // Service fees actually charged so far
+    pub approvals: u16,         // Bitmap over the vault's approvers
+    pub approvals_version: u32, // Approver set version the bitmap was collected under
+    pub asset_type: Option<AssetType>, // Asset of every payout, fixed by the first one
+    pub fee_delta: i64,         // `fees_collected - service_fee`, recorded at finalize
+}
+
+impl PayrollBatch {
+    /// Payouts need the approval threshold of the vault's current approver set, read
+    /// from its `ApprovalConfig` PDA. A vault whose PDA is uninitialized has no approvers.
+    pub fn require_approved(&self, approval_config: &AccountInfo) -> Result<()> {
+        if approval_config.data_is_empty() {
+            return Ok(());
+        }
+        require_keys_eq!(
+            *approval_config.owner,
+            crate::ID,
+            ErrorVault::InvalidApproverConfig
+        );
+        let config = ApprovalConfig::try_deserialize(&mut &approval_config.try_borrow_data()?[..])?;
+        require!(
+            self.approvals_version == config.version && config.threshold_met(self.approvals),
+            ErrorVault::InsufficientApprovals
+        );
+        Ok(())
+    }
+
+    /// Counts a payout against the batch cap. The cap and fee totals are kept in a
+    /// single asset's units, so a batch only pays out the asset of its first payout.
+    pub fn record_payout(
+        &mut self,
+        asset_type: &AssetType,
+        amount: u64,
+        service_fee: u64,
+    ) -> Result<()> {
+        match &self.asset_type {
+            Some(batch_asset) => {
+                require!(batch_asset == asset_type, ErrorVault::BatchAssetMismatch)
+            }
+            None => self.asset_type = Some(asset_type.clone()),
+        }
+        let paid_amount = checked_add(self.paid_amount, amount)?;
+        require!(
+            paid_amount <= self.total_amount,
+            ErrorVault::BatchCapExceeded
+        );
+        self.paid_amount = paid_amount;
+        self.fees_collected = checked_add(self.fees_collected, service_fee)?;
+        Ok(())
+    }
+
+    /// How far the fees actually charged ended up from the creation-time estimate.
+    /// Positive when per-payout minimum fees or rate changes pushed them above it.
+    pub fn fee_delta(&self) -> Result<i64> {
+        i64::try_from(self.fees_collected as i128 - self.service_fee as i128)
+            .map_err(|_| error!(ErrorVault::MathOverflow))
+    }
 }
 
 #[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct PayoutSchedule {
     pub interval: i64,
-    pub next_payout_ts: i64,
+    pub next_payout_ts: i64, // Start of the period scheduled payouts are made for
     pub active: bool,
-}
\ No newline at end of file
+}
+
+impl PayoutSchedule {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.interval > 0, ErrorVault::InvalidPayoutSchedule);
+        Ok(())
+    }
+
+    /// Start of the period that is payable at `now`. A period ends when the next one
+    /// begins, so members not paid by then are skipped rather than stalling the schedule.
+    pub fn current_period(&self, now: i64) -> Result<i64> {
+        self.validate()?;
+        if now < self.next_payout_ts {
+            return Ok(self.next_payout_ts);
+        }
+        now.checked_sub(self.next_payout_ts)
+            .map(|elapsed| elapsed / self.interval)
+            .and_then(|elapsed_periods| elapsed_periods.checked_mul(self.interval))
+            .and_then(|elapsed| self.next_payout_ts.checked_add(elapsed))
+            .ok_or_else(|| error!(ErrorVault::MathOverflow))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WEEK: i64 = 7 * 24 * 60 * 60;
+
+    fn schedule(next_payout_ts: i64) -> PayoutSchedule {
+        PayoutSchedule {
+            interval: WEEK,
+            next_payout_ts,
+            active: true,
+        }
+    }
+
+    #[test]
+    fn period_stays_open_until_the_next_one_begins() {
+        let schedule = schedule(1_000);
+        assert_eq!(schedule.current_period(0).unwrap(), 1_000);
+        assert_eq!(schedule.current_period(1_000).unwrap(), 1_000);
+        assert_eq!(schedule.current_period(1_000 + WEEK - 1).unwrap(), 1_000);
+        assert_eq!(schedule.current_period(1_000 + WEEK).unwrap(), 1_000 + WEEK);
+    }
+
+    #[test]
+    fn missed_periods_are_skipped() {
+        let schedule = schedule(1_000);
+        assert_eq!(
+            schedule.current_period(1_000 + 3 * WEEK + 5).unwrap(),
+            1_000 + 3 * WEEK
+        );
+    }
+
+    #[test]
+    fn non_positive_interval_is_rejected() {
+        let mut schedule = schedule(1_000);
+        schedule.interval = 0;
+        assert!(schedule.current_period(2_000).is_err());
+        schedule.interval = -WEEK;
+        assert!(schedule.validate().is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::state::AssetType;
+
+/// Identifies the payroll cycle a receipt belongs to
+#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum PayoutCycle {
+    Batch { batch_id: u64 },
+    Period { start_ts: i64 },
+}
+
+impl PayoutCycle {
+    pub fn batch_id(&self) -> Option<u64> {
+        match self {
+            PayoutCycle::Batch { batch_id } => Some(*batch_id),
+            PayoutCycle::Period { .. } => None,
+        }
+    }
+}
+
+/// Created when a member is paid; its existence blocks a second payout in the same cycle.
+/// Seeds: `[RECEIPT_SEED, payroll_batch, member]` for batch payouts and
+/// `[RECEIPT_SEED, vault, period_start_ts, member]` for scheduled payouts.
+///
+/// Receipts created by `allocate_claim` start unclaimed: the amount stays reserved
+/// in the vault until the member wallet pulls it with a claim instruction, or the
+/// vault owner withdraws it with `cancel_claim`.
+///
+/// `close_receipt` returns the rent to `payer` once the batch is finalized or the
+/// period has ended.
+#[account]
+pub struct PayoutReceipt {
+    pub vault: Pubkey,
+    pub member: Pubkey,
+    pub wallet: Pubkey,
+    pub cycle: PayoutCycle,
+    pub asset_type: AssetType,
+    pub amount: u64,
+    pub service_fee: u64,
+    pub net_amount: u64, // Paid to the wallet; equals `amount` when the vault grossed up the fee
+    pub paid_at: i64,
+    pub claimed: bool,
+    pub bump: u8,
+    pub payer: Pubkey, // Receives the rent back when the receipt is closed
+}
+
+impl PayoutReceipt {
+    // discriminator + vault + member + wallet + cycle + asset_type + amount + service_fee + net_amount + paid_at + claimed + bump + payer
+    pub const SPACE: usize = 8
+        + 32
+        + 32
+        + 32
+        + PayoutCycle::INIT_SPACE
+        + AssetType::INIT_SPACE
+        + 8
+        + 8
+        + 8
+        + 8
+        + 1
+        + 1
+        + 32;
+}
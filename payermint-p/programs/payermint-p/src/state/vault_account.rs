@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
 
-use crate::state::PayoutSchedule;
+use crate::constants::{FEE_VOLUME_WINDOW_DAYS, MAX_WHITELISTED_ASSETS, SECONDS_PER_DAY};
+use crate::errors::ErrorVault;
+use crate::math::{checked_add, checked_sub};
+use crate::state::{ClaimCode, PayoutSchedule};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub enum VaultType {
@@ -22,31 +25,349 @@ pub enum AllocationType {
     AllocationSpecify,
 }
 
+/// Who bears the service fee on a payout. Pull-based flows (claims, streams,
+/// vesting) fix the mode and the fee when their funds are reserved, and reserve
+/// the fee as well when it is grossed up.
+#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FeeMode {
+    Deduct,  // Member receives the amount minus the fee
+    GrossUp, // Member receives the full amount; the vault pays the fee on top
+}
+
 #[account]
 pub struct VaultAccount {
     pub owner: Pubkey,
+    pub vault_id: u64, // company id used in the vault PDA seeds
     // #[max_len(32)]
     pub name: String,
     pub vault_type: VaultType,
     pub whitelisted_assets: Vec<AssetType>,
     pub payout_schedule: Option<PayoutSchedule>,
-    pub total_balance: u64,
+    pub total_balance: u64, // SOL available to payouts; kept equal to `sol_balance`
     pub required_balance: u64,
-    pub required_spl_balance: u64,
     pub last_deposit_ts: i64,
     pub allocation_type: AllocationType,
     // #[max_len(200)]
     pub metadata_uri: Option<String>,
-    // #[max_len(10)]
-    pub code_claim: Option<String>, // since i didnt found the practical way to handle this iam gonna handle the code claim offchain
+    pub code_claim: Option<ClaimCode>, // Hashed claim code redeemable with `claim_with_code`
     pub bump: u8,
 
-    pub spl_balances: Vec<SplTokenBalance>, // A list of balances for SPL tokens in the vault
-    pub sol_balance: u64,                   // SOL balance in the vault (native Solana tokens)
+    pub spl_balances: Vec<SplTokenBalance>, // Tracked balance of each whitelisted mint's vault ATA
+    pub sol_balance: u64,                   // Lamports held above the vault's rent-exempt minimum
+
+    pub keeper_reward: u64, // Lamports paid to whoever cranks a scheduled payout
+    pub active_member_count: u32, // Members currently marked active
+    pub period_paid_count: u32, // Members paid in the current schedule period
+    pub period_start_balance: u64, // Vault balance snapshot used for bps allocations this period
+    pub paid_through: i64,  // End of the latest period a scheduled payout was made for
+
+    pub member_count: u32,     // Member accounts not yet removed, active or not
+    pub open_batch_count: u32, // Payroll batches not yet finalized
+    pub batch_count: u32,      // Payroll batches not yet closed, finalized or not
+    pub stream_count: u32,     // Payment streams not yet closed
+    pub vesting_count: u32,    // Vesting schedules not yet closed
+    pub delegate_count: u32,   // Delegates not yet removed
+    pub batch_retention_secs: i64, // Time a finalized batch is kept before it can be closed
+
+    pub creator: Pubkey, // Owner at creation; stays in the PDA seeds after ownership transfers
+    pub pending_owner: Option<Pubkey>, // Proposed owner awaiting `accept_vault_owner`
+
+    pub paused: bool, // Blocks payouts from this vault
+
+    pub fee_override_bps: Option<u16>, // Negotiated rate set by the config owner
+    pub sol_volume: VolumeWindow,      // Lamports paid out over the last 30 days
+
+    pub fee_mode: FeeMode,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+impl VaultAccount {
+    pub const SPACE: usize = 8 // discriminator
+        + 32 // owner
+        + 8 // vault_id
+        + 4 + 32 // name
+        + 1 // vault_type
+        + 4 + MAX_WHITELISTED_ASSETS * AssetType::INIT_SPACE // whitelisted_assets
+        + 1 + PayoutSchedule::INIT_SPACE // payout_schedule
+        + 8 // total_balance
+        + 8 // required_balance
+        + 8 // last_deposit_ts
+        + 1 // allocation_type
+        + 1 + 4 + 200 // metadata_uri
+        + 1 + ClaimCode::INIT_SPACE // code_claim
+        + 1 // bump
+        + 4 + MAX_WHITELISTED_ASSETS * SplTokenBalance::INIT_SPACE // spl_balances, one per whitelisted mint
+        + 8 // sol_balance
+        + 8 // keeper_reward
+        + 4 // active_member_count
+        + 4 // period_paid_count
+        + 8 // period_start_balance
+        + 8 // paid_through
+        + 4 // member_count
+        + 4 // open_batch_count
+        + 4 // batch_count
+        + 4 // stream_count
+        + 4 // vesting_count
+        + 4 // delegate_count
+        + 8 // batch_retention_secs
+        + 32 // creator
+        + 1 + 32 // pending_owner
+        + 1 // paused
+        + 1 + 2 // fee_override_bps
+        + VolumeWindow::INIT_SPACE // sol_volume
+        + 1; // fee_mode
+
+    /// SOL payout volume counted towards fee tiers at `now`
+    pub fn sol_volume_at(&self, now: i64) -> u64 {
+        self.sol_volume.total_at(now)
+    }
+
+    pub fn record_sol_volume(&mut self, amount: u64, now: i64) {
+        self.sol_volume.record(amount, now);
+    }
+
+    /// Records SOL entering the vault
+    pub fn credit_sol(&mut self, amount: u64) -> Result<()> {
+        self.set_sol_balance(checked_add(self.total_balance, amount)?);
+        Ok(())
+    }
+
+    /// Records SOL leaving the vault
+    pub fn debit_sol(&mut self, amount: u64) -> Result<()> {
+        self.set_sol_balance(checked_sub(self.total_balance, amount)?);
+        Ok(())
+    }
+
+    pub fn set_sol_balance(&mut self, balance: u64) {
+        self.total_balance = balance;
+        self.sol_balance = balance;
+    }
+
+    pub fn tracks_spl(&self, mint: &Pubkey) -> bool {
+        self.spl_balances.iter().any(|entry| entry.mint == *mint)
+    }
+
+    pub fn spl_balance(&self, mint: &Pubkey) -> u64 {
+        self.spl_balances
+            .iter()
+            .find(|entry| entry.mint == *mint)
+            .map_or(0, |entry| entry.balance)
+    }
+
+    /// Records tokens of `mint` entering the vault ATA
+    pub fn credit_spl(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        let balance = checked_add(self.spl_balance(mint), amount)?;
+        self.set_spl_balance(mint, balance)
+    }
+
+    /// Records tokens of `mint` leaving the vault ATA
+    pub fn debit_spl(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        let balance = checked_sub(self.spl_balance(mint), amount)?;
+        self.set_spl_balance(mint, balance)
+    }
+
+    pub fn set_spl_balance(&mut self, mint: &Pubkey, balance: u64) -> Result<()> {
+        self.spl_entry_mut(mint)?.balance = balance;
+        Ok(())
+    }
+
+    /// Tokens of `mint` reserved for claims, streams and vesting
+    pub fn spl_reserved(&self, mint: &Pubkey) -> u64 {
+        self.spl_balances
+            .iter()
+            .find(|entry| entry.mint == *mint)
+            .map_or(0, |entry| entry.reserved)
+    }
+
+    pub fn reserve_spl(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        let entry = self.spl_entry_mut(mint)?;
+        entry.reserved = checked_add(entry.reserved, amount)?;
+        Ok(())
+    }
+
+    pub fn release_spl(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        let entry = self.spl_entry_mut(mint)?;
+        entry.reserved = checked_sub(entry.reserved, amount)?;
+        Ok(())
+    }
+
+    pub fn has_spl_reservations(&self) -> bool {
+        self.spl_balances.iter().any(|entry| entry.reserved > 0)
+    }
+
+    /// Drops the entry for `mint` once it holds nothing, so a mint can leave the whitelist
+    pub fn untrack_spl(&mut self, mint: &Pubkey) -> Result<()> {
+        require!(
+            self.spl_balance(mint) == 0 && self.spl_reserved(mint) == 0,
+            ErrorVault::AssetHasBalance
+        );
+        self.spl_balances.retain(|entry| entry.mint != *mint);
+        Ok(())
+    }
+
+    /// Reconciles the tracked balance of `mint` with its vault ATA. A mint that left the
+    /// whitelist keeps an entry while its ATA still holds tokens, so they stay visible to
+    /// `withdraw_spl_token` and `close_vault`; the entry is dropped once it is empty.
+    pub fn sync_spl_balance(&mut self, mint: &Pubkey, balance: u64) -> Result<()> {
+        if self.is_whitelisted_mint(mint) {
+            return self.set_spl_balance(mint, balance);
+        }
+        if let Some(index) = self
+            .spl_balances
+            .iter()
+            .position(|entry| entry.mint == *mint)
+        {
+            let entry = &mut self.spl_balances[index];
+            entry.balance = balance;
+            if entry.balance == 0 && entry.reserved == 0 {
+                self.spl_balances.remove(index);
+            }
+        } else if balance > 0 {
+            require!(
+                self.spl_slots_used() < MAX_WHITELISTED_ASSETS,
+                ErrorVault::TooManyWhitelistedAssets
+            );
+            self.spl_balances.push(SplTokenBalance {
+                mint: *mint,
+                balance,
+                reserved: 0,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn is_whitelisted_mint(&self, mint: &Pubkey) -> bool {
+        self.whitelisted_assets
+            .contains(&AssetType::SPLToken { mint: *mint })
+    }
+
+    /// `spl_balances` entries a mint may need: one per whitelisted mint plus one per
+    /// mint synced after it left the whitelist. Kept within `MAX_WHITELISTED_ASSETS`.
+    pub fn spl_slots_used(&self) -> usize {
+        let whitelisted = self
+            .whitelisted_assets
+            .iter()
+            .filter(|asset| matches!(asset, AssetType::SPLToken { .. }))
+            .count();
+        let delisted = self
+            .spl_balances
+            .iter()
+            .filter(|entry| !self.is_whitelisted_mint(&entry.mint))
+            .count();
+        whitelisted + delisted
+    }
+
+    /// New entries are only made for whitelisted mints (or by `sync_spl_balance`, within
+    /// `spl_slots_used`), so `spl_balances` never holds more than `MAX_WHITELISTED_ASSETS`
+    fn spl_entry_mut(&mut self, mint: &Pubkey) -> Result<&mut SplTokenBalance> {
+        if let Some(index) = self
+            .spl_balances
+            .iter()
+            .position(|entry| entry.mint == *mint)
+        {
+            return Ok(&mut self.spl_balances[index]);
+        }
+        require!(
+            self.is_whitelisted_mint(mint),
+            ErrorVault::AssetNotWhitelisted
+        );
+        self.spl_balances.push(SplTokenBalance {
+            mint: *mint,
+            balance: 0,
+            reserved: 0,
+        });
+        Ok(self.spl_balances.last_mut().unwrap())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct SplTokenBalance {
-    pub mint: Pubkey, // The mint address of the SPL token
-    pub balance: u64, // The balance of the SPL token
+    pub mint: Pubkey,  // The mint address of the SPL token
+    pub balance: u64,  // The balance of the SPL token
+    pub reserved: u64, // Part of `balance` reserved for claims, streams and vesting
+}
+
+/// Payout volume over a rolling window of `FEE_VOLUME_WINDOW_DAYS` days. Each day has
+/// its own bucket, so volume drops out one day at a time instead of all at once.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct VolumeWindow {
+    pub last_day: i64, // Day of the most recent `record`, in days since the epoch
+    pub daily_volume: [u64; FEE_VOLUME_WINDOW_DAYS], // Indexed by day modulo the window
+}
+
+impl VolumeWindow {
+    const DAYS: i64 = FEE_VOLUME_WINDOW_DAYS as i64;
+
+    fn day_of(now: i64) -> i64 {
+        now.div_euclid(SECONDS_PER_DAY)
+    }
+
+    fn bucket(day: i64) -> usize {
+        day.rem_euclid(Self::DAYS) as usize
+    }
+
+    /// Volume recorded during the window ending on the day of `now`
+    pub fn total_at(&self, now: i64) -> u64 {
+        let today = Self::day_of(now).max(self.last_day);
+        (today - Self::DAYS + 1..=self.last_day)
+            .map(|day| self.daily_volume[Self::bucket(day)])
+            .fold(0, u64::saturating_add)
+    }
+
+    pub fn record(&mut self, amount: u64, now: i64) {
+        let today = Self::day_of(now).max(self.last_day);
+        // Clear the buckets of days that passed without a payout
+        let stale_days = (today - self.last_day).min(Self::DAYS);
+        for day in today - stale_days + 1..=today {
+            self.daily_volume[Self::bucket(day)] = 0;
+        }
+        self.last_day = today;
+        let bucket = &mut self.daily_volume[Self::bucket(today)];
+        *bucket = bucket.saturating_add(amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = SECONDS_PER_DAY;
+    const WINDOW: i64 = FEE_VOLUME_WINDOW_DAYS as i64 * DAY;
+
+    #[test]
+    fn volume_accumulates_within_the_window() {
+        let mut window = VolumeWindow::default();
+        window.record(100, 10 * DAY);
+        window.record(50, 10 * DAY + 5);
+        window.record(25, 20 * DAY);
+        assert_eq!(window.total_at(20 * DAY), 175);
+    }
+
+    #[test]
+    fn volume_rolls_off_one_day_at_a_time() {
+        let mut window = VolumeWindow::default();
+        window.record(100, 10 * DAY);
+        window.record(50, 11 * DAY);
+
+        // Last day the first payout still counts
+        assert_eq!(window.total_at(10 * DAY + WINDOW - 1), 150);
+        // Crossing the boundary drops only the oldest day
+        assert_eq!(window.total_at(10 * DAY + WINDOW), 50);
+        assert_eq!(window.total_at(11 * DAY + WINDOW), 0);
+
+        // Recording after the boundary keeps the days still inside the window
+        window.record(10, 10 * DAY + WINDOW);
+        assert_eq!(window.total_at(10 * DAY + WINDOW), 60);
+    }
+
+    #[test]
+    fn long_gaps_clear_every_bucket() {
+        let mut window = VolumeWindow::default();
+        for day in 0..FEE_VOLUME_WINDOW_DAYS as i64 {
+            window.record(1, day * DAY);
+        }
+        assert_eq!(window.total_at(WINDOW - 1), FEE_VOLUME_WINDOW_DAYS as u64);
+
+        window.record(7, 5 * WINDOW);
+        assert_eq!(window.total_at(5 * WINDOW), 7);
+    }
 }
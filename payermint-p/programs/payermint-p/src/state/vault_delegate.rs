@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+/// Permissions a vault owner grants to a delegate.
+/// Seeds: `[DELEGATE_SEED, vault, delegate]`
+#[account]
+#[derive(InitSpace)]
+pub struct VaultDelegate {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub permissions: u8,    // Bitflags, see the constants below
+    pub granted_by: Pubkey, // Grants from a previous vault owner lapse
+    pub bump: u8,
+}
+
+impl VaultDelegate {
+    pub const MANAGE_MEMBERS: u8 = 1 << 0;
+    pub const CREATE_BATCHES: u8 = 1 << 1;
+    pub const EXECUTE_PAYOUTS: u8 = 1 << 2;
+    pub const UPDATE_SCHEDULE: u8 = 1 << 3;
+    pub const WITHDRAW: u8 = 1 << 4;
+    pub const ALL_PERMISSIONS: u8 = Self::MANAGE_MEMBERS
+        | Self::CREATE_BATCHES
+        | Self::EXECUTE_PAYOUTS
+        | Self::UPDATE_SCHEDULE
+        | Self::WITHDRAW;
+
+    pub fn has_permission(&self, permission: u8) -> bool {
+        self.permissions & permission == permission
+    }
+}
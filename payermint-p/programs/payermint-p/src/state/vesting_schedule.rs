@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::math::pro_rata;
+use crate::state::FeeMode;
+
+/// Vesting of a member's `spl_token_allocation` in a whitelisted mint. Nothing vests
+/// before `cliff_ts`; after it tokens vest linearly from `start_ts` over `duration`.
+/// The tokens stay reserved in the vault ATA until the member claims them.
+/// Seeds: `[VESTING_SEED, member]`
+#[account]
+pub struct VestingSchedule {
+    pub vault: Pubkey,
+    pub member: Pubkey,
+    pub wallet: Pubkey,
+    pub payer: Pubkey, // Receives the rent back when the schedule is closed
+    pub mint: Pubkey,
+    pub total_amount: u64, // Frozen at the vested amount when revoked
+    pub start_ts: i64,
+    pub cliff_ts: i64,
+    pub duration: i64,
+    pub claimed: u64,
+    pub fee_mode: FeeMode, // Fixed when the schedule is created
+    pub service_fee: u64,  // Fee on `total_amount`, fixed when reserved and charged pro rata
+    pub fees_paid: u64,
+    pub revocable: bool,
+    pub revoked: bool,
+    pub bump: u8,
+}
+
+impl VestingSchedule {
+    // discriminator + vault + member + wallet + payer + mint + total + start + cliff + duration + claimed + fee_mode + service_fee + fees_paid + revocable + revoked + bump
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + 8 + 1 + 1 + 1;
+
+    /// Fee owed on a claim that brings the total claimed to `vested`: the share of
+    /// `service_fee` vested so far, less what earlier claims already paid
+    pub fn fee_due(&self, vested: u64) -> Result<u64> {
+        Ok(pro_rata(self.service_fee, vested, self.total_amount)?.saturating_sub(self.fees_paid))
+    }
+
+    /// Everything that will ever vest has been claimed
+    pub fn is_settled(&self) -> bool {
+        self.claimed == self.total_amount
+    }
+
+    /// Amount vested at `now`
+    pub fn vested_at(&self, now: i64) -> u64 {
+        if self.revoked {
+            return self.total_amount;
+        }
+        if now < self.cliff_ts {
+            return 0;
+        }
+        let elapsed = now.saturating_sub(self.start_ts).clamp(0, self.duration);
+        ((self.total_amount as u128 * elapsed as u128) / self.duration as u128) as u64
+    }
+}
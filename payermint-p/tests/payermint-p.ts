@@ -3,15 +3,19 @@ import { Program, BN, web3 } from "@coral-xyz/anchor";
 import { PayermintP } from "../target/types/payermint_p";
 import {
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
   createMint,
   createAssociatedTokenAccount,
+  createAccount,
   mintTo,
   getAssociatedTokenAddress,
+  getAssociatedTokenAddressSync,
   getAccount,
   getMint,
 } from "@solana/spl-token";
 import { expect } from "chai";
+import { createHash, randomBytes } from "crypto";
 import * as fs from "fs";
 
 describe("Payermint Protocol", () => {
@@ -43,6 +47,79 @@ describe("Payermint Protocol", () => {
   const DEPOSIT_AMOUNT = new BN(10 * web3.LAMPORTS_PER_SOL); // 10 SOL
   const TOKEN_DEPOSIT_AMOUNT = new BN(1000 * 10 ** 6); // 1000 tokens (assuming 6 decimals)
 
+  // Salted claim code hash stored on the vault: sha256(code || vault)
+  const claimCodeHash = (code: string, vault: web3.PublicKey): number[] =>
+    Array.from(
+      createHash("sha256")
+        .update(Buffer.concat([Buffer.from(code), vault.toBuffer()]))
+        .digest()
+    );
+
+  // Redemption PDA created when a wallet redeems a vault claim code
+  const redemptionFor = (
+    vault: web3.PublicKey,
+    code: string,
+    claimant: web3.PublicKey
+  ) =>
+    web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("redemption"),
+        vault.toBuffer(),
+        Buffer.from(claimCodeHash(code, vault)),
+        claimant.toBuffer(),
+      ],
+      program.programId
+    )[0];
+
+  // Approval config PDA; batch payouts pass it whether or not it is initialized
+  const approvalConfigFor = (vault: web3.PublicKey) =>
+    web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("approval"), vault.toBuffer()],
+      program.programId
+    )[0];
+
+  // Receipt PDA created when a member is paid within a payroll batch
+  const receiptFor = (batch: web3.PublicKey, member: web3.PublicKey) =>
+    web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("receipt"), batch.toBuffer(), member.toBuffer()],
+      program.programId
+    )[0];
+
+  // Receipt PDA created when a member is paid for a scheduled period
+  const periodReceiptFor = (
+    vault: web3.PublicKey,
+    period: BN,
+    member: web3.PublicKey
+  ) =>
+    web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("receipt"),
+        vault.toBuffer(),
+        period.toArrayLike(Buffer, "le", 8),
+        member.toBuffer(),
+      ],
+      program.programId
+    )[0];
+
+  // Period a scheduled payout must target; a period stays payable until the
+  // next one begins, and the next crank moves the schedule forward to it
+  const currentPeriod = (vault: any): BN => {
+    const { interval, nextPayoutTs } = vault.payoutSchedule;
+    const now = new BN(Math.floor(Date.now() / 1000));
+    if (now.lt(nextPayoutTs.add(interval))) {
+      return nextPayoutTs;
+    }
+    return nextPayoutTs.add(now.sub(nextPayoutTs).div(interval).mul(interval));
+  };
+
+  // Tokens of `mint` reserved for claims, streams and vesting
+  const splReserved = (vault: any, mint: web3.PublicKey): BN => {
+    const entry = vault.splBalances.find((balance: any) =>
+      balance.mint.equals(mint)
+    );
+    return entry ? entry.reserved : new BN(0);
+  };
+
   before(async () => {
     // Initialize test accounts
     treasury = web3.Keypair.generate();
@@ -135,7 +212,7 @@ describe("Payermint Protocol", () => {
       expect(configAccount.nextCompanyId.toString()).to.equal("1");
     });
 
-    it("Should update treasury", async () => {
+    it("Should queue a treasury change behind the timelock", async () => {
       const originalTreasury = treasury.publicKey;
       const newTreasury = web3.Keypair.generate().publicKey;
 
@@ -150,44 +227,172 @@ describe("Payermint Protocol", () => {
       const configAccount = await program.account.globalConfig.fetch(
         globalConfig
       );
-      expect(configAccount.treasury.toString()).to.equal(
+      expect(configAccount.pendingTreasury.toString()).to.equal(
         newTreasury.toString()
       );
+      // The live treasury is untouched until the timelock elapses
+      expect(configAccount.treasury.toString()).to.equal(
+        originalTreasury.toString()
+      );
+      expect(configAccount.treasuryEta.toNumber()).to.be.greaterThan(
+        Math.floor(Date.now() / 1000)
+      );
+
+      try {
+        await program.methods
+          .applyConfigChange()
+          .accountsStrict({ globalConfig: globalConfig })
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("TimelockNotElapsed");
+      }
+    });
+
+    it("Should queue a fee change and cancel pending changes", async () => {
+      const newFee = 300; // 3%
+
+      await program.methods
+        .updateDefaultFee(newFee)
+        .accountsStrict({
+          globalConfig: globalConfig,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      let configAccount = await program.account.globalConfig.fetch(
+        globalConfig
+      );
+      expect(configAccount.pendingFeeBps).to.equal(newFee);
+      expect(configAccount.defaultFeeBps).to.equal(DEFAULT_FEE_BPS);
 
-      // Reset back to original treasury for other tests
       await program.methods
-        .updateTreasury(originalTreasury)
+        .cancelConfigChange()
         .accountsStrict({
           globalConfig: globalConfig,
           owner: provider.wallet.publicKey,
         })
         .rpc();
+
+      configAccount = await program.account.globalConfig.fetch(globalConfig);
+      expect(configAccount.pendingFeeBps).to.be.null;
+      expect(configAccount.pendingTreasury).to.be.null;
     });
 
-    it("Should update default fee", async () => {
-      const newFee = 300; // 3%
+    it("Should queue a fee limit behind the timelock", async () => {
+      try {
+        await program.methods
+          .updateFeeLimit({
+            assetType: { sol: {} },
+            minFee: new BN(2000),
+            maxFee: new BN(1000),
+          })
+          .accountsStrict({
+            globalConfig: globalConfig,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidFeeLimit");
+      }
 
       await program.methods
-        .updateDefaultFee(newFee)
+        .updateFeeLimit({
+          assetType: { sol: {} },
+          minFee: new BN(1000),
+          maxFee: new BN(web3.LAMPORTS_PER_SOL),
+        })
         .accountsStrict({
           globalConfig: globalConfig,
           owner: provider.wallet.publicKey,
         })
         .rpc();
 
-      const configAccount = await program.account.globalConfig.fetch(
+      let configAccount = await program.account.globalConfig.fetch(
         globalConfig
       );
-      expect(configAccount.defaultFeeBps).to.equal(newFee);
+      expect(configAccount.pendingFeeLimit.minFee.toNumber()).to.equal(1000);
+      expect(configAccount.feeLimits.length).to.equal(0);
+
+      await program.methods
+        .cancelConfigChange()
+        .accountsStrict({
+          globalConfig: globalConfig,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      configAccount = await program.account.globalConfig.fetch(globalConfig);
+      expect(configAccount.pendingFeeLimit).to.be.null;
+    });
+
+    it("Should hand the config over in two steps", async () => {
+      const nextAdmin = web3.Keypair.generate();
 
-      // Reset to original fee for other tests
       await program.methods
-        .updateDefaultFee(DEFAULT_FEE_BPS)
+        .proposeConfigOwner(nextAdmin.publicKey)
         .accountsStrict({
           globalConfig: globalConfig,
           owner: provider.wallet.publicKey,
         })
         .rpc();
+
+      await program.methods
+        .acceptConfigOwner()
+        .accountsStrict({
+          globalConfig: globalConfig,
+          newOwner: nextAdmin.publicKey,
+        })
+        .signers([nextAdmin])
+        .rpc();
+
+      let configAccount = await program.account.globalConfig.fetch(
+        globalConfig
+      );
+      expect(configAccount.owner.toString()).to.equal(
+        nextAdmin.publicKey.toString()
+      );
+      expect(configAccount.pendingOwner).to.be.null;
+
+      // Hand it back so the remaining tests keep using the provider wallet
+      await program.methods
+        .proposeConfigOwner(provider.wallet.publicKey)
+        .accountsStrict({
+          globalConfig: globalConfig,
+          owner: nextAdmin.publicKey,
+        })
+        .signers([nextAdmin])
+        .rpc();
+
+      await program.methods
+        .acceptConfigOwner()
+        .accountsStrict({
+          globalConfig: globalConfig,
+          newOwner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      configAccount = await program.account.globalConfig.fetch(globalConfig);
+      expect(configAccount.owner.toString()).to.equal(
+        provider.wallet.publicKey.toString()
+      );
+    });
+
+    it("Should only let the proposed config owner accept", async () => {
+      try {
+        await program.methods
+          .acceptConfigOwner()
+          .accountsStrict({
+            globalConfig: globalConfig,
+            newOwner: member1.publicKey,
+          })
+          .signers([member1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("NotPendingOwner");
+      }
     });
 
     it("Should fail to update with invalid fee (>10000 bps)", async () => {
@@ -235,7 +440,14 @@ describe("Payermint Protocol", () => {
           null, // no payout schedule initially
           { allocationPerBps: {} },
           "https://example.com/metadata.json", // metadata_uri
-          "TESTCODE" // code_claim
+          {
+            codeHash: claimCodeHash("TESTCODE-7F3A9C21E4B5", vaultAccount),
+            amount: new BN(web3.LAMPORTS_PER_SOL / 10),
+            assetType: { sol: {} },
+            expiresAt: null,
+            maxUses: 2,
+            uses: 0,
+          } // code_claim, stored hashed
         )
         .accountsStrict({
           vaultAccount: vaultAccount,
@@ -275,7 +487,8 @@ describe("Payermint Protocol", () => {
         .updatePayoutSchedule(payoutSchedule)
         .accountsStrict({
           vaultAccount: vaultAccount,
-          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
         })
         .signers([vaultOwner])
         .rpc();
@@ -324,6 +537,48 @@ describe("Payermint Protocol", () => {
       const vault = await program.account.vaultAccount.fetch(vaultAccount);
       expect(vault.whitelistedAssets).to.have.lengthOf(3);
     });
+
+    it("Should cap the number of whitelisted assets", async () => {
+      const updateAsset = (method: string, mint: web3.PublicKey) =>
+        program.methods[method]({ splToken: { mint } })
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            owner: vaultOwner.publicKey,
+          })
+          .signers([vaultOwner])
+          .rpc();
+
+      const vaultBefore = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+      const extraMints = Array.from(
+        { length: 10 - vaultBefore.whitelistedAssets.length },
+        () => web3.Keypair.generate().publicKey
+      );
+      for (const mint of extraMints) {
+        await updateAsset("addWhitelistedAsset", mint);
+      }
+
+      try {
+        await updateAsset(
+          "addWhitelistedAsset",
+          web3.Keypair.generate().publicKey
+        );
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("TooManyWhitelistedAssets");
+      }
+
+      for (const mint of extraMints) {
+        await updateAsset("removeWhitelistedAsset", mint);
+      }
+      const vaultAfter = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+      expect(vaultAfter.whitelistedAssets).to.have.lengthOf(
+        vaultBefore.whitelistedAssets.length
+      );
+    });
   });
 
   describe("Member Management", () => {
@@ -372,7 +627,8 @@ describe("Payermint Protocol", () => {
         .accountsStrict({
           payer: vaultOwner.publicKey,
           vaultAccount: vaultAccount,
-          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
           member: member1Account,
           wallet: member1.publicKey,
           systemProgram: web3.SystemProgram.programId,
@@ -392,7 +648,8 @@ describe("Payermint Protocol", () => {
         .accountsStrict({
           payer: vaultOwner.publicKey,
           vaultAccount: vaultAccount,
-          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
           member: member2Account,
           wallet: member2.publicKey,
           systemProgram: web3.SystemProgram.programId,
@@ -412,7 +669,8 @@ describe("Payermint Protocol", () => {
         .accountsStrict({
           payer: vaultOwner.publicKey,
           vaultAccount: vaultAccount,
-          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
           member: member3Account,
           wallet: member3.publicKey,
           systemProgram: web3.SystemProgram.programId,
@@ -439,7 +697,8 @@ describe("Payermint Protocol", () => {
         .accountsStrict({
           vaultAccount: vaultAccount,
           member: member1Account,
-          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
         })
         .signers([vaultOwner])
         .rpc();
@@ -457,7 +716,8 @@ describe("Payermint Protocol", () => {
         .accountsStrict({
           vaultAccount: vaultAccount,
           member: member1Account,
-          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
         })
         .signers([vaultOwner])
         .rpc();
@@ -477,7 +737,8 @@ describe("Payermint Protocol", () => {
         .accountsStrict({
           vaultAccount: vaultAccount,
           member: member1Account,
-          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
         })
         .signers([vaultOwner])
         .rpc();
@@ -491,7 +752,8 @@ describe("Payermint Protocol", () => {
         .accountsStrict({
           vaultAccount: vaultAccount,
           member: member1Account,
-          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
         })
         .signers([vaultOwner])
         .rpc();
@@ -523,7 +785,8 @@ describe("Payermint Protocol", () => {
           .accountsStrict({
             payer: vaultOwner.publicKey,
             vaultAccount: vaultAccount,
-            owner: vaultOwner.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
             member: invalidMemberAccount,
             wallet: invalidMember.publicKey,
             systemProgram: web3.SystemProgram.programId,
@@ -547,6 +810,7 @@ describe("Payermint Protocol", () => {
         .depositSol(DEPOSIT_AMOUNT)
         .accountsStrict({
           vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
           depositor: vaultOwner.publicKey,
           systemProgram: web3.SystemProgram.programId,
         })
@@ -635,6 +899,7 @@ describe("Payermint Protocol", () => {
         .depositSplToken(TOKEN_DEPOSIT_AMOUNT)
         .accountsStrict({
           vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
           depositor: vaultOwner.publicKey,
           mint: testMint,
           depositorTokenAccount: depositorTokenAccount,
@@ -675,12 +940,38 @@ describe("Payermint Protocol", () => {
       const vault = await program.account.vaultAccount.fetch(vaultAccount);
       expect(vault.lastDepositTs.toNumber()).to.be.greaterThan(0);
     });
+
+    it("Should refuse to remove a whitelisted mint the vault still holds", async () => {
+      try {
+        await program.methods
+          .removeWhitelistedAsset({ splToken: { mint: testMint } })
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            owner: vaultOwner.publicKey,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("AssetHasBalance");
+      }
+    });
   });
 
   describe("Payroll Batch Processing", () => {
     const batchId = new BN(1);
+    const splBatchId = new BN(107);
+    let splBatch: web3.PublicKey;
 
     before(async () => {
+      [splBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          splBatchId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
       [payrollBatch] = web3.PublicKey.findProgramAddressSync(
         [
           Buffer.from("batch"),
@@ -701,7 +992,8 @@ describe("Payermint Protocol", () => {
           vaultAccount: vaultAccount,
           globalConfig: globalConfig,
           payrollBatch: payrollBatch,
-          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
           systemProgram: web3.SystemProgram.programId,
         })
         .signers([vaultOwner])
@@ -754,13 +1046,17 @@ describe("Payermint Protocol", () => {
       await program.methods
         .processSolPayout(payoutAmount)
         .accountsStrict({
+          payer: vaultOwner.publicKey,
           vaultAccount: vaultAccount,
           payrollBatch: payrollBatch,
+          approvalConfig: approvalConfigFor(vaultAccount),
           member: member1Account,
+          receipt: receiptFor(payrollBatch, member1Account),
           globalConfig: globalConfig,
           treasury: treasury.publicKey,
           memberWallet: member1.publicKey,
-          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
           systemProgram: web3.SystemProgram.programId,
         })
         .signers([vaultOwner])
@@ -811,2127 +1107,5485 @@ describe("Payermint Protocol", () => {
       );
     });
 
-    it("Should finalize payroll batch", async () => {
-      await program.methods
-        .finalizePayrollBatch()
-        .accountsStrict({
-          vaultAccount: vaultAccount,
-          payrollBatch: payrollBatch,
-          owner: vaultOwner.publicKey,
-        })
-        .signers([vaultOwner])
-        .rpc();
-
-      const batch = await program.account.payrollBatch.fetch(payrollBatch);
-      expect(batch.finalized).to.be.true;
-    });
-
-    it("Should fail to process payout on finalized batch", async () => {
-      const member2Account = web3.PublicKey.findProgramAddressSync(
+    it("Should record a receipt and block a second payout in the batch", async () => {
+      const member1Account = web3.PublicKey.findProgramAddressSync(
         [
           Buffer.from("member"),
           vaultAccount.toBuffer(),
-          member2.publicKey.toBuffer(),
+          member1.publicKey.toBuffer(),
         ],
         program.programId
       )[0];
+      const receipt = receiptFor(payrollBatch, member1Account);
+
+      const receiptData = await program.account.payoutReceipt.fetch(receipt);
+      expect(receiptData.member.toString()).to.equal(member1Account.toString());
+      expect(receiptData.cycle.batch.batchId.toString()).to.equal(
+        batchId.toString()
+      );
+      expect(receiptData.amount.toString()).to.equal(
+        new BN(2 * web3.LAMPORTS_PER_SOL).toString()
+      );
 
       try {
         await program.methods
           .processSolPayout(new BN(web3.LAMPORTS_PER_SOL))
           .accountsStrict({
+            payer: vaultOwner.publicKey,
             vaultAccount: vaultAccount,
             payrollBatch: payrollBatch,
-            member: member2Account,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            member: member1Account,
+            receipt: receipt,
             globalConfig: globalConfig,
             treasury: treasury.publicKey,
-            memberWallet: member2.publicKey,
-            owner: vaultOwner.publicKey,
+            memberWallet: member1.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
             systemProgram: web3.SystemProgram.programId,
           })
           .signers([vaultOwner])
           .rpc();
         expect.fail("Should have thrown error");
       } catch (error) {
-        expect(error.message).to.include("BatchAlreadyFinalized");
+        expect(error.message).to.include("already in use");
       }
     });
-  });
-
-  describe("Scheduled Payouts", () => {
-    it("Should process scheduled payout", async () => {
-      // First, set up a payout schedule that's ready to trigger
-      const currentTime = Math.floor(Date.now() / 1000);
-      const payoutSchedule = {
-        interval: new BN(86400 * 7), // 1 week
-        nextPayoutTs: new BN(currentTime - 3600), // 1 hour ago (ready to trigger)
-        active: true,
-      };
-
-      await program.methods
-        .updatePayoutSchedule(payoutSchedule)
-        .accountsStrict({
-          vaultAccount: vaultAccount,
-          owner: vaultOwner.publicKey,
-        })
-        .signers([vaultOwner])
-        .rpc();
 
-      // Process scheduled payout for member 2
-      const member2Account = web3.PublicKey.findProgramAddressSync(
+    it("Should process SPL payout", async () => {
+      const payoutAmount = new BN(100 * 10 ** 6); // 100 tokens
+      // Member 1 already holds a receipt for the SOL batch, so pay member 3
+      const member3Account = web3.PublicKey.findProgramAddressSync(
         [
           Buffer.from("member"),
           vaultAccount.toBuffer(),
-          member2.publicKey.toBuffer(),
+          member3.publicKey.toBuffer(),
         ],
         program.programId
       )[0];
 
-      const initialMemberBalance = await provider.connection.getBalance(
-        member2.publicKey
+      const vaultTokenAccount = await getAssociatedTokenAddress(
+        testMint,
+        vaultAccount,
+        true
+      );
+      const treasuryTokenAccount = await getAssociatedTokenAddress(
+        testMint,
+        treasury.publicKey
+      );
+      const memberTokenAccount = await getAssociatedTokenAddress(
+        testMint,
+        member3.publicKey
       );
 
+      const initialVaultTokens = (
+        await getAccount(provider.connection, vaultTokenAccount)
+      ).amount;
+
+      const serviceFee = payoutAmount
+        .mul(new BN(DEFAULT_FEE_BPS))
+        .div(new BN(10000));
+      const netAmount = payoutAmount.sub(serviceFee);
+
+      const splPayout = (batch: web3.PublicKey) =>
+        program.methods
+          .processSplPayout(payoutAmount)
+          .accountsStrict({
+            payer: vaultOwner.publicKey,
+            vaultAccount: vaultAccount,
+            payrollBatch: batch,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            member: member3Account,
+            receipt: receiptFor(batch, member3Account),
+            globalConfig: globalConfig,
+            mint: testMint,
+            vaultTokenAccount: vaultTokenAccount,
+            treasury: treasury.publicKey,
+            treasuryTokenAccount: treasuryTokenAccount,
+            memberWallet: member3.publicKey,
+            memberTokenAccount: memberTokenAccount,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([vaultOwner])
+          .rpc();
+
+      // The batch already paid SOL, so its cap and fees are in lamports
+      try {
+        await splPayout(payrollBatch);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("BatchAssetMismatch");
+      }
+
       await program.methods
-        .processScheduledPayout()
+        .createPayrollBatch(splBatchId, payoutAmount)
         .accountsStrict({
+          payer: vaultOwner.publicKey,
           vaultAccount: vaultAccount,
-          member: member2Account,
           globalConfig: globalConfig,
-          treasury: treasury.publicKey,
-          memberWallet: member2.publicKey,
-          owner: vaultOwner.publicKey,
+          payrollBatch: splBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
           systemProgram: web3.SystemProgram.programId,
         })
         .signers([vaultOwner])
         .rpc();
 
-      // Verify payout occurred
-      const finalMemberBalance = await provider.connection.getBalance(
-        member2.publicKey
-      );
-      expect(finalMemberBalance).to.be.greaterThan(initialMemberBalance);
+      // Member and treasury token accounts are created on demand
+      await splPayout(splBatch);
 
-      // Verify next payout timestamp was updated
-      const vault = await program.account.vaultAccount.fetch(vaultAccount);
-      const expectedNextPayout = payoutSchedule.nextPayoutTs.add(
-        payoutSchedule.interval
-      );
-      expect(vault.payoutSchedule.nextPayoutTs.toString()).to.equal(
-        expectedNextPayout.toString()
+      const memberTokens = await getAccount(
+        provider.connection,
+        memberTokenAccount
       );
-    });
-  });
+      expect(memberTokens.amount.toString()).to.equal(netAmount.toString());
 
-  describe("Bulk Operations", () => {
-    it("Should validate bulk add members data", async () => {
-      const membersData = [
-        {
-          wallet: web3.Keypair.generate().publicKey,
-          role: "Developer",
-          allocationBps: 2000,
-          solPaymentAllocation: null,
-          splTokenAllocation: null,
-          metadataUri: null,
-        },
-        {
-          wallet: web3.Keypair.generate().publicKey,
-          role: "Designer",
-          allocationBps: 3000,
-          solPaymentAllocation: null,
-          splTokenAllocation: null,
-          metadataUri: null,
-        },
-      ];
+      const treasuryTokens = await getAccount(
+        provider.connection,
+        treasuryTokenAccount
+      );
+      expect(treasuryTokens.amount.toString()).to.equal(serviceFee.toString());
 
-      // This validates data but doesn't actually create accounts
-      await program.methods
-        .bulkAddMembers(membersData)
-        .accountsStrict({
-          payer: vaultOwner.publicKey,
-          vaultAccount: vaultAccount,
-          owner: vaultOwner.publicKey,
-          systemProgram: web3.SystemProgram.programId,
-        })
-        .signers([vaultOwner])
-        .rpc();
+      const vaultTokens = await getAccount(
+        provider.connection,
+        vaultTokenAccount
+      );
+      expect(vaultTokens.amount.toString()).to.equal(
+        new BN(initialVaultTokens.toString()).sub(payoutAmount).toString()
+      );
 
-      // If we reach here, validation passed
-      expect(true).to.be.true;
+      const batch = await program.account.payrollBatch.fetch(splBatch);
+      expect(batch.payoutCount).to.equal(1);
+      expect(batch.assetType.splToken.mint.toString()).to.equal(
+        testMint.toString()
+      );
     });
 
-    it("Should fail bulk add with excessive total allocation", async () => {
-      const membersData = [
-        {
-          wallet: web3.Keypair.generate().publicKey,
-          role: "Developer",
-          allocationBps: 6000,
-          solPaymentAllocation: null,
-          splTokenAllocation: null,
-          metadataUri: null,
-        },
-        {
-          wallet: web3.Keypair.generate().publicKey,
-          role: "Designer",
-          allocationBps: 5000, // Total would be 11000 > 10000
-          solPaymentAllocation: null,
-          splTokenAllocation: null,
-          metadataUri: null,
-        },
-      ];
+    it("Should reject payouts beyond the batch total", async () => {
+      const member2Account = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          member2.publicKey.toBuffer(),
+        ],
+        program.programId
+      )[0];
+
+      const batch = await program.account.payrollBatch.fetch(payrollBatch);
+      const overCap = batch.totalAmount.sub(batch.paidAmount).add(new BN(1));
 
       try {
         await program.methods
-          .bulkAddMembers(membersData)
+          .processSolPayout(overCap)
           .accountsStrict({
             payer: vaultOwner.publicKey,
             vaultAccount: vaultAccount,
-            owner: vaultOwner.publicKey,
+            payrollBatch: payrollBatch,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            member: member2Account,
+            receipt: receiptFor(payrollBatch, member2Account),
+            globalConfig: globalConfig,
+            treasury: treasury.publicKey,
+            memberWallet: member2.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
             systemProgram: web3.SystemProgram.programId,
           })
           .signers([vaultOwner])
           .rpc();
         expect.fail("Should have thrown error");
       } catch (error) {
-        expect(error.message).to.include("TotalAllocationExceeded");
+        expect(error.message).to.include("BatchCapExceeded");
       }
     });
 
-    it("Should validate bulk process payouts", async () => {
-      const payoutData = [
-        {
-          member: member1.publicKey,
-          amount: new BN(web3.LAMPORTS_PER_SOL),
-          assetType: { sol: {} },
-        },
-        {
-          member: member2.publicKey,
-          amount: new BN(2 * web3.LAMPORTS_PER_SOL),
-          assetType: { sol: {} },
-        },
-      ];
-
-      // Create a new batch for bulk processing
-      const bulkBatchId = new BN(2);
-      const [bulkPayrollBatch] = web3.PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("batch"),
-          vaultAccount.toBuffer(),
-          bulkBatchId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId
-      );
-
-      await program.methods
-        .createPayrollBatch(bulkBatchId, new BN(3 * web3.LAMPORTS_PER_SOL))
-        .accountsStrict({
-          payer: vaultOwner.publicKey,
-          vaultAccount: vaultAccount,
-          globalConfig: globalConfig,
-          payrollBatch: bulkPayrollBatch,
-          owner: vaultOwner.publicKey,
-          systemProgram: web3.SystemProgram.programId,
-        })
-        .signers([vaultOwner])
-        .rpc();
-
+    it("Should finalize payroll batch", async () => {
       await program.methods
-        .bulkProcessPayouts(payoutData)
+        .finalizePayrollBatch()
         .accountsStrict({
           vaultAccount: vaultAccount,
-          payrollBatch: bulkPayrollBatch,
-          globalConfig: globalConfig,
-          treasury: treasury.publicKey,
-          owner: vaultOwner.publicKey,
-          systemProgram: web3.SystemProgram.programId,
+          payrollBatch: payrollBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
         })
         .signers([vaultOwner])
         .rpc();
 
-      const batch = await program.account.payrollBatch.fetch(bulkPayrollBatch);
-      expect(batch.payoutCount).to.equal(payoutData.length);
+      const batch = await program.account.payrollBatch.fetch(payrollBatch);
+      expect(batch.finalized).to.be.true;
+      // The creation-time estimate is kept next to the fees actually charged
+      expect(batch.serviceFee.toString()).to.equal(
+        batch.totalAmount
+          .mul(new BN(DEFAULT_FEE_BPS))
+          .div(new BN(10000))
+          .toString()
+      );
+      expect(batch.feesCollected.toString()).to.equal(
+        new BN(2 * web3.LAMPORTS_PER_SOL)
+          .mul(new BN(DEFAULT_FEE_BPS))
+          .div(new BN(10000))
+          .toString()
+      );
+      expect(batch.feeDelta.toString()).to.equal(
+        batch.feesCollected.sub(batch.serviceFee).toString()
+      );
     });
-  });
-
-  describe("Error Cases", () => {
-    it("Should fail unauthorized access", async () => {
-      const unauthorizedUser = web3.Keypair.generate();
 
-      // Fund the unauthorized user
-      await provider.connection.requestAirdrop(
-        unauthorizedUser.publicKey,
-        web3.LAMPORTS_PER_SOL
-      );
-      await new Promise((resolve) => setTimeout(resolve, 1000));
+    it("Should fail to process payout on finalized batch", async () => {
+      const member2Account = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          member2.publicKey.toBuffer(),
+        ],
+        program.programId
+      )[0];
 
       try {
         await program.methods
-          .updatePayoutSchedule(null)
+          .processSolPayout(new BN(web3.LAMPORTS_PER_SOL))
           .accountsStrict({
+            payer: vaultOwner.publicKey,
             vaultAccount: vaultAccount,
-            owner: unauthorizedUser.publicKey,
+            payrollBatch: payrollBatch,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            member: member2Account,
+            receipt: receiptFor(payrollBatch, member2Account),
+            globalConfig: globalConfig,
+            treasury: treasury.publicKey,
+            memberWallet: member2.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+            systemProgram: web3.SystemProgram.programId,
           })
-          .signers([unauthorizedUser])
+          .signers([vaultOwner])
           .rpc();
         expect.fail("Should have thrown error");
       } catch (error) {
-        expect(error.message).to.satisfy(
-          (msg: string) =>
-            msg.includes("has_one") || msg.includes("ConstraintHasOne")
-        );
+        expect(error.message).to.include("BatchAlreadyFinalized");
       }
     });
 
-    it("Should fail with insufficient vault balance", async () => {
-      const member3Account = web3.PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("member"),
-          vaultAccount.toBuffer(),
-          member3.publicKey.toBuffer(),
-        ],
-        program.programId
-      )[0];
-
-      // Create a new batch
-      const insufficientBatchId = new BN(3);
-      const [insufficientBatch] = web3.PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("batch"),
-          vaultAccount.toBuffer(),
-          insufficientBatchId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId
-      );
+    it("Should reject a retention period beyond the maximum", async () => {
+      try {
+        await program.methods
+          .updateBatchRetention(new BN("9223372036854775807"))
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            owner: vaultOwner.publicKey,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidRetentionPeriod");
+      }
+    });
 
+    it("Should keep a finalized batch for the retention period", async () => {
       await program.methods
-        .createPayrollBatch(
-          insufficientBatchId,
-          new BN(100 * web3.LAMPORTS_PER_SOL)
-        )
+        .updateBatchRetention(new BN(3600))
         .accountsStrict({
-          payer: vaultOwner.publicKey,
           vaultAccount: vaultAccount,
-          globalConfig: globalConfig,
-          payrollBatch: insufficientBatch,
           owner: vaultOwner.publicKey,
-          systemProgram: web3.SystemProgram.programId,
         })
         .signers([vaultOwner])
         .rpc();
 
       try {
         await program.methods
-          .processSolPayout(new BN(100 * web3.LAMPORTS_PER_SOL)) // More than vault balance
+          .closePayrollBatch()
           .accountsStrict({
             vaultAccount: vaultAccount,
-            payrollBatch: insufficientBatch,
-            member: member3Account,
-            globalConfig: globalConfig,
-            treasury: treasury.publicKey,
-            memberWallet: member3.publicKey,
-            owner: vaultOwner.publicKey,
-            systemProgram: web3.SystemProgram.programId,
+            payrollBatch: payrollBatch,
+            payer: vaultOwner.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
           })
           .signers([vaultOwner])
           .rpc();
         expect.fail("Should have thrown error");
       } catch (error) {
-        expect(error.message).to.include("InsufficientVaultBalance");
+        expect(error.message).to.include("RetentionPeriodActive");
       }
     });
 
-    it("Should fail payout to inactive member", async () => {
-      // First deactivate member 3
-      const member3Account = web3.PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("member"),
-          vaultAccount.toBuffer(),
-          member3.publicKey.toBuffer(),
-        ],
-        program.programId
-      )[0];
-
+    it("Should close a finalized batch and refund its payer", async () => {
       await program.methods
-        .toggleMemberActiveStatus()
+        .updateBatchRetention(new BN(0))
         .accountsStrict({
           vaultAccount: vaultAccount,
-          member: member3Account,
           owner: vaultOwner.publicKey,
         })
         .signers([vaultOwner])
         .rpc();
 
-      // Create a new batch for this test
-      const inactiveBatchId = new BN(4);
-      const [inactiveBatch] = web3.PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("batch"),
-          vaultAccount.toBuffer(),
-          inactiveBatchId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId
+      const batchRent = await provider.connection.getBalance(payrollBatch);
+      const payerBefore = await provider.connection.getBalance(
+        vaultOwner.publicKey
       );
 
       await program.methods
-        .createPayrollBatch(inactiveBatchId, new BN(web3.LAMPORTS_PER_SOL))
+        .closePayrollBatch()
         .accountsStrict({
-          payer: vaultOwner.publicKey,
           vaultAccount: vaultAccount,
-          globalConfig: globalConfig,
-          payrollBatch: inactiveBatch,
-          owner: vaultOwner.publicKey,
-          systemProgram: web3.SystemProgram.programId,
+          payrollBatch: payrollBatch,
+          payer: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
         })
         .signers([vaultOwner])
         .rpc();
 
-      try {
-        await program.methods
-          .processSolPayout(new BN(web3.LAMPORTS_PER_SOL))
-          .accountsStrict({
-            vaultAccount: vaultAccount,
-            payrollBatch: inactiveBatch,
-            member: member3Account,
-            globalConfig: globalConfig,
-            treasury: treasury.publicKey,
-            memberWallet: member3.publicKey,
-            owner: vaultOwner.publicKey,
-            systemProgram: web3.SystemProgram.programId,
-          })
-          .signers([vaultOwner])
-          .rpc();
-        expect.fail("Should have thrown error");
-      } catch (error) {
-        expect(error.message).to.include("MemberNotActive");
-      }
-    });
-  });
-
-  describe("Member Removal and Payroll Exclusion", () => {
-    let removedMemberKeypair: web3.Keypair;
-    let removedMemberAccount: web3.PublicKey;
-    let activeMemberKeypair: web3.Keypair;
-    let activeMemberAccount: web3.PublicKey;
-
-    before(async () => {
-      // Create test members for this specific test
-      removedMemberKeypair = web3.Keypair.generate();
-      activeMemberKeypair = web3.Keypair.generate();
-
-      // Fund the test member accounts
-      await Promise.all([
-        provider.connection.requestAirdrop(
-          removedMemberKeypair.publicKey,
-          2 * web3.LAMPORTS_PER_SOL
-        ),
-        provider.connection.requestAirdrop(
-          activeMemberKeypair.publicKey,
-          2 * web3.LAMPORTS_PER_SOL
-        ),
-      ]);
-
-      // Wait for confirmations
-      await new Promise((resolve) => setTimeout(resolve, 2000));
+      const batchInfo = await provider.connection.getAccountInfo(payrollBatch);
+      expect(batchInfo).to.be.null;
 
-      // Derive member PDAs
-      [removedMemberAccount] = web3.PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("member"),
-          vaultAccount.toBuffer(),
-          removedMemberKeypair.publicKey.toBuffer(),
-        ],
-        program.programId
+      // The payer also signs, so it pays the transaction fee
+      const payerAfter = await provider.connection.getBalance(
+        vaultOwner.publicKey
       );
+      expect(payerAfter).to.be.greaterThan(payerBefore + batchRent - 10000);
+    });
 
-      [activeMemberAccount] = web3.PublicKey.findProgramAddressSync(
+    it("Should close a receipt once its batch is closed", async () => {
+      const member1Account = web3.PublicKey.findProgramAddressSync(
         [
           Buffer.from("member"),
           vaultAccount.toBuffer(),
-          activeMemberKeypair.publicKey.toBuffer(),
+          member1.publicKey.toBuffer(),
         ],
         program.programId
+      )[0];
+      const receipt = receiptFor(payrollBatch, member1Account);
+      const receiptData = await program.account.payoutReceipt.fetch(receipt);
+      expect(receiptData.payer.toString()).to.equal(
+        vaultOwner.publicKey.toString()
       );
 
-      // Add both members to the vault (roles must be <= 16 characters)
       await program.methods
-        .addMember(
-          "ToBeRemoved", // 11 chars - within 16 char limit
-          2000, // 20% allocation
-          null,
-          null,
-          "https://example.com/removed-member.json"
-        )
+        .closeReceipt()
         .accountsStrict({
-          payer: vaultOwner.publicKey,
+          receipt,
           vaultAccount: vaultAccount,
-          owner: vaultOwner.publicKey,
-          member: removedMemberAccount,
-          wallet: removedMemberKeypair.publicKey,
-          systemProgram: web3.SystemProgram.programId,
-        })
-        .signers([vaultOwner])
-        .rpc();
-
-      await program.methods
-        .addMember(
-          "ActiveMember", // 12 chars - within 16 char limit
-          3000, // 30% allocation
-          null,
-          null,
-          "https://example.com/active-member.json"
-        )
-        .accountsStrict({
+          payrollBatch: payrollBatch,
           payer: vaultOwner.publicKey,
-          vaultAccount: vaultAccount,
-          owner: vaultOwner.publicKey,
-          member: activeMemberAccount,
-          wallet: activeMemberKeypair.publicKey,
-          systemProgram: web3.SystemProgram.programId,
         })
         .signers([vaultOwner])
         .rpc();
 
-      console.log("✅ Test members added successfully");
-    });
-
-    it("Should verify members exist before removal", async () => {
-      // Verify both members exist and are active
-      const removedMemberData = await program.account.member.fetch(
-        removedMemberAccount
-      );
-      const activeMemberData = await program.account.member.fetch(
-        activeMemberAccount
-      );
-
-      expect(removedMemberData.isActive).to.be.true;
-      expect(removedMemberData.allocationBps).to.equal(2000);
-      expect(activeMemberData.isActive).to.be.true;
-      expect(activeMemberData.allocationBps).to.equal(3000);
-
-      console.log("✅ Both members verified as active before removal");
+      expect(await provider.connection.getAccountInfo(receipt)).to.be.null;
     });
+  });
 
-    it("Should successfully process payroll for members before removal", async () => {
-      // Create a new batch for pre-removal testing
-      const preRemovalBatchId = new BN(100);
-      const [preRemovalBatch] = web3.PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("batch"),
-          vaultAccount.toBuffer(),
-          preRemovalBatchId.toArrayLike(Buffer, "le", 8),
-        ],
+  describe("Scheduled Payouts", () => {
+    const memberFor = (wallet: web3.PublicKey) =>
+      web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("member"), vaultAccount.toBuffer(), wallet.toBuffer()],
         program.programId
-      );
+      )[0];
 
-      await program.methods
-        .createPayrollBatch(
-          preRemovalBatchId,
-          new BN(3 * web3.LAMPORTS_PER_SOL)
-        )
+    const crank = (period: BN, wallet: web3.PublicKey) =>
+      program.methods
+        .processScheduledPayout(period)
         .accountsStrict({
-          payer: vaultOwner.publicKey,
           vaultAccount: vaultAccount,
+          member: memberFor(wallet),
+          receipt: periodReceiptFor(vaultAccount, period, memberFor(wallet)),
           globalConfig: globalConfig,
-          payrollBatch: preRemovalBatch,
-          owner: vaultOwner.publicKey,
+          treasury: treasury.publicKey,
+          memberWallet: wallet,
+          keeper: vaultOwner.publicKey,
           systemProgram: web3.SystemProgram.programId,
         })
         .signers([vaultOwner])
         .rpc();
 
-      // Process payout for the member that will be removed
-      const payoutAmount = new BN(web3.LAMPORTS_PER_SOL);
-      const initialRemovedMemberBalance = await provider.connection.getBalance(
-        removedMemberKeypair.publicKey
-      );
+    const toggleActive = (wallet: web3.PublicKey) =>
+      program.methods
+        .toggleMemberActiveStatus()
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          member: memberFor(wallet),
+          authority: vaultOwner.publicKey,
+          delegate: null,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+    it("Should process scheduled payout", async () => {
+      // First, set up a payout schedule that's ready to trigger
+      const currentTime = Math.floor(Date.now() / 1000);
+      const payoutSchedule = {
+        interval: new BN(86400 * 7), // 1 week
+        nextPayoutTs: new BN(currentTime - 3600), // 1 hour ago (ready to trigger)
+        active: true,
+      };
 
       await program.methods
-        .processSolPayout(payoutAmount)
+        .updatePayoutSchedule(payoutSchedule)
         .accountsStrict({
           vaultAccount: vaultAccount,
-          payrollBatch: preRemovalBatch,
-          member: removedMemberAccount,
-          globalConfig: globalConfig,
-          treasury: treasury.publicKey,
-          memberWallet: removedMemberKeypair.publicKey,
-          owner: vaultOwner.publicKey,
-          systemProgram: web3.SystemProgram.programId,
+          authority: vaultOwner.publicKey,
+          delegate: null,
         })
         .signers([vaultOwner])
         .rpc();
 
-      // Verify the payout was successful
-      const finalRemovedMemberBalance = await provider.connection.getBalance(
-        removedMemberKeypair.publicKey
-      );
-      expect(finalRemovedMemberBalance).to.be.greaterThan(
-        initialRemovedMemberBalance
+      // Process scheduled payout for member 2
+      const member2Account = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          member2.publicKey.toBuffer(),
+        ],
+        program.programId
+      )[0];
+
+      // Any keeper may crank a due payout and earns the vault's keeper reward
+      const keeper = web3.Keypair.generate();
+      await provider.connection.requestAirdrop(
+        keeper.publicKey,
+        web3.LAMPORTS_PER_SOL
       );
+      await new Promise((resolve) => setTimeout(resolve, 1000));
 
-      // Finalize the batch
+      const keeperReward = new BN(20000);
       await program.methods
-        .finalizePayrollBatch()
+        .updateKeeperReward(keeperReward)
         .accountsStrict({
           vaultAccount: vaultAccount,
-          payrollBatch: preRemovalBatch,
+          approvalConfig: approvalConfigFor(vaultAccount),
           owner: vaultOwner.publicKey,
         })
         .signers([vaultOwner])
         .rpc();
 
-      console.log(
-        "✅ Pre-removal payroll processed successfully for both members"
+      const initialMemberBalance = await provider.connection.getBalance(
+        member2.publicKey
       );
-    });
-
-    it("Should remove the member", async () => {
-      // Record initial balance before removal
-      const initialRemovedMemberBalance = await provider.connection.getBalance(
-        removedMemberKeypair.publicKey
+      const initialKeeperBalance = await provider.connection.getBalance(
+        keeper.publicKey
       );
 
+      const period = payoutSchedule.nextPayoutTs;
       await program.methods
-        .removeMember()
+        .processScheduledPayout(period)
         .accountsStrict({
           vaultAccount: vaultAccount,
-          member: removedMemberAccount,
-          owner: vaultOwner.publicKey,
+          member: member2Account,
+          receipt: periodReceiptFor(vaultAccount, period, member2Account),
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          memberWallet: member2.publicKey,
+          keeper: keeper.publicKey,
+          systemProgram: web3.SystemProgram.programId,
         })
-        .signers([vaultOwner])
+        .signers([keeper])
         .rpc();
 
-      console.log("✅ Member removed successfully");
+      // Verify payout occurred
+      const finalMemberBalance = await provider.connection.getBalance(
+        member2.publicKey
+      );
+      expect(finalMemberBalance).to.be.greaterThan(initialMemberBalance);
 
-      // Verify member account was closed and SOL rent was reclaimed
-      try {
-        await program.account.member.fetch(removedMemberAccount);
-        expect.fail("Member account should have been closed");
-      } catch (error) {
-        expect(error.message).to.include("Account does not exist");
-        console.log("✅ Member account confirmed as closed");
-      }
+      // Keeper reward covers more than the transaction fee
+      const finalKeeperBalance = await provider.connection.getBalance(
+        keeper.publicKey
+      );
+      expect(finalKeeperBalance).to.be.greaterThan(initialKeeperBalance);
 
-      // Verify rent was reclaimed (account closure should return rent to vault owner)
-      const finalRemovedMemberBalance = await provider.connection.getBalance(
-        removedMemberKeypair.publicKey
+      // The period stays open until the next one begins
+      const vault = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(vault.periodPaidCount).to.equal(1);
+      expect(vault.payoutSchedule.nextPayoutTs.toString()).to.equal(
+        payoutSchedule.nextPayoutTs.toString()
       );
 
-      // Note: The removed member's wallet balance shouldn't change from the removal
-      // The rent is reclaimed by the vault owner, not the removed member
-      console.log("Initial balance:", initialRemovedMemberBalance);
-      console.log("Final balance:", finalRemovedMemberBalance);
+      const receipt = await program.account.payoutReceipt.fetch(
+        periodReceiptFor(vaultAccount, period, member2Account)
+      );
+      expect(receipt.amount.toNumber()).to.be.greaterThan(0);
     });
 
-    it("Should fail to process payroll for removed member", async () => {
-      // Create a new batch for post-removal testing
-      const postRemovalBatchId = new BN(101);
-      const [postRemovalBatch] = web3.PublicKey.findProgramAddressSync(
+    it("Should not pay the same member twice in one period", async () => {
+      const member2Account = web3.PublicKey.findProgramAddressSync(
         [
-          Buffer.from("batch"),
+          Buffer.from("member"),
           vaultAccount.toBuffer(),
-          postRemovalBatchId.toArrayLike(Buffer, "le", 8),
+          member2.publicKey.toBuffer(),
         ],
         program.programId
-      );
+      )[0];
 
-      await program.methods
-        .createPayrollBatch(
-          postRemovalBatchId,
-          new BN(2 * web3.LAMPORTS_PER_SOL)
-        )
-        .accountsStrict({
-          payer: vaultOwner.publicKey,
-          vaultAccount: vaultAccount,
-          globalConfig: globalConfig,
-          payrollBatch: postRemovalBatch,
-          owner: vaultOwner.publicKey,
-          systemProgram: web3.SystemProgram.programId,
-        })
-        .signers([vaultOwner])
-        .rpc();
+      const period = currentPeriod(
+        await program.account.vaultAccount.fetch(vaultAccount)
+      );
 
-      // Attempt to process payout for the removed member - this should fail
+      // The period receipt already exists, so its creation fails
       try {
         await program.methods
-          .processSolPayout(new BN(web3.LAMPORTS_PER_SOL))
+          .processScheduledPayout(period)
           .accountsStrict({
             vaultAccount: vaultAccount,
-            payrollBatch: postRemovalBatch,
-            member: removedMemberAccount, // This account no longer exists
+            member: member2Account,
+            receipt: periodReceiptFor(vaultAccount, period, member2Account),
             globalConfig: globalConfig,
             treasury: treasury.publicKey,
-            memberWallet: removedMemberKeypair.publicKey,
-            owner: vaultOwner.publicKey,
+            memberWallet: member2.publicKey,
+            keeper: vaultOwner.publicKey,
             systemProgram: web3.SystemProgram.programId,
           })
           .signers([vaultOwner])
           .rpc();
-        expect.fail("Should have thrown error for removed member");
+        expect.fail("Should have thrown error");
       } catch (error) {
-        // Log the full error to see what we're actually getting
-        console.log("Full error message:", error.message);
-        console.log("Error name:", error.name);
-        console.log("Error constructor:", error.constructor.name);
+        expect(error.message).to.include("already in use");
+      }
+    });
 
-        // More flexible error checking - any error is acceptable since the account is gone
-        expect(error).to.exist;
-        expect(error.message).to.be.a("string");
-        expect(error.message.length).to.be.greaterThan(0);
+    it("Should keep the period open after a paid member is deactivated", async () => {
+      const vaultBefore = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+      const period = vaultBefore.payoutSchedule.nextPayoutTs;
 
-        console.log(
-          "✅ Payroll correctly failed for removed member (any error is expected)"
-        );
-      }
+      // member2 was paid for this period; once deactivated, paying member3
+      // matches the active member count but member1 is still owed
+      await toggleActive(member2.publicKey);
+      await crank(period, member3.publicKey);
 
-      // Verify active member can still receive payroll
-      const initialActiveMemberBalance = await provider.connection.getBalance(
-        activeMemberKeypair.publicKey
+      const vault = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(vault.activeMemberCount).to.equal(vault.periodPaidCount);
+      expect(vault.payoutSchedule.nextPayoutTs.toString()).to.equal(
+        period.toString()
+      );
+
+      await toggleActive(member2.publicKey);
+    });
+
+    it("Should keep a receipt while its period is open", async () => {
+      const { payoutSchedule } = await program.account.vaultAccount.fetch(
+        vaultAccount
       );
+      try {
+        await program.methods
+          .closeReceipt()
+          .accountsStrict({
+            receipt: periodReceiptFor(
+              vaultAccount,
+              payoutSchedule.nextPayoutTs,
+              memberFor(member3.publicKey)
+            ),
+            vaultAccount: vaultAccount,
+            payrollBatch: null,
+            payer: vaultOwner.publicKey,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("PayoutCycleOpen");
+      }
+    });
 
+    it("Should skip an active member without an allocation", async () => {
+      const unallocated = web3.Keypair.generate();
       await program.methods
-        .processSolPayout(new BN(web3.LAMPORTS_PER_SOL))
+        .addMember("Intern", null, null, null, null)
         .accountsStrict({
+          payer: vaultOwner.publicKey,
           vaultAccount: vaultAccount,
-          payrollBatch: postRemovalBatch,
-          member: activeMemberAccount,
-          globalConfig: globalConfig,
-          treasury: treasury.publicKey,
-          memberWallet: activeMemberKeypair.publicKey,
-          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          member: memberFor(unallocated.publicKey),
+          wallet: unallocated.publicKey,
           systemProgram: web3.SystemProgram.programId,
         })
         .signers([vaultOwner])
         .rpc();
 
-      const finalActiveMemberBalance = await provider.connection.getBalance(
-        activeMemberKeypair.publicKey
-      );
-      expect(finalActiveMemberBalance).to.be.greaterThan(
-        initialActiveMemberBalance
+      const vaultBefore = await program.account.vaultAccount.fetch(
+        vaultAccount
       );
+      const period = vaultBefore.payoutSchedule.nextPayoutTs;
+      await crank(period, unallocated.publicKey);
 
-      console.log(
-        "✅ Active member can still receive payroll after other member removal"
+      // Nothing is paid, but the member no longer blocks the period
+      const receipt = await program.account.payoutReceipt.fetch(
+        periodReceiptFor(vaultAccount, period, memberFor(unallocated.publicKey))
       );
-    });
+      expect(receipt.amount.toNumber()).to.equal(0);
+      expect(
+        await provider.connection.getBalance(unallocated.publicKey)
+      ).to.equal(0);
 
-    it("Should fail scheduled payouts for removed member", async () => {
-      // Set up a scheduled payout
-      const currentTime = Math.floor(Date.now() / 1000);
-      const payoutSchedule = {
-        interval: new BN(3600), // 1 hour
-        nextPayoutTs: new BN(currentTime - 1800), // 30 minutes ago (ready to trigger)
-        active: true,
-      };
+      const vaultAfter = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+      expect(vaultAfter.totalBalance.toString()).to.equal(
+        vaultBefore.totalBalance.toString()
+      );
 
       await program.methods
-        .updatePayoutSchedule(payoutSchedule)
+        .removeMember()
         .accountsStrict({
           vaultAccount: vaultAccount,
+          member: memberFor(unallocated.publicKey),
           owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
         })
         .signers([vaultOwner])
         .rpc();
+    });
 
-      // Attempt scheduled payout for removed member - should fail
-      try {
-        await program.methods
-          .processScheduledPayout()
-          .accountsStrict({
-            vaultAccount: vaultAccount,
-            member: removedMemberAccount, // This account no longer exists
-            globalConfig: globalConfig,
-            treasury: treasury.publicKey,
-            memberWallet: removedMemberKeypair.publicKey,
-            owner: vaultOwner.publicKey,
-            systemProgram: web3.SystemProgram.programId,
-          })
-          .signers([vaultOwner])
-          .rpc();
-        expect.fail(
-          "Should have thrown error for removed member in scheduled payout"
-        );
-      } catch (error) {
-        // Log the full error to see what we're actually getting
-        console.log("Full scheduled payout error message:", error.message);
-        console.log("Error name:", error.name);
-        console.log("Error constructor:", error.constructor.name);
-
-        // More flexible error checking - any error is acceptable since the account is gone
-        expect(error).to.exist;
-        expect(error.message).to.be.a("string");
-        expect(error.message.length).to.be.greaterThan(0);
-
-        console.log(
-          "✅ Scheduled payout correctly failed for removed member (any error is expected)"
-        );
-      }
-
-      // Verify scheduled payout works for active member
-      const initialActiveMemberBalance = await provider.connection.getBalance(
-        activeMemberKeypair.publicKey
+    it("Should keep the paid count when the schedule is updated mid-period", async () => {
+      const vaultBefore = await program.account.vaultAccount.fetch(
+        vaultAccount
       );
+      const schedule = vaultBefore.payoutSchedule;
 
       await program.methods
-        .processScheduledPayout()
+        .updatePayoutSchedule({ ...schedule, interval: new BN(86400 * 14) })
         .accountsStrict({
           vaultAccount: vaultAccount,
-          member: activeMemberAccount,
-          globalConfig: globalConfig,
-          treasury: treasury.publicKey,
-          memberWallet: activeMemberKeypair.publicKey,
-          owner: vaultOwner.publicKey,
-          systemProgram: web3.SystemProgram.programId,
+          authority: vaultOwner.publicKey,
+          delegate: null,
         })
         .signers([vaultOwner])
         .rpc();
 
-      const finalActiveMemberBalance = await provider.connection.getBalance(
-        activeMemberKeypair.publicKey
+      const vaultAfter = await program.account.vaultAccount.fetch(
+        vaultAccount
       );
-      expect(finalActiveMemberBalance).to.be.greaterThan(
-        initialActiveMemberBalance
+      expect(vaultAfter.periodPaidCount).to.equal(vaultBefore.periodPaidCount);
+    });
+
+    it("Should not reopen a period that was already paid", async () => {
+      const vaultBefore = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+      const schedule = vaultBefore.payoutSchedule;
+      expect(vaultBefore.paidThrough.toNumber()).to.be.greaterThan(
+        schedule.nextPayoutTs.toNumber()
       );
 
-      console.log("✅ Scheduled payout works correctly for active members");
+      const rejects = async (update: object) => {
+        try {
+          await program.methods
+            .updatePayoutSchedule(update)
+            .accountsStrict({
+              vaultAccount: vaultAccount,
+              authority: vaultOwner.publicKey,
+              delegate: null,
+            })
+            .signers([vaultOwner])
+            .rpc();
+          expect.fail("Should have thrown error");
+        } catch (error) {
+          expect(error.message).to.include("PeriodAlreadyPaid");
+        }
+      };
+
+      // Restarting the schedule inside the paid period
+      await rejects({
+        interval: new BN(3600),
+        nextPayoutTs: new BN(Math.floor(Date.now() / 1000) - 2 * 3600 - 600),
+        active: true,
+      });
+      // Shortening the paid period so the next one opens right away
+      await rejects({ ...schedule, interval: new BN(60) });
+
+      // A period from before the current one can no longer be paid
+      try {
+        await crank(
+          schedule.nextPayoutTs.sub(schedule.interval),
+          member1.publicKey
+        );
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidPayoutPeriod");
+      }
+
+      const vaultAfter = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+      expect(vaultAfter.periodPaidCount).to.equal(vaultBefore.periodPaidCount);
+      expect(vaultAfter.payoutSchedule.nextPayoutTs.toString()).to.equal(
+        schedule.nextPayoutTs.toString()
+      );
     });
+  });
 
-    it("Should fail bulk operations that include removed member", async () => {
-      // Attempt bulk payout that includes the removed member
-      const payoutData = [
+  describe("Bulk Operations", () => {
+    it("Should bulk add members", async () => {
+      const membersData = [
         {
-          member: removedMemberKeypair.publicKey, // Removed member
-          amount: new BN(web3.LAMPORTS_PER_SOL),
-          assetType: { sol: {} },
+          wallet: web3.Keypair.generate().publicKey,
+          role: "Developer",
+          allocationBps: 2000,
+          solPaymentAllocation: null,
+          splTokenAllocation: null,
+          metadataUri: null,
         },
         {
-          member: activeMemberKeypair.publicKey, // Active member
-          amount: new BN(web3.LAMPORTS_PER_SOL),
-          assetType: { sol: {} },
+          wallet: web3.Keypair.generate().publicKey,
+          role: "Designer",
+          allocationBps: 3000,
+          solPaymentAllocation: null,
+          splTokenAllocation: null,
+          metadataUri: null,
         },
       ];
 
-      const bulkBatchId = new BN(102);
-      const [bulkBatch] = web3.PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("batch"),
-          vaultAccount.toBuffer(),
-          bulkBatchId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId
+      // One uninitialized member PDA per entry, in order
+      const memberAccounts = membersData.map(
+        (m) =>
+          web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("member"), vaultAccount.toBuffer(), m.wallet.toBuffer()],
+            program.programId
+          )[0]
       );
 
       await program.methods
-        .createPayrollBatch(bulkBatchId, new BN(2 * web3.LAMPORTS_PER_SOL))
+        .bulkAddMembers(membersData)
         .accountsStrict({
           payer: vaultOwner.publicKey,
           vaultAccount: vaultAccount,
-          globalConfig: globalConfig,
-          payrollBatch: bulkBatch,
-          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
           systemProgram: web3.SystemProgram.programId,
         })
+        .remainingAccounts(
+          memberAccounts.map((pubkey) => ({
+            pubkey,
+            isWritable: true,
+            isSigner: false,
+          }))
+        )
         .signers([vaultOwner])
         .rpc();
 
-      // This should either fail completely or only process valid members
+      for (let i = 0; i < membersData.length; i++) {
+        const member = await program.account.member.fetch(memberAccounts[i]);
+        expect(member.wallet.toString()).to.equal(
+          membersData[i].wallet.toString()
+        );
+        expect(member.vault.toString()).to.equal(vaultAccount.toString());
+        expect(member.role).to.equal(membersData[i].role);
+        expect(member.allocationBps).to.equal(membersData[i].allocationBps);
+        expect(member.isActive).to.be.true;
+      }
+    });
+
+    it("Should fail bulk add with duplicate wallets", async () => {
+      const wallet = web3.Keypair.generate().publicKey;
+      const memberData = {
+        wallet,
+        role: "Developer",
+        allocationBps: 100,
+        solPaymentAllocation: null,
+        splTokenAllocation: null,
+        metadataUri: null,
+      };
+      const [memberAccount] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("member"), vaultAccount.toBuffer(), wallet.toBuffer()],
+        program.programId
+      );
+
       try {
         await program.methods
-          .bulkProcessPayouts(payoutData)
+          .bulkAddMembers([memberData, memberData])
           .accountsStrict({
+            payer: vaultOwner.publicKey,
             vaultAccount: vaultAccount,
-            payrollBatch: bulkBatch,
-            globalConfig: globalConfig,
-            treasury: treasury.publicKey,
-            owner: vaultOwner.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
             systemProgram: web3.SystemProgram.programId,
           })
+          .remainingAccounts([
+            { pubkey: memberAccount, isWritable: true, isSigner: false },
+            { pubkey: memberAccount, isWritable: true, isSigner: false },
+          ])
           .signers([vaultOwner])
           .rpc();
-
-        // If it succeeds, check that only valid members were processed
-        const batch = await program.account.payrollBatch.fetch(bulkBatch);
-        // Should be less than total payouts attempted due to removed member
-        expect(batch.payoutCount).to.be.lessThan(payoutData.length);
-        console.log(
-          "✅ Bulk operation partially succeeded, skipping removed member"
-        );
+        expect.fail("Should have thrown error");
       } catch (error) {
-        // If it fails completely, that's also acceptable behavior
-        console.log(
-          "✅ Bulk operation correctly failed due to removed member:",
-          error.message
-        );
-        expect(true).to.be.true; // Mark as passed since failure is expected
+        expect(error.message).to.include("DuplicateMemberWallet");
       }
     });
 
-    it("Should not allow re-adding member with same wallet after removal", async () => {
-      // Attempt to re-add the same member (same wallet address)
+    it("Should fail bulk add with excessive total allocation", async () => {
+      const membersData = [
+        {
+          wallet: web3.Keypair.generate().publicKey,
+          role: "Developer",
+          allocationBps: 6000,
+          solPaymentAllocation: null,
+          splTokenAllocation: null,
+          metadataUri: null,
+        },
+        {
+          wallet: web3.Keypair.generate().publicKey,
+          role: "Designer",
+          allocationBps: 5000, // Total would be 11000 > 10000
+          solPaymentAllocation: null,
+          splTokenAllocation: null,
+          metadataUri: null,
+        },
+      ];
+
       try {
         await program.methods
-          .addMember(
-            "Readded Member",
-            1500, // Different allocation
-            null,
-            null,
-            "https://example.com/readded-member.json"
-          )
+          .bulkAddMembers(membersData)
           .accountsStrict({
             payer: vaultOwner.publicKey,
             vaultAccount: vaultAccount,
-            owner: vaultOwner.publicKey,
-            member: removedMemberAccount, // Same PDA should be derivable
-            wallet: removedMemberKeypair.publicKey, // Same wallet
+            authority: vaultOwner.publicKey,
+            delegate: null,
             systemProgram: web3.SystemProgram.programId,
           })
           .signers([vaultOwner])
           .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("TotalAllocationExceeded");
+      }
+    });
 
-        // If this succeeds, verify the member was re-added properly
-        const readdedMemberData = await program.account.member.fetch(
-          removedMemberAccount
-        );
-        expect(readdedMemberData.isActive).to.be.true;
-        expect(readdedMemberData.allocationBps).to.equal(1500);
-        expect(readdedMemberData.role).to.equal("ReaddedMember");
-        console.log("✅ Member successfully re-added after removal");
+    it("Should pay every member in bulk process payouts", async () => {
+      const payoutData = [
+        {
+          member: member1.publicKey,
+          amount: new BN(web3.LAMPORTS_PER_SOL),
+          assetType: { sol: {} },
+        },
+        {
+          member: member2.publicKey,
+          amount: new BN(2 * web3.LAMPORTS_PER_SOL),
+          assetType: { sol: {} },
+        },
+      ];
 
-        // Clean up by removing again for consistency
+      // Create a new batch for bulk processing
+      const bulkBatchId = new BN(2);
+      const [bulkPayrollBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          bulkBatchId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createPayrollBatch(bulkBatchId, new BN(3 * web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          payrollBatch: bulkPayrollBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      // Each SOL leg takes [member PDA, receipt PDA, member wallet] as remaining accounts
+      const remainingAccounts = payoutData.flatMap((payout) => {
+        const [memberAccount] = web3.PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("member"),
+            vaultAccount.toBuffer(),
+            payout.member.toBuffer(),
+          ],
+          program.programId
+        );
+        return [
+          { pubkey: memberAccount, isWritable: false, isSigner: false },
+          {
+            pubkey: receiptFor(bulkPayrollBatch, memberAccount),
+            isWritable: true,
+            isSigner: false,
+          },
+          { pubkey: payout.member, isWritable: true, isSigner: false },
+        ];
+      });
+
+      const initialMember2Balance = await provider.connection.getBalance(
+        member2.publicKey
+      );
+
+      await program.methods
+        .bulkProcessPayouts(payoutData)
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          payrollBatch: bulkPayrollBatch,
+          approvalConfig: approvalConfigFor(vaultAccount),
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          tokenProgram: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([vaultOwner])
+        .rpc();
+
+      const batch = await program.account.payrollBatch.fetch(bulkPayrollBatch);
+      expect(batch.payoutCount).to.equal(payoutData.length);
+
+      // Member 2 receives the net amount of its leg
+      const expectedNet = payoutData[1].amount.sub(
+        payoutData[1].amount.mul(new BN(DEFAULT_FEE_BPS)).div(new BN(10000))
+      );
+      const finalMember2Balance = await provider.connection.getBalance(
+        member2.publicKey
+      );
+      expect(finalMember2Balance - initialMember2Balance).to.equal(
+        expectedNet.toNumber()
+      );
+    });
+
+    it("Should fail bulk process payouts with a mismatched member wallet", async () => {
+      const payoutData = [
+        {
+          member: member1.publicKey,
+          amount: new BN(web3.LAMPORTS_PER_SOL / 10),
+          assetType: { sol: {} },
+        },
+      ];
+
+      const bulkBatchId = new BN(5);
+      const [bulkPayrollBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          bulkBatchId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createPayrollBatch(bulkBatchId, new BN(web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          payrollBatch: bulkPayrollBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const [member1Account] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          member1.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      try {
         await program.methods
-          .removeMember()
+          .bulkProcessPayouts(payoutData)
           .accountsStrict({
+            payer: vaultOwner.publicKey,
             vaultAccount: vaultAccount,
-            member: removedMemberAccount,
-            owner: vaultOwner.publicKey,
+            payrollBatch: bulkPayrollBatch,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            globalConfig: globalConfig,
+            treasury: treasury.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+            tokenProgram: null,
+            systemProgram: web3.SystemProgram.programId,
           })
+          .remainingAccounts([
+            { pubkey: member1Account, isWritable: false, isSigner: false },
+            {
+              pubkey: receiptFor(bulkPayrollBatch, member1Account),
+              isWritable: true,
+              isSigner: false,
+            },
+            // Wrong wallet for member 1
+            { pubkey: member2.publicKey, isWritable: true, isSigner: false },
+          ])
           .signers([vaultOwner])
           .rpc();
+        expect.fail("Should have thrown error");
       } catch (error) {
-        // Some programs might prevent re-adding, which is also valid
-        console.log(
-          "✅ Re-adding member after removal prevented (this is acceptable):",
-          error.message
-        );
-        expect(true).to.be.true; // Mark as passed since prevention is valid behavior
+        expect(error.message).to.include("InvalidMemberWallet");
       }
     });
+  });
 
-    it("Should verify vault state after member removal", async () => {
-      // Check that vault doesn't reference the removed member
-      const vault = await program.account.vaultAccount.fetch(vaultAccount);
+  describe("Pull-Based Claims", () => {
+    const claimBatchId = new BN(6);
+    const claimAmount = new BN(web3.LAMPORTS_PER_SOL / 2);
+    let claimBatch: web3.PublicKey;
+    let member2Account: web3.PublicKey;
 
-      // The vault itself shouldn't maintain a list of members (that's handled by PDAs)
-      // But we can verify other vault state is intact
-      expect(vault.owner.toString()).to.equal(vaultOwner.publicKey.toString());
-      expect(vault.name).to.equal(VAULT_NAME);
+    before(async () => {
+      [claimBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          claimBatchId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      [member2Account] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          member2.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
 
-      // Verify active member is still accessible
-      const activeMemberData = await program.account.member.fetch(
-        activeMemberAccount
+      await program.methods
+        .createPayrollBatch(claimBatchId, claimAmount)
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          payrollBatch: claimBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+    });
+
+    it("Should allocate a SOL claim and reserve the amount", async () => {
+      const vaultBefore = await program.account.vaultAccount.fetch(vaultAccount);
+
+      await program.methods
+        .allocateClaim(claimAmount, { sol: {} })
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          payrollBatch: claimBatch,
+          approvalConfig: approvalConfigFor(vaultAccount),
+          member: member2Account,
+          receipt: receiptFor(claimBatch, member2Account),
+          globalConfig: globalConfig,
+          vaultTokenAccount: null,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const receipt = await program.account.payoutReceipt.fetch(
+        receiptFor(claimBatch, member2Account)
       );
-      expect(activeMemberData.isActive).to.be.true;
-      expect(activeMemberData.role).to.equal("ActiveMember");
+      expect(receipt.claimed).to.be.false;
+      expect(receipt.wallet.toString()).to.equal(member2.publicKey.toString());
 
-      console.log("✅ Vault state remains consistent after member removal");
-      console.log("✅ Active members unaffected by removal of other members");
+      const vaultAfter = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(vaultAfter.requiredBalance.toString()).to.equal(
+        vaultBefore.requiredBalance.add(claimAmount).toString()
+      );
     });
 
-    after(async () => {
-      // Clean up the active member for other tests
+    it("Should reject a claim from a different wallet", async () => {
       try {
         await program.methods
-          .removeMember()
+          .claimSolPayment()
           .accountsStrict({
+            claimant: member1.publicKey,
             vaultAccount: vaultAccount,
-            member: activeMemberAccount,
-            owner: vaultOwner.publicKey,
+            receipt: receiptFor(claimBatch, member2Account),
+            globalConfig: globalConfig,
+            treasury: treasury.publicKey,
+            systemProgram: web3.SystemProgram.programId,
           })
-          .signers([vaultOwner])
+          .signers([member1])
           .rpc();
-        console.log("✅ Test cleanup: Active member removed");
+        expect.fail("Should have thrown error");
       } catch (error) {
-        console.log("Test cleanup failed (non-critical):", error.message);
+        expect(error.message).to.include("InvalidClaimant");
       }
     });
-  });
 
-  describe("Edge Cases and Validation", () => {
-    it("Should handle vault name length validation", async () => {
-      const longName = "A".repeat(50); // Exceeds MAX_NAME_LENGTH (32)
-      const newVaultOwner = web3.Keypair.generate();
+    it("Should let the member claim their allocation", async () => {
+      const serviceFee = claimAmount
+        .mul(new BN(DEFAULT_FEE_BPS))
+        .div(new BN(10000));
+      const vaultBefore = await program.account.vaultAccount.fetch(vaultAccount);
+      const treasuryBefore = await provider.connection.getBalance(
+        treasury.publicKey
+      );
 
-      await provider.connection.requestAirdrop(
-        newVaultOwner.publicKey,
-        2 * web3.LAMPORTS_PER_SOL
+      await program.methods
+        .claimSolPayment()
+        .accountsStrict({
+          claimant: member2.publicKey,
+          vaultAccount: vaultAccount,
+          receipt: receiptFor(claimBatch, member2Account),
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([member2])
+        .rpc();
+
+      const receipt = await program.account.payoutReceipt.fetch(
+        receiptFor(claimBatch, member2Account)
       );
-      await new Promise((resolve) => setTimeout(resolve, 1000));
+      expect(receipt.claimed).to.be.true;
 
-      const configAccount = await program.account.globalConfig.fetch(
-        globalConfig
+      const vaultAfter = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(vaultAfter.requiredBalance.toString()).to.equal(
+        vaultBefore.requiredBalance.sub(claimAmount).toString()
       );
-      const nextCompanyId = configAccount.nextCompanyId;
-      const [newVaultAccount] = web3.PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("vault"),
-          newVaultOwner.publicKey.toBuffer(),
-          nextCompanyId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId
+      expect(vaultAfter.totalBalance.toString()).to.equal(
+        vaultBefore.totalBalance.sub(claimAmount).toString()
+      );
+
+      const treasuryAfter = await provider.connection.getBalance(
+        treasury.publicKey
       );
+      expect(treasuryAfter - treasuryBefore).to.equal(serviceFee.toNumber());
+    });
 
+    it("Should not allow claiming twice", async () => {
       try {
         await program.methods
-          .createVault(
-            longName,
-            { individuals: {} },
-            [{ sol: {} }],
-            null,
-            { allocationPerBps: {} },
-            null,
-            null
-          )
+          .claimSolPayment()
           .accountsStrict({
-            vaultAccount: newVaultAccount,
+            claimant: member2.publicKey,
+            vaultAccount: vaultAccount,
+            receipt: receiptFor(claimBatch, member2Account),
             globalConfig: globalConfig,
-            payer: newVaultOwner.publicKey,
-            owner: newVaultOwner.publicKey,
+            treasury: treasury.publicKey,
             systemProgram: web3.SystemProgram.programId,
           })
-          .signers([newVaultOwner])
+          .signers([member2])
           .rpc();
         expect.fail("Should have thrown error");
       } catch (error) {
-        expect(error.message).to.include("NameTooLong");
+        expect(error.message).to.include("AlreadyClaimed");
       }
     });
 
-    it("Should handle metadata URI length validation", async () => {
-      const longUri = "https://example.com/" + "a".repeat(300); // Exceeds 200 chars
-      const testMember = web3.Keypair.generate();
-      const [testMemberAccount] = web3.PublicKey.findProgramAddressSync(
+    it("Should let the owner cancel an unclaimed allocation", async () => {
+      const cancelBatchId = new BN(7);
+      const [cancelBatch] = web3.PublicKey.findProgramAddressSync(
         [
-          Buffer.from("member"),
+          Buffer.from("batch"),
           vaultAccount.toBuffer(),
-          testMember.publicKey.toBuffer(),
+          cancelBatchId.toArrayLike(Buffer, "le", 8),
         ],
         program.programId
       );
+      const receipt = receiptFor(cancelBatch, member2Account);
 
-      try {
-        await program.methods
-          .addMember("Test", 1000, null, null, longUri)
-          .accountsStrict({
-            payer: vaultOwner.publicKey,
-            vaultAccount: vaultAccount,
-            owner: vaultOwner.publicKey,
-            member: testMemberAccount,
-            wallet: testMember.publicKey,
-            systemProgram: web3.SystemProgram.programId,
-          })
-          .signers([vaultOwner])
-          .rpc();
-        expect.fail("Should have thrown error");
-      } catch (error) {
-        expect(error.message).to.satisfy(
-          (msg: string) =>
-            msg.includes("MetadataUriTooLong") ||
-            msg.includes("ConstraintHasOne")
-        );
-      }
-    });
+      await program.methods
+        .createPayrollBatch(cancelBatchId, claimAmount)
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          payrollBatch: cancelBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
 
-    it("Should handle zero amounts gracefully", async () => {
+      const vaultBefore = await program.account.vaultAccount.fetch(vaultAccount);
+
+      await program.methods
+        .allocateClaim(claimAmount, { sol: {} })
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          payrollBatch: cancelBatch,
+          approvalConfig: approvalConfigFor(vaultAccount),
+          member: member2Account,
+          receipt,
+          globalConfig: globalConfig,
+          vaultTokenAccount: null,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      // Only the vault owner may cancel
       try {
         await program.methods
-          .depositSol(new BN(0))
+          .cancelClaim()
           .accountsStrict({
             vaultAccount: vaultAccount,
-            depositor: vaultOwner.publicKey,
-            systemProgram: web3.SystemProgram.programId,
+            receipt,
+            payer: vaultOwner.publicKey,
+            owner: member2.publicKey,
           })
-          .signers([vaultOwner])
+          .signers([member2])
           .rpc();
-
-        // Zero deposits should be allowed (though not very useful)
-        expect(true).to.be.true;
+        expect.fail("Should have thrown error");
       } catch (error) {
-        // If there's a zero amount check, that's also valid
-        console.log("Zero amount handled:", error.message);
+        expect(error.message).to.include("ConstraintHasOne");
       }
+
+      await program.methods
+        .cancelClaim()
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          receipt,
+          payer: vaultOwner.publicKey,
+          owner: vaultOwner.publicKey,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(receipt)).to.be.null;
+      const vaultAfter = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(vaultAfter.requiredBalance.toString()).to.equal(
+        vaultBefore.requiredBalance.toString()
+      );
+      expect(vaultAfter.totalBalance.toString()).to.equal(
+        vaultBefore.totalBalance.toString()
+      );
+
+      await program.methods
+        .finalizePayrollBatch()
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          payrollBatch: cancelBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+        })
+        .signers([vaultOwner])
+        .rpc();
     });
   });
 
-  describe("Client-Side Scheduling Simulation", () => {
-    let schedulerMember: web3.Keypair;
-    let schedulerMemberAccount: web3.PublicKey;
-    let schedulingVault: web3.PublicKey;
-    let schedulingOwner: web3.Keypair;
+  describe("Streaming Payroll", () => {
+    const RATE_PER_SECOND = new BN(1000);
+    const STREAM_DEPOSIT = new BN(web3.LAMPORTS_PER_SOL / 100);
+    let member2Account: web3.PublicKey;
+    let stream: web3.PublicKey;
+
+    const updateStreamAccounts = () => ({
+      vaultAccount: vaultAccount,
+      stream: stream,
+      globalConfig: globalConfig,
+      vaultTokenAccount: null,
+      authority: vaultOwner.publicKey,
+      delegate: null,
+    });
 
-    // Scheduling configuration
-    interface ScheduleConfig {
-      intervalSeconds: number;
-      isActive: boolean;
-      nextRunTime: number;
-      membersToProcess: web3.PublicKey[];
-    }
+    const withdrawSolStream = () =>
+      program.methods
+        .withdrawSolStream()
+        .accountsStrict({
+          wallet: member2.publicKey,
+          vaultAccount: vaultAccount,
+          stream: stream,
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([member2])
+        .rpc();
 
-    // Mock scheduler class
-    class PayrollScheduler {
-      private schedules: Map<string, ScheduleConfig> = new Map();
-      private timers: Map<string, NodeJS.Timeout> = new Map();
-      private program: Program<PayermintP>;
-      private provider: anchor.AnchorProvider;
-      private vaultOwners: Map<string, web3.Keypair> = new Map(); // Store vault owners for signing
+    before(async () => {
+      [member2Account] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          member2.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+      [stream] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stream"), member2Account.toBuffer()],
+        program.programId
+      );
+    });
 
-      constructor(
-        program: Program<PayermintP>,
-        provider: anchor.AnchorProvider
-      ) {
-        this.program = program;
-        this.provider = provider;
-      }
+    it("Should create a SOL stream and reserve the deposit", async () => {
+      const vaultBefore = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
 
-      // Register vault owner for signing
-      registerVaultOwner(vaultId: string, owner: web3.Keypair): void {
-        this.vaultOwners.set(vaultId, owner);
-      }
+      await program.methods
+        .createStream(
+          { sol: {} },
+          RATE_PER_SECOND,
+          new BN(Math.floor(Date.now() / 1000)),
+          null,
+          STREAM_DEPOSIT
+        )
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          member: member2Account,
+          globalConfig: globalConfig,
+          stream: stream,
+          vaultTokenAccount: null,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
 
-      // Add a new schedule
-      addSchedule(
-        vaultId: string,
-        intervalSeconds: number,
-        membersToProcess: web3.PublicKey[]
-      ): void {
-        const schedule: ScheduleConfig = {
-          intervalSeconds,
-          isActive: true,
-          nextRunTime: Math.floor(Date.now() / 1000) + intervalSeconds,
-          membersToProcess,
-        };
+      const streamData = await program.account.paymentStream.fetch(stream);
+      expect(streamData.wallet.toString()).to.equal(
+        member2.publicKey.toString()
+      );
+      expect(streamData.deposited.toString()).to.equal(
+        STREAM_DEPOSIT.toString()
+      );
 
-        this.schedules.set(vaultId, schedule);
-        this.startSchedule(vaultId);
+      const vaultAfter = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(vaultAfter.requiredBalance.toString()).to.equal(
+        vaultBefore.requiredBalance.add(STREAM_DEPOSIT).toString()
+      );
+    });
 
-        console.log(`📅 Schedule added for vault ${vaultId.substring(0, 8)}... 
-        Interval: ${intervalSeconds}s, Members: ${membersToProcess.length}`);
-      }
+    it("Should let the member withdraw what has accrued", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 2000));
 
-      // Start a schedule
-      private startSchedule(vaultId: string): void {
-        const schedule = this.schedules.get(vaultId);
-        if (!schedule || !schedule.isActive) return;
+      await withdrawSolStream();
 
-        const delay = schedule.nextRunTime * 1000 - Date.now();
-        const adjustedDelay = Math.max(100, delay); // Minimum 100ms for testing
+      const streamData = await program.account.paymentStream.fetch(stream);
+      expect(streamData.withdrawn.toNumber()).to.be.greaterThan(0);
+      expect(streamData.withdrawn.lte(STREAM_DEPOSIT)).to.be.true;
+    });
 
-        const timer = setTimeout(async () => {
-          await this.executeScheduledPayout(vaultId);
+    it("Should stop accrual while paused", async () => {
+      await program.methods
+        .pauseStream()
+        .accountsStrict(updateStreamAccounts())
+        .signers([vaultOwner])
+        .rpc();
 
-          // Schedule next execution
-          if (schedule.isActive) {
-            schedule.nextRunTime =
-              Math.floor(Date.now() / 1000) + schedule.intervalSeconds;
-            this.startSchedule(vaultId);
-          }
-        }, adjustedDelay);
+      // Drain whatever vested before the pause
+      const paused = await program.account.paymentStream.fetch(stream);
+      if (paused.accrued.gt(paused.withdrawn)) {
+        await withdrawSolStream();
+      }
 
-        this.timers.set(vaultId, timer);
+      await new Promise((resolve) => setTimeout(resolve, 2000));
 
-        console.log(
-          `⏰ Next execution for vault ${vaultId.substring(
-            0,
-            8
-          )}... scheduled in ${Math.ceil(adjustedDelay / 1000)}s`
-        );
+      try {
+        await withdrawSolStream();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("NothingToWithdraw");
       }
 
-      // Execute scheduled payout
-      private async executeScheduledPayout(vaultId: string): Promise<void> {
-        try {
-          const schedule = this.schedules.get(vaultId);
-          const vaultOwner = this.vaultOwners.get(vaultId);
+      await program.methods
+        .resumeStream()
+        .accountsStrict(updateStreamAccounts())
+        .signers([vaultOwner])
+        .rpc();
 
-          if (!schedule || !vaultOwner) {
-            console.log(
-              `⚠️ Missing schedule or vault owner for ${vaultId.substring(
-                0,
-                8
-              )}...`
-            );
-            return;
-          }
+      const resumed = await program.account.paymentStream.fetch(stream);
+      expect(resumed.paused).to.be.false;
+      expect(resumed.accrued.toString()).to.equal(paused.accrued.toString());
+    });
 
-          console.log(
-            `\n🚀 Executing scheduled payout for vault ${vaultId.substring(
-              0,
-              8
-            )}...`
-          );
+    it("Should return unvested funds to the vault on cancel", async () => {
+      const vaultBefore = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+      const streamBefore = await program.account.paymentStream.fetch(stream);
 
-          // Get vault account from vaultId
-          const vaultPublicKey = new web3.PublicKey(vaultId);
-          const vault = await this.program.account.vaultAccount.fetch(
-            vaultPublicKey
-          );
+      await program.methods
+        .cancelStream()
+        .accountsStrict(updateStreamAccounts())
+        .signers([vaultOwner])
+        .rpc();
 
-          // Check if vault's payout schedule is ready
-          if (!vault.payoutSchedule || !vault.payoutSchedule.active) {
-            console.log(`⏭️ Vault payout schedule not active, skipping`);
-            return;
-          }
+      const streamAfter = await program.account.paymentStream.fetch(stream);
+      expect(streamAfter.cancelled).to.be.true;
+      expect(streamAfter.deposited.toString()).to.equal(
+        streamAfter.accrued.toString()
+      );
 
-          const currentTime = Math.floor(Date.now() / 1000);
-          if (currentTime < vault.payoutSchedule.nextPayoutTs.toNumber()) {
-            console.log(
-              `⏭️ Not yet time for payout (${
-                vault.payoutSchedule.nextPayoutTs.toNumber() - currentTime
-              }s remaining)`
-            );
-            return;
-          }
+      const released = streamBefore.deposited.sub(streamAfter.deposited);
+      const vaultAfter = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(vaultAfter.requiredBalance.toString()).to.equal(
+        vaultBefore.requiredBalance.sub(released).toString()
+      );
+    });
 
-          // Process payouts for each member
-          let successfulPayouts = 0;
-          let failedPayouts = 0;
+    it("Should not top up a cancelled stream", async () => {
+      try {
+        await program.methods
+          .topUpStream(STREAM_DEPOSIT)
+          .accountsStrict(updateStreamAccounts())
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("StreamCancelled");
+      }
+    });
 
-          for (const memberWallet of schedule.membersToProcess) {
-            try {
-              // Derive member PDA
-              const [memberAccount] = web3.PublicKey.findProgramAddressSync(
-                [
-                  Buffer.from("member"),
-                  vaultPublicKey.toBuffer(),
-                  memberWallet.toBuffer(),
-                ],
-                this.program.programId
-              );
+    it("Should close the stream once everything vested is withdrawn", async () => {
+      const closeStream = () =>
+        program.methods
+          .closeStream()
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            stream: stream,
+            payer: vaultOwner.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+          })
+          .signers([vaultOwner])
+          .rpc();
+
+      const streamData = await program.account.paymentStream.fetch(stream);
+      if (streamData.withdrawn.lt(streamData.deposited)) {
+        try {
+          await closeStream();
+          expect.fail("Should have thrown error");
+        } catch (error) {
+          expect(error.message).to.include("StreamNotSettled");
+        }
+        await withdrawSolStream();
+      }
+
+      await closeStream();
+
+      const streamInfo = await provider.connection.getAccountInfo(stream);
+      expect(streamInfo).to.be.null;
+    });
+  });
+
+  describe("Withdrawals", () => {
+    it("Should let the owner withdraw unreserved SOL", async () => {
+      const amount = new BN(web3.LAMPORTS_PER_SOL / 10);
+      const vaultBefore = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+
+      await program.methods
+        .withdrawSol(amount)
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          approvalConfig: approvalConfigFor(vaultAccount),
+          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const vaultAfter = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(vaultAfter.totalBalance.toString()).to.equal(
+        vaultBefore.totalBalance.sub(amount).toString()
+      );
+    });
+
+    it("Should not withdraw reserved SOL", async () => {
+      const vault = await program.account.vaultAccount.fetch(vaultAccount);
+      const unreserved = vault.totalBalance.sub(vault.requiredBalance);
+
+      try {
+        await program.methods
+          .withdrawSol(unreserved.add(new BN(1)))
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            owner: vaultOwner.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InsufficientVaultBalance");
+      }
+    });
+
+    it("Should let the owner withdraw SPL tokens", async () => {
+      const amount = new BN(10 * 10 ** 6);
+      const vaultTokenAccount = getAssociatedTokenAddressSync(
+        testMint,
+        vaultAccount,
+        true
+      );
+      const ownerTokenAccount = getAssociatedTokenAddressSync(
+        testMint,
+        vaultOwner.publicKey
+      );
+      const vaultTokensBefore = (
+        await getAccount(provider.connection, vaultTokenAccount)
+      ).amount;
+
+      await program.methods
+        .withdrawSplToken(amount)
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          approvalConfig: approvalConfigFor(vaultAccount),
+          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          mint: testMint,
+          vaultTokenAccount: vaultTokenAccount,
+          ownerTokenAccount: ownerTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const vaultTokensAfter = (
+        await getAccount(provider.connection, vaultTokenAccount)
+      ).amount;
+      expect(
+        new BN(vaultTokensBefore.toString())
+          .sub(new BN(vaultTokensAfter.toString()))
+          .toString()
+      ).to.equal(amount.toString());
+    });
+
+    it("Should reject withdrawals by anyone but the owner", async () => {
+      try {
+        await program.methods
+          .withdrawSol(new BN(1))
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            owner: vaultOwner.publicKey,
+            authority: member1.publicKey,
+            delegate: null,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([member1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+  });
+
+  describe("Error Cases", () => {
+    it("Should fail unauthorized access", async () => {
+      const unauthorizedUser = web3.Keypair.generate();
+
+      // Fund the unauthorized user
+      await provider.connection.requestAirdrop(
+        unauthorizedUser.publicKey,
+        web3.LAMPORTS_PER_SOL
+      );
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      try {
+        await program.methods
+          .updatePayoutSchedule(null)
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            authority: unauthorizedUser.publicKey,
+            delegate: null,
+          })
+          .signers([unauthorizedUser])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Should fail with insufficient vault balance", async () => {
+      const member3Account = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          member3.publicKey.toBuffer(),
+        ],
+        program.programId
+      )[0];
+
+      // Create a new batch
+      const insufficientBatchId = new BN(3);
+      const [insufficientBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          insufficientBatchId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createPayrollBatch(
+          insufficientBatchId,
+          new BN(100 * web3.LAMPORTS_PER_SOL)
+        )
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          payrollBatch: insufficientBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      try {
+        await program.methods
+          .processSolPayout(new BN(100 * web3.LAMPORTS_PER_SOL)) // More than vault balance
+          .accountsStrict({
+            payer: vaultOwner.publicKey,
+            vaultAccount: vaultAccount,
+            payrollBatch: insufficientBatch,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            member: member3Account,
+            receipt: receiptFor(insufficientBatch, member3Account),
+            globalConfig: globalConfig,
+            treasury: treasury.publicKey,
+            memberWallet: member3.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InsufficientVaultBalance");
+      }
+    });
+
+    it("Should fail payout to inactive member", async () => {
+      // First deactivate member 3
+      const member3Account = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          member3.publicKey.toBuffer(),
+        ],
+        program.programId
+      )[0];
+
+      await program.methods
+        .toggleMemberActiveStatus()
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          member: member3Account,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      // Create a new batch for this test
+      const inactiveBatchId = new BN(4);
+      const [inactiveBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          inactiveBatchId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createPayrollBatch(inactiveBatchId, new BN(web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          payrollBatch: inactiveBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      try {
+        await program.methods
+          .processSolPayout(new BN(web3.LAMPORTS_PER_SOL))
+          .accountsStrict({
+            payer: vaultOwner.publicKey,
+            vaultAccount: vaultAccount,
+            payrollBatch: inactiveBatch,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            member: member3Account,
+            receipt: receiptFor(inactiveBatch, member3Account),
+            globalConfig: globalConfig,
+            treasury: treasury.publicKey,
+            memberWallet: member3.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("MemberNotActive");
+      }
+    });
+  });
+
+  describe("Member Removal and Payroll Exclusion", () => {
+    let removedMemberKeypair: web3.Keypair;
+    let removedMemberAccount: web3.PublicKey;
+    let activeMemberKeypair: web3.Keypair;
+    let activeMemberAccount: web3.PublicKey;
+
+    before(async () => {
+      // Create test members for this specific test
+      removedMemberKeypair = web3.Keypair.generate();
+      activeMemberKeypair = web3.Keypair.generate();
+
+      // Fund the test member accounts
+      await Promise.all([
+        provider.connection.requestAirdrop(
+          removedMemberKeypair.publicKey,
+          2 * web3.LAMPORTS_PER_SOL
+        ),
+        provider.connection.requestAirdrop(
+          activeMemberKeypair.publicKey,
+          2 * web3.LAMPORTS_PER_SOL
+        ),
+      ]);
+
+      // Wait for confirmations
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      // Derive member PDAs
+      [removedMemberAccount] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          removedMemberKeypair.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      [activeMemberAccount] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          activeMemberKeypair.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      // Add both members to the vault (roles must be <= 16 characters)
+      await program.methods
+        .addMember(
+          "ToBeRemoved", // 11 chars - within 16 char limit
+          2000, // 20% allocation
+          null,
+          null,
+          "https://example.com/removed-member.json"
+        )
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          member: removedMemberAccount,
+          wallet: removedMemberKeypair.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      await program.methods
+        .addMember(
+          "ActiveMember", // 12 chars - within 16 char limit
+          3000, // 30% allocation
+          null,
+          null,
+          "https://example.com/active-member.json"
+        )
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          member: activeMemberAccount,
+          wallet: activeMemberKeypair.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      console.log("✅ Test members added successfully");
+    });
+
+    it("Should verify members exist before removal", async () => {
+      // Verify both members exist and are active
+      const removedMemberData = await program.account.member.fetch(
+        removedMemberAccount
+      );
+      const activeMemberData = await program.account.member.fetch(
+        activeMemberAccount
+      );
+
+      expect(removedMemberData.isActive).to.be.true;
+      expect(removedMemberData.allocationBps).to.equal(2000);
+      expect(activeMemberData.isActive).to.be.true;
+      expect(activeMemberData.allocationBps).to.equal(3000);
+
+      console.log("✅ Both members verified as active before removal");
+    });
+
+    it("Should successfully process payroll for members before removal", async () => {
+      // Create a new batch for pre-removal testing
+      const preRemovalBatchId = new BN(100);
+      const [preRemovalBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          preRemovalBatchId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createPayrollBatch(
+          preRemovalBatchId,
+          new BN(3 * web3.LAMPORTS_PER_SOL)
+        )
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          payrollBatch: preRemovalBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      // Process payout for the member that will be removed
+      const payoutAmount = new BN(web3.LAMPORTS_PER_SOL);
+      const initialRemovedMemberBalance = await provider.connection.getBalance(
+        removedMemberKeypair.publicKey
+      );
+
+      await program.methods
+        .processSolPayout(payoutAmount)
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          payrollBatch: preRemovalBatch,
+          approvalConfig: approvalConfigFor(vaultAccount),
+          member: removedMemberAccount,
+          receipt: receiptFor(preRemovalBatch, removedMemberAccount),
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          memberWallet: removedMemberKeypair.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      // Verify the payout was successful
+      const finalRemovedMemberBalance = await provider.connection.getBalance(
+        removedMemberKeypair.publicKey
+      );
+      expect(finalRemovedMemberBalance).to.be.greaterThan(
+        initialRemovedMemberBalance
+      );
+
+      // Finalize the batch
+      await program.methods
+        .finalizePayrollBatch()
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          payrollBatch: preRemovalBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      console.log(
+        "✅ Pre-removal payroll processed successfully for both members"
+      );
+    });
+
+    it("Should remove the member", async () => {
+      // Record initial balance before removal
+      const initialRemovedMemberBalance = await provider.connection.getBalance(
+        removedMemberKeypair.publicKey
+      );
+
+      await program.methods
+        .removeMember()
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          member: removedMemberAccount,
+          owner: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      console.log("✅ Member removed successfully");
+
+      // Verify member account was closed and SOL rent was reclaimed
+      try {
+        await program.account.member.fetch(removedMemberAccount);
+        expect.fail("Member account should have been closed");
+      } catch (error) {
+        expect(error.message).to.include("Account does not exist");
+        console.log("✅ Member account confirmed as closed");
+      }
+
+      // Verify rent was reclaimed (account closure should return rent to vault owner)
+      const finalRemovedMemberBalance = await provider.connection.getBalance(
+        removedMemberKeypair.publicKey
+      );
+
+      // Note: The removed member's wallet balance shouldn't change from the removal
+      // The rent is reclaimed by the vault owner, not the removed member
+      console.log("Initial balance:", initialRemovedMemberBalance);
+      console.log("Final balance:", finalRemovedMemberBalance);
+    });
+
+    it("Should fail to process payroll for removed member", async () => {
+      // Create a new batch for post-removal testing
+      const postRemovalBatchId = new BN(101);
+      const [postRemovalBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          postRemovalBatchId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createPayrollBatch(
+          postRemovalBatchId,
+          new BN(2 * web3.LAMPORTS_PER_SOL)
+        )
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          payrollBatch: postRemovalBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      // Attempt to process payout for the removed member - this should fail
+      try {
+        await program.methods
+          .processSolPayout(new BN(web3.LAMPORTS_PER_SOL))
+          .accountsStrict({
+            payer: vaultOwner.publicKey,
+            vaultAccount: vaultAccount,
+            payrollBatch: postRemovalBatch,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            member: removedMemberAccount, // This account no longer exists
+            receipt: receiptFor(postRemovalBatch, removedMemberAccount),
+            globalConfig: globalConfig,
+            treasury: treasury.publicKey,
+            memberWallet: removedMemberKeypair.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error for removed member");
+      } catch (error) {
+        // Log the full error to see what we're actually getting
+        console.log("Full error message:", error.message);
+        console.log("Error name:", error.name);
+        console.log("Error constructor:", error.constructor.name);
+
+        // More flexible error checking - any error is acceptable since the account is gone
+        expect(error).to.exist;
+        expect(error.message).to.be.a("string");
+        expect(error.message.length).to.be.greaterThan(0);
+
+        console.log(
+          "✅ Payroll correctly failed for removed member (any error is expected)"
+        );
+      }
+
+      // Verify active member can still receive payroll
+      const initialActiveMemberBalance = await provider.connection.getBalance(
+        activeMemberKeypair.publicKey
+      );
+
+      await program.methods
+        .processSolPayout(new BN(web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          payrollBatch: postRemovalBatch,
+          approvalConfig: approvalConfigFor(vaultAccount),
+          member: activeMemberAccount,
+          receipt: receiptFor(postRemovalBatch, activeMemberAccount),
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          memberWallet: activeMemberKeypair.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const finalActiveMemberBalance = await provider.connection.getBalance(
+        activeMemberKeypair.publicKey
+      );
+      expect(finalActiveMemberBalance).to.be.greaterThan(
+        initialActiveMemberBalance
+      );
+
+      console.log(
+        "✅ Active member can still receive payroll after other member removal"
+      );
+    });
+
+    it("Should fail scheduled payouts for removed member", async () => {
+      // Both members were added during the current, already open period
+      const { payoutSchedule } = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+
+      // Attempt scheduled payout for removed member - should fail
+      try {
+        await program.methods
+          .processScheduledPayout(payoutSchedule.nextPayoutTs)
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            member: removedMemberAccount, // This account no longer exists
+            receipt: periodReceiptFor(
+              vaultAccount,
+              payoutSchedule.nextPayoutTs,
+              removedMemberAccount
+            ),
+            globalConfig: globalConfig,
+            treasury: treasury.publicKey,
+            memberWallet: removedMemberKeypair.publicKey,
+            keeper: vaultOwner.publicKey,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail(
+          "Should have thrown error for removed member in scheduled payout"
+        );
+      } catch (error) {
+        // Log the full error to see what we're actually getting
+        console.log("Full scheduled payout error message:", error.message);
+        console.log("Error name:", error.name);
+        console.log("Error constructor:", error.constructor.name);
+
+        // More flexible error checking - any error is acceptable since the account is gone
+        expect(error).to.exist;
+        expect(error.message).to.be.a("string");
+        expect(error.message.length).to.be.greaterThan(0);
+
+        console.log(
+          "✅ Scheduled payout correctly failed for removed member (any error is expected)"
+        );
+      }
+
+      // Verify scheduled payout works for active member
+      const initialActiveMemberBalance = await provider.connection.getBalance(
+        activeMemberKeypair.publicKey
+      );
+
+      await program.methods
+        .processScheduledPayout(payoutSchedule.nextPayoutTs)
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          member: activeMemberAccount,
+          receipt: periodReceiptFor(
+            vaultAccount,
+            payoutSchedule.nextPayoutTs,
+            activeMemberAccount
+          ),
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          memberWallet: activeMemberKeypair.publicKey,
+          keeper: vaultOwner.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const finalActiveMemberBalance = await provider.connection.getBalance(
+        activeMemberKeypair.publicKey
+      );
+      expect(finalActiveMemberBalance).to.be.greaterThan(
+        initialActiveMemberBalance
+      );
+
+      console.log("✅ Scheduled payout works correctly for active members");
+    });
+
+    it("Should fail bulk operations that include removed member", async () => {
+      // Attempt bulk payout that includes the removed member
+      const payoutData = [
+        {
+          member: removedMemberKeypair.publicKey, // Removed member
+          amount: new BN(web3.LAMPORTS_PER_SOL),
+          assetType: { sol: {} },
+        },
+        {
+          member: activeMemberKeypair.publicKey, // Active member
+          amount: new BN(web3.LAMPORTS_PER_SOL),
+          assetType: { sol: {} },
+        },
+      ];
+
+      const bulkBatchId = new BN(102);
+      const [bulkBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          bulkBatchId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createPayrollBatch(bulkBatchId, new BN(2 * web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          payrollBatch: bulkBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      // This should either fail completely or only process valid members
+      try {
+        await program.methods
+          .bulkProcessPayouts(payoutData)
+          .accountsStrict({
+            payer: vaultOwner.publicKey,
+            vaultAccount: vaultAccount,
+            payrollBatch: bulkBatch,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            globalConfig: globalConfig,
+            treasury: treasury.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+            tokenProgram: null,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .remainingAccounts(
+            payoutData.flatMap((payout) => {
+              const [memberAccount] = web3.PublicKey.findProgramAddressSync(
+                [
+                  Buffer.from("member"),
+                  vaultAccount.toBuffer(),
+                  payout.member.toBuffer(),
+                ],
+                program.programId
+              );
+              return [
+                { pubkey: memberAccount, isWritable: false, isSigner: false },
+                {
+                  pubkey: receiptFor(bulkBatch, memberAccount),
+                  isWritable: true,
+                  isSigner: false,
+                },
+                { pubkey: payout.member, isWritable: true, isSigner: false },
+              ];
+            })
+          )
+          .signers([vaultOwner])
+          .rpc();
+
+        // If it succeeds, check that only valid members were processed
+        const batch = await program.account.payrollBatch.fetch(bulkBatch);
+        // Should be less than total payouts attempted due to removed member
+        expect(batch.payoutCount).to.be.lessThan(payoutData.length);
+        console.log(
+          "✅ Bulk operation partially succeeded, skipping removed member"
+        );
+      } catch (error) {
+        // If it fails completely, that's also acceptable behavior
+        console.log(
+          "✅ Bulk operation correctly failed due to removed member:",
+          error.message
+        );
+        expect(true).to.be.true; // Mark as passed since failure is expected
+      }
+    });
+
+    it("Should not allow re-adding member with same wallet after removal", async () => {
+      // Attempt to re-add the same member (same wallet address)
+      try {
+        await program.methods
+          .addMember(
+            "Readded Member",
+            1500, // Different allocation
+            null,
+            null,
+            "https://example.com/readded-member.json"
+          )
+          .accountsStrict({
+            payer: vaultOwner.publicKey,
+            vaultAccount: vaultAccount,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+            member: removedMemberAccount, // Same PDA should be derivable
+            wallet: removedMemberKeypair.publicKey, // Same wallet
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([vaultOwner])
+          .rpc();
+
+        // If this succeeds, verify the member was re-added properly
+        const readdedMemberData = await program.account.member.fetch(
+          removedMemberAccount
+        );
+        expect(readdedMemberData.isActive).to.be.true;
+        expect(readdedMemberData.allocationBps).to.equal(1500);
+        expect(readdedMemberData.role).to.equal("ReaddedMember");
+        console.log("✅ Member successfully re-added after removal");
+
+        // Clean up by removing again for consistency
+        await program.methods
+          .removeMember()
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            member: removedMemberAccount,
+            owner: vaultOwner.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+          })
+          .signers([vaultOwner])
+          .rpc();
+      } catch (error) {
+        // Some programs might prevent re-adding, which is also valid
+        console.log(
+          "✅ Re-adding member after removal prevented (this is acceptable):",
+          error.message
+        );
+        expect(true).to.be.true; // Mark as passed since prevention is valid behavior
+      }
+    });
+
+    it("Should verify vault state after member removal", async () => {
+      // Check that vault doesn't reference the removed member
+      const vault = await program.account.vaultAccount.fetch(vaultAccount);
+
+      // The vault itself shouldn't maintain a list of members (that's handled by PDAs)
+      // But we can verify other vault state is intact
+      expect(vault.owner.toString()).to.equal(vaultOwner.publicKey.toString());
+      expect(vault.name).to.equal(VAULT_NAME);
+
+      // Verify active member is still accessible
+      const activeMemberData = await program.account.member.fetch(
+        activeMemberAccount
+      );
+      expect(activeMemberData.isActive).to.be.true;
+      expect(activeMemberData.role).to.equal("ActiveMember");
+
+      console.log("✅ Vault state remains consistent after member removal");
+      console.log("✅ Active members unaffected by removal of other members");
+    });
+
+    after(async () => {
+      // Clean up the active member for other tests
+      try {
+        await program.methods
+          .removeMember()
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            member: activeMemberAccount,
+            owner: vaultOwner.publicKey,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        console.log("✅ Test cleanup: Active member removed");
+      } catch (error) {
+        console.log("Test cleanup failed (non-critical):", error.message);
+      }
+    });
+  });
+
+  describe("Edge Cases and Validation", () => {
+    it("Should handle vault name length validation", async () => {
+      const longName = "A".repeat(50); // Exceeds MAX_NAME_LENGTH (32)
+      const newVaultOwner = web3.Keypair.generate();
+
+      await provider.connection.requestAirdrop(
+        newVaultOwner.publicKey,
+        2 * web3.LAMPORTS_PER_SOL
+      );
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      const configAccount = await program.account.globalConfig.fetch(
+        globalConfig
+      );
+      const nextCompanyId = configAccount.nextCompanyId;
+      const [newVaultAccount] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          newVaultOwner.publicKey.toBuffer(),
+          nextCompanyId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .createVault(
+            longName,
+            { individuals: {} },
+            [{ sol: {} }],
+            null,
+            { allocationPerBps: {} },
+            null,
+            null
+          )
+          .accountsStrict({
+            vaultAccount: newVaultAccount,
+            globalConfig: globalConfig,
+            payer: newVaultOwner.publicKey,
+            owner: newVaultOwner.publicKey,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([newVaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("NameTooLong");
+      }
+    });
+
+    it("Should handle metadata URI length validation", async () => {
+      const longUri = "https://example.com/" + "a".repeat(300); // Exceeds 200 chars
+      const testMember = web3.Keypair.generate();
+      const [testMemberAccount] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          testMember.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .addMember("Test", 1000, null, null, longUri)
+          .accountsStrict({
+            payer: vaultOwner.publicKey,
+            vaultAccount: vaultAccount,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+            member: testMemberAccount,
+            wallet: testMember.publicKey,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.satisfy(
+          (msg: string) =>
+            msg.includes("MetadataUriTooLong") ||
+            msg.includes("ConstraintHasOne")
+        );
+      }
+    });
+
+    it("Should handle zero amounts gracefully", async () => {
+      try {
+        await program.methods
+          .depositSol(new BN(0))
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            globalConfig: globalConfig,
+            depositor: vaultOwner.publicKey,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([vaultOwner])
+          .rpc();
+
+        // Zero deposits should be allowed (though not very useful)
+        expect(true).to.be.true;
+      } catch (error) {
+        // If there's a zero amount check, that's also valid
+        console.log("Zero amount handled:", error.message);
+      }
+    });
+  });
+
+  describe("Client-Side Scheduling Simulation", () => {
+    let schedulerMember: web3.Keypair;
+    let schedulerMemberAccount: web3.PublicKey;
+    let schedulingVault: web3.PublicKey;
+    let schedulingOwner: web3.Keypair;
+
+    // Scheduling configuration
+    interface ScheduleConfig {
+      intervalSeconds: number;
+      isActive: boolean;
+      nextRunTime: number;
+      membersToProcess: web3.PublicKey[];
+    }
+
+    // Mock scheduler class
+    class PayrollScheduler {
+      private schedules: Map<string, ScheduleConfig> = new Map();
+      private timers: Map<string, NodeJS.Timeout> = new Map();
+      private program: Program<PayermintP>;
+      private provider: anchor.AnchorProvider;
+      private vaultOwners: Map<string, web3.Keypair> = new Map(); // Store vault owners for signing
+
+      constructor(
+        program: Program<PayermintP>,
+        provider: anchor.AnchorProvider
+      ) {
+        this.program = program;
+        this.provider = provider;
+      }
+
+      // Register vault owner for signing
+      registerVaultOwner(vaultId: string, owner: web3.Keypair): void {
+        this.vaultOwners.set(vaultId, owner);
+      }
+
+      // Add a new schedule
+      addSchedule(
+        vaultId: string,
+        intervalSeconds: number,
+        membersToProcess: web3.PublicKey[]
+      ): void {
+        const schedule: ScheduleConfig = {
+          intervalSeconds,
+          isActive: true,
+          nextRunTime: Math.floor(Date.now() / 1000) + intervalSeconds,
+          membersToProcess,
+        };
+
+        this.schedules.set(vaultId, schedule);
+        this.startSchedule(vaultId);
+
+        console.log(`📅 Schedule added for vault ${vaultId.substring(0, 8)}... 
+        Interval: ${intervalSeconds}s, Members: ${membersToProcess.length}`);
+      }
+
+      // Start a schedule
+      private startSchedule(vaultId: string): void {
+        const schedule = this.schedules.get(vaultId);
+        if (!schedule || !schedule.isActive) return;
+
+        const delay = schedule.nextRunTime * 1000 - Date.now();
+        const adjustedDelay = Math.max(100, delay); // Minimum 100ms for testing
+
+        const timer = setTimeout(async () => {
+          await this.executeScheduledPayout(vaultId);
+
+          // Schedule next execution
+          if (schedule.isActive) {
+            schedule.nextRunTime =
+              Math.floor(Date.now() / 1000) + schedule.intervalSeconds;
+            this.startSchedule(vaultId);
+          }
+        }, adjustedDelay);
+
+        this.timers.set(vaultId, timer);
+
+        console.log(
+          `⏰ Next execution for vault ${vaultId.substring(
+            0,
+            8
+          )}... scheduled in ${Math.ceil(adjustedDelay / 1000)}s`
+        );
+      }
+
+      // Execute scheduled payout
+      private async executeScheduledPayout(vaultId: string): Promise<void> {
+        try {
+          const schedule = this.schedules.get(vaultId);
+          const vaultOwner = this.vaultOwners.get(vaultId);
+
+          if (!schedule || !vaultOwner) {
+            console.log(
+              `⚠️ Missing schedule or vault owner for ${vaultId.substring(
+                0,
+                8
+              )}...`
+            );
+            return;
+          }
+
+          console.log(
+            `\n🚀 Executing scheduled payout for vault ${vaultId.substring(
+              0,
+              8
+            )}...`
+          );
+
+          // Get vault account from vaultId
+          const vaultPublicKey = new web3.PublicKey(vaultId);
+          const vault = await this.program.account.vaultAccount.fetch(
+            vaultPublicKey
+          );
+
+          // Check if vault's payout schedule is ready
+          if (!vault.payoutSchedule || !vault.payoutSchedule.active) {
+            console.log(`⏭️ Vault payout schedule not active, skipping`);
+            return;
+          }
+
+          const currentTime = Math.floor(Date.now() / 1000);
+          if (currentTime < vault.payoutSchedule.nextPayoutTs.toNumber()) {
+            console.log(
+              `⏭️ Not yet time for payout (${
+                vault.payoutSchedule.nextPayoutTs.toNumber() - currentTime
+              }s remaining)`
+            );
+            return;
+          }
+
+          // Process payouts for each member
+          let successfulPayouts = 0;
+          let failedPayouts = 0;
+
+          for (const memberWallet of schedule.membersToProcess) {
+            try {
+              // Derive member PDA
+              const [memberAccount] = web3.PublicKey.findProgramAddressSync(
+                [
+                  Buffer.from("member"),
+                  vaultPublicKey.toBuffer(),
+                  memberWallet.toBuffer(),
+                ],
+                this.program.programId
+              );
+
+              // Check if member exists and is active
+              let memberData;
+              try {
+                memberData = await this.program.account.member.fetch(
+                  memberAccount
+                );
+              } catch (error) {
+                console.log(
+                  `⚠️ Member ${memberWallet
+                    .toString()
+                    .substring(0, 8)}... not found, skipping`
+                );
+                failedPayouts++;
+                continue;
+              }
+
+              if (!memberData.isActive) {
+                console.log(
+                  `⚠️ Member ${memberWallet
+                    .toString()
+                    .substring(0, 8)}... is inactive, skipping`
+                );
+                failedPayouts++;
+                continue;
+              }
+
+              // Get global config and treasury
+              const [globalConfig] = web3.PublicKey.findProgramAddressSync(
+                [Buffer.from("global_config")],
+                this.program.programId
+              );
+
+              const globalConfigData =
+                await this.program.account.globalConfig.fetch(globalConfig);
+
+              // Process the scheduled payout with proper signer
+              // Each payment may close the period, so re-read it per member
+              const period = currentPeriod(
+                await this.program.account.vaultAccount.fetch(vaultPublicKey)
+              );
+
+              await this.program.methods
+                .processScheduledPayout(period)
+                .accountsStrict({
+                  vaultAccount: vaultPublicKey,
+                  member: memberAccount,
+                  receipt: periodReceiptFor(
+                    vaultPublicKey,
+                    period,
+                    memberAccount
+                  ),
+                  globalConfig: globalConfig,
+                  treasury: globalConfigData.treasury,
+                  memberWallet: memberWallet,
+                  keeper: vaultOwner.publicKey,
+                  systemProgram: web3.SystemProgram.programId,
+                })
+                .signers([vaultOwner]) // The vault owner cranks as keeper
+                .rpc();
+
+              console.log(
+                `✅ Scheduled payout successful for member ${memberWallet
+                  .toString()
+                  .substring(0, 8)}...`
+              );
+              successfulPayouts++;
+            } catch (error) {
+              console.log(
+                `❌ Scheduled payout failed for member ${memberWallet
+                  .toString()
+                  .substring(0, 8)}...: ${error.message}`
+              );
+              failedPayouts++;
+            }
+          }
+
+          console.log(
+            `📊 Scheduled payout summary: ${successfulPayouts} successful, ${failedPayouts} failed`
+          );
+        } catch (error) {
+          console.log(
+            `❌ Scheduled execution error for vault ${vaultId.substring(
+              0,
+              8
+            )}...: ${error.message}`
+          );
+        }
+      }
+
+      // Stop a schedule
+      stopSchedule(vaultId: string): void {
+        const timer = this.timers.get(vaultId);
+        if (timer) {
+          clearTimeout(timer);
+          this.timers.delete(vaultId);
+        }
+
+        const schedule = this.schedules.get(vaultId);
+        if (schedule) {
+          schedule.isActive = false;
+        }
+
+        console.log(
+          `🛑 Schedule stopped for vault ${vaultId.substring(0, 8)}...`
+        );
+      }
+
+      // Get schedule status
+      getScheduleStatus(vaultId: string): ScheduleConfig | undefined {
+        return this.schedules.get(vaultId);
+      }
+
+      // Stop all schedules (cleanup)
+      stopAll(): void {
+        for (const vaultId of this.timers.keys()) {
+          this.stopSchedule(vaultId);
+        }
+        console.log("🧹 All schedules stopped");
+      }
+    }
+
+    // Initialize scheduler
+    let scheduler: PayrollScheduler;
+
+    before(async () => {
+      // Create scheduler instance
+      scheduler = new PayrollScheduler(program, provider);
+
+      // Create test member for scheduling
+      schedulerMember = web3.Keypair.generate();
+      schedulingOwner = web3.Keypair.generate();
+
+      // Fund accounts
+      await Promise.all([
+        provider.connection.requestAirdrop(
+          schedulerMember.publicKey,
+          2 * web3.LAMPORTS_PER_SOL
+        ),
+        provider.connection.requestAirdrop(
+          schedulingOwner.publicKey,
+          20 * web3.LAMPORTS_PER_SOL
+        ),
+      ]);
+
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      // Create a separate vault for scheduling tests
+      const configAccount = await program.account.globalConfig.fetch(
+        globalConfig
+      );
+      const nextCompanyId = configAccount.nextCompanyId;
+
+      [schedulingVault] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          schedulingOwner.publicKey.toBuffer(),
+          nextCompanyId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      // Create the scheduling vault
+      await program.methods
+        .createVault(
+          "Scheduling Test Vault",
+          { company: {} },
+          [{ sol: {} }],
+          null,
+          { allocationPerBps: {} },
+          "https://example.com/scheduling-vault.json",
+          null
+        )
+        .accountsStrict({
+          vaultAccount: schedulingVault,
+          globalConfig: globalConfig,
+          payer: schedulingOwner.publicKey,
+          owner: schedulingOwner.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([schedulingOwner])
+        .rpc();
+
+      // Add member to vault
+      [schedulerMemberAccount] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          schedulingVault.toBuffer(),
+          schedulerMember.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .addMember(
+          "ScheduledMember",
+          5000, // 50% allocation
+          null,
+          null,
+          null
+        )
+        .accountsStrict({
+          payer: schedulingOwner.publicKey,
+          vaultAccount: schedulingVault,
+          authority: schedulingOwner.publicKey,
+          delegate: null,
+          member: schedulerMemberAccount,
+          wallet: schedulerMember.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([schedulingOwner])
+        .rpc();
+
+      // Deposit funds to the scheduling vault
+      await program.methods
+        .depositSol(new BN(10 * web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          vaultAccount: schedulingVault,
+          globalConfig: globalConfig,
+          depositor: schedulingOwner.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([schedulingOwner])
+        .rpc();
+
+      console.log("📋 Scheduling test environment initialized");
+    });
+
+    it("Should set up vault payout schedule", async () => {
+      const currentTime = Math.floor(Date.now() / 1000);
+      const payoutSchedule = {
+        interval: new BN(5), // 5 seconds for testing
+        nextPayoutTs: new BN(currentTime + 2), // 2 seconds from now
+        active: true,
+      };
+
+      await program.methods
+        .updatePayoutSchedule(payoutSchedule)
+        .accountsStrict({
+          vaultAccount: schedulingVault,
+          authority: schedulingOwner.publicKey,
+          delegate: null,
+        })
+        .signers([schedulingOwner])
+        .rpc();
+
+      const vault = await program.account.vaultAccount.fetch(schedulingVault);
+      expect(vault.payoutSchedule).to.not.be.null;
+      expect(vault.payoutSchedule.active).to.be.true;
+      expect(vault.payoutSchedule.interval.toNumber()).to.equal(5);
+
+      console.log("✅ Vault payout schedule configured for 5-second intervals");
+    });
+
+    it("Should simulate client-side scheduling with multiple executions", async () => {
+      // Add the vault to scheduler and register owner
+      scheduler.registerVaultOwner(schedulingVault.toString(), schedulingOwner);
+      scheduler.addSchedule(
+        schedulingVault.toString(),
+        3, // Execute every 3 seconds (faster than vault schedule for testing)
+        [schedulerMember.publicKey]
+      );
+
+      // Record initial balance
+      const initialMemberBalance = await provider.connection.getBalance(
+        schedulerMember.publicKey
+      );
+      console.log(
+        `💰 Initial member balance: ${
+          initialMemberBalance / web3.LAMPORTS_PER_SOL
+        } SOL`
+      );
+
+      // Wait for multiple scheduled executions
+      console.log("⏳ Waiting for scheduled payouts to execute...");
+      await new Promise((resolve) => setTimeout(resolve, 12000)); // Wait 12 seconds for multiple executions
+
+      // Check if payouts occurred
+      const finalMemberBalance = await provider.connection.getBalance(
+        schedulerMember.publicKey
+      );
+      console.log(
+        `💰 Final member balance: ${
+          finalMemberBalance / web3.LAMPORTS_PER_SOL
+        } SOL`
+      );
+
+      // Should have received at least one payout
+      expect(finalMemberBalance).to.be.greaterThan(initialMemberBalance);
+
+      const balanceIncrease = finalMemberBalance - initialMemberBalance;
+      const expectedPayouts = Math.floor(
+        balanceIncrease / (0.95 * web3.LAMPORTS_PER_SOL * 0.05)
+      ); // Rough estimate considering fees
+
+      console.log(
+        `📈 Balance increased by ${balanceIncrease / web3.LAMPORTS_PER_SOL} SOL`
+      );
+      console.log(`🔄 Estimated number of payouts: ${expectedPayouts}`);
+
+      // Verify scheduler status
+      const scheduleStatus = scheduler.getScheduleStatus(
+        schedulingVault.toString()
+      );
+      expect(scheduleStatus).to.not.be.undefined;
+      expect(scheduleStatus.isActive).to.be.true;
+    });
+
+    it("Should handle scheduling interruption and resumption", async () => {
+      console.log("🛑 Stopping scheduler...");
+      scheduler.stopSchedule(schedulingVault.toString());
+
+      const initialBalance = await provider.connection.getBalance(
+        schedulerMember.publicKey
+      );
+
+      // Wait a bit to ensure no payouts occur
+      await new Promise((resolve) => setTimeout(resolve, 8000));
+
+      const balanceAfterStop = await provider.connection.getBalance(
+        schedulerMember.publicKey
+      );
+
+      // Balance should not have changed significantly (only minor fluctuations from transaction fees)
+      expect(Math.abs(balanceAfterStop - initialBalance)).to.be.lessThan(
+        0.01 * web3.LAMPORTS_PER_SOL
+      );
+      console.log("✅ No payouts occurred while scheduler was stopped");
+
+      // Resume scheduling
+      console.log("▶️ Resuming scheduler...");
+      scheduler.registerVaultOwner(schedulingVault.toString(), schedulingOwner); // Re-register owner
+      scheduler.addSchedule(
+        schedulingVault.toString(),
+        4, // Different interval
+        [schedulerMember.publicKey]
+      );
+
+      // Wait for execution
+      await new Promise((resolve) => setTimeout(resolve, 8000));
+
+      const balanceAfterResume = await provider.connection.getBalance(
+        schedulerMember.publicKey
+      );
+
+      expect(balanceAfterResume).to.be.greaterThan(balanceAfterStop);
+      console.log("✅ Payouts resumed after scheduler restart");
+    });
+
+    it("Should handle multiple members in scheduled payouts", async () => {
+      // Create additional test members
+      const member2 = web3.Keypair.generate();
+      const member3 = web3.Keypair.generate();
+
+      await Promise.all([
+        provider.connection.requestAirdrop(
+          member2.publicKey,
+          2 * web3.LAMPORTS_PER_SOL
+        ),
+        provider.connection.requestAirdrop(
+          member3.publicKey,
+          2 * web3.LAMPORTS_PER_SOL
+        ),
+      ]);
+
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      // Add members to vault
+      const [member2Account] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          schedulingVault.toBuffer(),
+          member2.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+      const [member3Account] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          schedulingVault.toBuffer(),
+          member3.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .addMember("ScheduledMember2", 2500, null, null, null)
+        .accountsStrict({
+          payer: schedulingOwner.publicKey,
+          vaultAccount: schedulingVault,
+          authority: schedulingOwner.publicKey,
+          delegate: null,
+          member: member2Account,
+          wallet: member2.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([schedulingOwner])
+        .rpc();
+
+      await program.methods
+        .addMember("ScheduledMember3", 2500, null, null, null)
+        .accountsStrict({
+          payer: schedulingOwner.publicKey,
+          vaultAccount: schedulingVault,
+          authority: schedulingOwner.publicKey,
+          delegate: null,
+          member: member3Account,
+          wallet: member3.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([schedulingOwner])
+        .rpc();
+
+      // Stop previous schedule and start multi-member schedule
+      scheduler.stopSchedule(schedulingVault.toString());
+
+      // Add more funds to vault
+      await program.methods
+        .depositSol(new BN(5 * web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          vaultAccount: schedulingVault,
+          globalConfig: globalConfig,
+          depositor: schedulingOwner.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([schedulingOwner])
+        .rpc();
+
+      console.log("👥 Starting multi-member scheduled payouts...");
+      scheduler.registerVaultOwner(schedulingVault.toString(), schedulingOwner); // Re-register owner
+      scheduler.addSchedule(
+        schedulingVault.toString(),
+        6, // 6-second intervals
+        [schedulerMember.publicKey, member2.publicKey, member3.publicKey]
+      );
+
+      // Record initial balances
+      const initialBalances = await Promise.all([
+        provider.connection.getBalance(schedulerMember.publicKey),
+        provider.connection.getBalance(member2.publicKey),
+        provider.connection.getBalance(member3.publicKey),
+      ]);
+
+      // Wait for executions
+      await new Promise((resolve) => setTimeout(resolve, 15000));
+
+      // Check final balances
+      const finalBalances = await Promise.all([
+        provider.connection.getBalance(schedulerMember.publicKey),
+        provider.connection.getBalance(member2.publicKey),
+        provider.connection.getBalance(member3.publicKey),
+      ]);
+
+      // All members should have received payouts
+      for (let i = 0; i < 3; i++) {
+        expect(finalBalances[i]).to.be.greaterThan(initialBalances[i]);
+        console.log(
+          `💰 Member ${i + 1} balance increase: ${
+            (finalBalances[i] - initialBalances[i]) / web3.LAMPORTS_PER_SOL
+          } SOL`
+        );
+      }
+
+      console.log("✅ Multi-member scheduled payouts working correctly");
+    });
+
+    it("Should demonstrate scheduling with error handling", async () => {
+      // Create a member and then deactivate them to test error handling
+      const errorTestMember = web3.Keypair.generate();
+      await provider.connection.requestAirdrop(
+        errorTestMember.publicKey,
+        2 * web3.LAMPORTS_PER_SOL
+      );
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      const [errorMemberAccount] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          schedulingVault.toBuffer(),
+          errorTestMember.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      // Add member
+      await program.methods
+        .addMember("ErrorTestMember", 1000, null, null, null)
+        .accountsStrict({
+          payer: schedulingOwner.publicKey,
+          vaultAccount: schedulingVault,
+          authority: schedulingOwner.publicKey,
+          delegate: null,
+          member: errorMemberAccount,
+          wallet: errorTestMember.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([schedulingOwner])
+        .rpc();
+
+      // Deactivate the member
+      await program.methods
+        .toggleMemberActiveStatus()
+        .accountsStrict({
+          vaultAccount: schedulingVault,
+          member: errorMemberAccount,
+          authority: schedulingOwner.publicKey,
+          delegate: null,
+        })
+        .signers([schedulingOwner])
+        .rpc();
+
+      // Stop current schedule and create one that includes the inactive member
+      scheduler.stopSchedule(schedulingVault.toString());
+
+      console.log(
+        "🚨 Testing scheduler error handling with inactive member..."
+      );
+      scheduler.registerVaultOwner(schedulingVault.toString(), schedulingOwner); // Re-register owner
+      scheduler.addSchedule(
+        schedulingVault.toString(),
+        5,
+        [schedulerMember.publicKey, errorTestMember.publicKey] // Include inactive member
+      );
+
+      // Wait for executions - scheduler should skip inactive member gracefully
+      await new Promise((resolve) => setTimeout(resolve, 12000));
+
+      // The active member should still receive payouts despite the error with the inactive member
+      console.log(
+        "✅ Scheduler handled errors gracefully and continued processing active members"
+      );
+    });
+
+    it("Should demonstrate schedule monitoring and metrics", async () => {
+      console.log("\n📊 SCHEDULING METRICS SUMMARY");
+      console.log("============================");
+
+      // Get current schedule status
+      const status = scheduler.getScheduleStatus(schedulingVault.toString());
+      if (status) {
+        console.log(`⏰ Schedule Interval: ${status.intervalSeconds} seconds`);
+        console.log(
+          `👥 Members in Schedule: ${status.membersToProcess.length}`
+        );
+        console.log(`🟢 Schedule Active: ${status.isActive}`);
+        console.log(
+          `⏭️ Next Run: ${new Date(
+            status.nextRunTime * 1000
+          ).toLocaleTimeString()}`
+        );
+      }
+
+      // Get vault information
+      const vault = await program.account.vaultAccount.fetch(schedulingVault);
+      console.log(
+        `💰 Vault Balance: ${vault.totalBalance.toString()} lamports (${
+          vault.totalBalance.toNumber() / web3.LAMPORTS_PER_SOL
+        } SOL)`
+      );
+
+      if (vault.payoutSchedule) {
+        console.log(
+          `🏦 Vault Schedule Interval: ${vault.payoutSchedule.interval} seconds`
+        );
+        console.log(
+          `🏦 Vault Next Payout: ${new Date(
+            vault.payoutSchedule.nextPayoutTs.toNumber() * 1000
+          ).toLocaleTimeString()}`
+        );
+        console.log(`🏦 Vault Schedule Active: ${vault.payoutSchedule.active}`);
+      }
+
+      console.log("============================\n");
+
+      // Verify we can read the schedule status
+      expect(status).to.not.be.undefined;
+      if (status) {
+        expect(status.intervalSeconds).to.be.greaterThan(0);
+        expect(status.membersToProcess.length).to.be.greaterThan(0);
+      }
+    });
+
+    after(async () => {
+      // Cleanup: Stop all schedulers
+      console.log("🧹 Cleaning up schedulers...");
+      scheduler.stopAll();
+
+      // Wait a moment to ensure cleanup
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      console.log("✅ Scheduling simulation tests completed");
+    });
+  });
+
+  describe("Claim Codes", () => {
+    const CLAIM_CODE = "TESTCODE-7F3A9C21E4B5";
+    const CODE_AMOUNT = new BN(web3.LAMPORTS_PER_SOL / 10);
+
+    const claimWithCode = (code: string, claimant: web3.Keypair) =>
+      program.methods
+        .claimWithCode(code)
+        .accountsStrict({
+          claimant: claimant.publicKey,
+          vaultAccount: vaultAccount,
+          redemption: redemptionFor(vaultAccount, code, claimant.publicKey),
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([claimant])
+        .rpc();
+
+    const closeRedemption = (code: string, claimant: web3.Keypair) =>
+      program.methods
+        .closeRedemption()
+        .accountsStrict({
+          redemption: redemptionFor(vaultAccount, code, claimant.publicKey),
+          vaultAccount: vaultAccount,
+          claimant: claimant.publicKey,
+        })
+        .signers([claimant])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .depositSol(new BN(web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          depositor: vaultOwner.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+    });
+
+    it("Should store only the hash of the claim code", async () => {
+      const vault = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(Buffer.from(vault.codeClaim.codeHash).toString("hex")).to.equal(
+        Buffer.from(claimCodeHash(CLAIM_CODE, vaultAccount)).toString("hex")
+      );
+      expect(vault.codeClaim.maxUses).to.equal(2);
+      expect(vault.codeClaim.uses).to.equal(0);
+    });
+
+    it("Should reject a wrong code", async () => {
+      try {
+        await claimWithCode("WRONGCODE-7F3A9C21E4B5", member1);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidClaimCode");
+      }
+    });
+
+    it("Should reject a code shorter than the minimum length", async () => {
+      try {
+        await claimWithCode("TESTCODE", member1);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ClaimCodeTooShort");
+      }
+    });
+
+    it("Should pay the claimant for a valid code", async () => {
+      const serviceFee = CODE_AMOUNT.mul(new BN(DEFAULT_FEE_BPS)).div(
+        new BN(10000)
+      );
+      const redemption = redemptionFor(
+        vaultAccount,
+        CLAIM_CODE,
+        member3.publicKey
+      );
+      const vaultBefore = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+      const treasuryBefore = await provider.connection.getBalance(
+        treasury.publicKey
+      );
+
+      await claimWithCode(CLAIM_CODE, member3);
+
+      const vaultAfter = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(vaultAfter.codeClaim.uses).to.equal(1);
+      expect(vaultAfter.totalBalance.toString()).to.equal(
+        vaultBefore.totalBalance.sub(CODE_AMOUNT).toString()
+      );
+
+      const treasuryAfter = await provider.connection.getBalance(
+        treasury.publicKey
+      );
+      expect(treasuryAfter - treasuryBefore).to.equal(serviceFee.toNumber());
+
+      const redemptionData = await program.account.codeRedemption.fetch(
+        redemption
+      );
+      expect(redemptionData.claimant.toString()).to.equal(
+        member3.publicKey.toString()
+      );
+      expect(redemptionData.amount.toString()).to.equal(CODE_AMOUNT.toString());
+    });
+
+    it("Should not let the same wallet redeem a code twice", async () => {
+      try {
+        await claimWithCode(CLAIM_CODE, member3);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("already in use");
+      }
+    });
+
+    it("Should keep a redemption while its code can still be redeemed", async () => {
+      try {
+        await closeRedemption(CLAIM_CODE, member3);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ClaimCodeStillRedeemable");
+      }
+    });
+
+    it("Should stop redemptions once the use limit is reached", async () => {
+      await claimWithCode(CLAIM_CODE, member1);
+
+      try {
+        await claimWithCode(CLAIM_CODE, member2);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ClaimCodeExhausted");
+      }
+    });
+
+    it("Should refund a redemption once its code is used up", async () => {
+      const redemption = redemptionFor(
+        vaultAccount,
+        CLAIM_CODE,
+        member3.publicKey
+      );
+      await closeRedemption(CLAIM_CODE, member3);
+      expect(await provider.connection.getAccountInfo(redemption)).to.be.null;
+    });
+
+    it("Should reject setting an already expired code", async () => {
+      try {
+        await program.methods
+          .setClaimCode({
+            codeHash: claimCodeHash("EXPIRED", vaultAccount),
+            amount: CODE_AMOUNT,
+            assetType: { sol: {} },
+            expiresAt: new BN(Math.floor(Date.now() / 1000) - 60),
+            maxUses: 1,
+            uses: 0,
+          })
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            owner: vaultOwner.publicKey,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ClaimCodeExpired");
+      }
+    });
+
+    it("Should pay a non-member through a freshly generated single-use code", async () => {
+      const code = randomBytes(16).toString("hex");
+      const claimer = web3.Keypair.generate();
+      const secondClaimer = web3.Keypair.generate();
+      await Promise.all([
+        provider.connection.requestAirdrop(
+          claimer.publicKey,
+          web3.LAMPORTS_PER_SOL
+        ),
+        provider.connection.requestAirdrop(
+          secondClaimer.publicKey,
+          web3.LAMPORTS_PER_SOL
+        ),
+      ]);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      await program.methods
+        .setClaimCode({
+          codeHash: claimCodeHash(code, vaultAccount),
+          amount: CODE_AMOUNT,
+          assetType: { sol: {} },
+          expiresAt: new BN(Math.floor(Date.now() / 1000) + 60 * 60),
+          maxUses: 1,
+          uses: 0,
+        })
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          approvalConfig: approvalConfigFor(vaultAccount),
+          owner: vaultOwner.publicKey,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const serviceFee = CODE_AMOUNT.mul(new BN(DEFAULT_FEE_BPS)).div(
+        new BN(10000)
+      );
+      const claimerBefore = await provider.connection.getBalance(
+        claimer.publicKey
+      );
+
+      await claimWithCode(code, claimer);
+
+      // The claimer also pays the redemption rent and the transaction fee
+      const redemptionRent = await provider.connection.getBalance(
+        redemptionFor(vaultAccount, code, claimer.publicKey)
+      );
+      const claimerAfter = await provider.connection.getBalance(
+        claimer.publicKey
+      );
+      expect(claimerAfter).to.be.greaterThan(
+        claimerBefore +
+          CODE_AMOUNT.sub(serviceFee).toNumber() -
+          redemptionRent -
+          10000
+      );
+
+      try {
+        await claimWithCode(code, secondClaimer);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ClaimCodeExhausted");
+      }
+    });
+
+    it("Should disable code claims when the code is cleared", async () => {
+      await program.methods
+        .setClaimCode(null)
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          approvalConfig: approvalConfigFor(vaultAccount),
+          owner: vaultOwner.publicKey,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const vault = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(vault.codeClaim).to.be.null;
+
+      try {
+        await claimWithCode(CLAIM_CODE, member2);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidClaimCode");
+      }
+    });
+  });
+
+  describe("Token Vesting", () => {
+    const VESTING_DURATION = new BN(10);
+    let member1Account: web3.PublicKey;
+    let vesting: web3.PublicKey;
+    let vaultTokenAccount: web3.PublicKey;
+
+    const claimVested = () =>
+      program.methods
+        .claimVested()
+        .accountsStrict({
+          wallet: member1.publicKey,
+          vaultAccount: vaultAccount,
+          vesting: vesting,
+          globalConfig: globalConfig,
+          mint: testMint,
+          vaultTokenAccount: vaultTokenAccount,
+          treasury: treasury.publicKey,
+          treasuryTokenAccount: getAssociatedTokenAddressSync(
+            testMint,
+            treasury.publicKey
+          ),
+          walletTokenAccount: getAssociatedTokenAddressSync(
+            testMint,
+            member1.publicKey
+          ),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([member1])
+        .rpc();
+
+    before(async () => {
+      [member1Account] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          member1.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+      [vesting] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("vesting"), member1Account.toBuffer()],
+        program.programId
+      );
+      vaultTokenAccount = getAssociatedTokenAddressSync(
+        testMint,
+        vaultAccount,
+        true
+      );
+    });
+
+    it("Should reject vesting in a mint the vault has not whitelisted", async () => {
+      const otherMint = await createMint(
+        provider.connection,
+        vaultOwner,
+        vaultOwner.publicKey,
+        null,
+        6
+      );
+      const otherVaultTokenAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        vaultOwner,
+        otherMint,
+        vaultAccount,
+        undefined,
+        TOKEN_PROGRAM_ID,
+        ASSOCIATED_TOKEN_PROGRAM_ID,
+        true
+      );
+      const now = new BN(Math.floor(Date.now() / 1000));
+
+      try {
+        await program.methods
+          .createVesting(now, now, VESTING_DURATION, true)
+          .accountsStrict({
+            payer: vaultOwner.publicKey,
+            vaultAccount: vaultAccount,
+            member: member1Account,
+            globalConfig: globalConfig,
+            vesting: vesting,
+            mint: otherMint,
+            vaultTokenAccount: otherVaultTokenAccount,
+            authority: vaultOwner.publicKey,
+            delegate: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("AssetNotWhitelisted");
+      }
+    });
+
+    it("Should create a vesting schedule from the member SPL allocation", async () => {
+      const now = new BN(Math.floor(Date.now() / 1000));
+      const vaultBefore = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+
+      await program.methods
+        .createVesting(now, now, VESTING_DURATION, true)
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          member: member1Account,
+          globalConfig: globalConfig,
+          vesting: vesting,
+          mint: testMint,
+          vaultTokenAccount: vaultTokenAccount,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const member = await program.account.member.fetch(member1Account);
+      const vestingData = await program.account.vestingSchedule.fetch(vesting);
+      expect(vestingData.totalAmount.toString()).to.equal(
+        member.splTokenAllocation.toString()
+      );
+
+      const vaultAfter = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(splReserved(vaultAfter, testMint).toString()).to.equal(
+        splReserved(vaultBefore, testMint)
+          .add(vestingData.totalAmount)
+          .toString()
+      );
+    });
+
+    it("Should let the member claim vested tokens", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      await claimVested();
+
+      const vestingData = await program.account.vestingSchedule.fetch(vesting);
+      expect(vestingData.claimed.toNumber()).to.be.greaterThan(0);
+      expect(vestingData.claimed.lt(vestingData.totalAmount)).to.be.true;
+    });
+
+    it("Should release unvested tokens on revoke", async () => {
+      const vaultBefore = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+      const vestingBefore = await program.account.vestingSchedule.fetch(
+        vesting
+      );
+
+      await program.methods
+        .revokeVesting()
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          vesting: vesting,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const vestingAfter = await program.account.vestingSchedule.fetch(vesting);
+      expect(vestingAfter.revoked).to.be.true;
+
+      const released = vestingBefore.totalAmount.sub(vestingAfter.totalAmount);
+      const vaultAfter = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(splReserved(vaultAfter, testMint).toString()).to.equal(
+        splReserved(vaultBefore, testMint).sub(released).toString()
+      );
+    });
+
+    it("Should only pay out what vested before revocation", async () => {
+      const vestingData = await program.account.vestingSchedule.fetch(vesting);
+      if (vestingData.totalAmount.gt(vestingData.claimed)) {
+        await claimVested();
+      }
+
+      try {
+        await claimVested();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("NothingToWithdraw");
+      }
+    });
+
+    it("Should close the fully claimed schedule and refund its payer", async () => {
+      const vestingRent = await provider.connection.getBalance(vesting);
+      const payerBefore = await provider.connection.getBalance(
+        vaultOwner.publicKey
+      );
+
+      await program.methods
+        .closeVesting()
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          vesting: vesting,
+          payer: vaultOwner.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const vestingInfo = await provider.connection.getAccountInfo(vesting);
+      expect(vestingInfo).to.be.null;
+
+      // The payer also signs, so it pays the transaction fee
+      const payerAfter = await provider.connection.getBalance(
+        vaultOwner.publicKey
+      );
+      expect(payerAfter).to.be.greaterThan(payerBefore + vestingRent - 10000);
+    });
+  });
+
+  describe("Vault Ownership Transfer", () => {
+    const originalOwner = web3.Keypair.generate();
+    const nextOwner = web3.Keypair.generate();
+    let transferVault: web3.PublicKey;
+
+    const proposeOwner = (newOwner: web3.PublicKey) =>
+      program.methods
+        .proposeVaultOwner(newOwner)
+        .accountsStrict({
+          vaultAccount: transferVault,
+          owner: originalOwner.publicKey,
+        })
+        .signers([originalOwner])
+        .rpc();
+
+    const acceptOwner = (signer: web3.Keypair) =>
+      program.methods
+        .acceptVaultOwner()
+        .accountsStrict({
+          vaultAccount: transferVault,
+          newOwner: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      await Promise.all([
+        provider.connection.requestAirdrop(
+          originalOwner.publicKey,
+          3 * web3.LAMPORTS_PER_SOL
+        ),
+        provider.connection.requestAirdrop(
+          nextOwner.publicKey,
+          web3.LAMPORTS_PER_SOL
+        ),
+      ]);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      const configAccount = await program.account.globalConfig.fetch(
+        globalConfig
+      );
+      [transferVault] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          originalOwner.publicKey.toBuffer(),
+          configAccount.nextCompanyId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createVault(
+          "Transfer Vault",
+          { company: {} },
+          [{ sol: {} }],
+          null,
+          { allocationPerBps: {} },
+          null,
+          null
+        )
+        .accountsStrict({
+          vaultAccount: transferVault,
+          globalConfig: globalConfig,
+          payer: originalOwner.publicKey,
+          owner: originalOwner.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([originalOwner])
+        .rpc();
+
+      await program.methods
+        .depositSol(new BN(web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          vaultAccount: transferVault,
+          globalConfig: globalConfig,
+          depositor: originalOwner.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([originalOwner])
+        .rpc();
+    });
+
+    it("Should cancel a pending ownership proposal", async () => {
+      await proposeOwner(nextOwner.publicKey);
+
+      let vault = await program.account.vaultAccount.fetch(transferVault);
+      expect(vault.pendingOwner.toString()).to.equal(
+        nextOwner.publicKey.toString()
+      );
+
+      await program.methods
+        .cancelVaultOwnerProposal()
+        .accountsStrict({
+          vaultAccount: transferVault,
+          owner: originalOwner.publicKey,
+        })
+        .signers([originalOwner])
+        .rpc();
+
+      vault = await program.account.vaultAccount.fetch(transferVault);
+      expect(vault.pendingOwner).to.be.null;
+
+      try {
+        await acceptOwner(nextOwner);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("NotPendingOwner");
+      }
+    });
+
+    it("Should only let the proposed owner accept", async () => {
+      await proposeOwner(nextOwner.publicKey);
+
+      try {
+        await acceptOwner(member1);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("NotPendingOwner");
+      }
+    });
+
+    it("Should hand the vault over without moving its address", async () => {
+      await acceptOwner(nextOwner);
+
+      const vault = await program.account.vaultAccount.fetch(transferVault);
+      expect(vault.owner.toString()).to.equal(nextOwner.publicKey.toString());
+      expect(vault.creator.toString()).to.equal(
+        originalOwner.publicKey.toString()
+      );
+      expect(vault.pendingOwner).to.be.null;
+
+      // The new owner can operate the vault at the same address
+      await program.methods
+        .withdrawSol(new BN(web3.LAMPORTS_PER_SOL / 10))
+        .accountsStrict({
+          vaultAccount: transferVault,
+          approvalConfig: approvalConfigFor(transferVault),
+          owner: nextOwner.publicKey,
+          authority: nextOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([nextOwner])
+        .rpc();
+    });
+
+    it("Should lock out the previous owner", async () => {
+      try {
+        await proposeOwner(originalOwner.publicKey);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ConstraintHasOne");
+      }
+    });
+  });
+
+  describe("Batch Approvals", () => {
+    const approvalOwner = web3.Keypair.generate();
+    const approverA = web3.Keypair.generate();
+    const approverB = web3.Keypair.generate();
+    const approverC = web3.Keypair.generate();
+    const payee = web3.Keypair.generate();
+    let approvalVault: web3.PublicKey;
+    let approvalConfig: web3.PublicKey;
+    let payeeMember: web3.PublicKey;
+
+    const batchFor = (id: number) =>
+      web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          approvalVault.toBuffer(),
+          new BN(id).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+
+    const createBatch = (id: number) =>
+      program.methods
+        .createPayrollBatch(new BN(id), new BN(web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          payer: approvalOwner.publicKey,
+          vaultAccount: approvalVault,
+          globalConfig: globalConfig,
+          payrollBatch: batchFor(id),
+          authority: approvalOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([approvalOwner])
+        .rpc();
+
+    const approveBatch = (id: number, approver: web3.Keypair) =>
+      program.methods
+        .approveBatch()
+        .accountsStrict({
+          approver: approver.publicKey,
+          vaultAccount: approvalVault,
+          approvalConfig: approvalConfig,
+          payrollBatch: batchFor(id),
+        })
+        .signers([approver])
+        .rpc();
+
+    const approveChange = (approver: web3.Keypair) =>
+      program.methods
+        .approveApproverChange()
+        .accountsStrict({
+          approver: approver.publicKey,
+          vaultAccount: approvalVault,
+          approvalConfig: approvalConfig,
+        })
+        .signers([approver])
+        .rpc();
+
+    const payOut = (id: number) =>
+      program.methods
+        .processSolPayout(new BN(web3.LAMPORTS_PER_SOL / 10))
+        .accountsStrict({
+          payer: approvalOwner.publicKey,
+          vaultAccount: approvalVault,
+          payrollBatch: batchFor(id),
+          approvalConfig: approvalConfigFor(approvalVault),
+          member: payeeMember,
+          receipt: receiptFor(batchFor(id), payeeMember),
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          memberWallet: payee.publicKey,
+          authority: approvalOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([approvalOwner])
+        .rpc();
+
+    before(async () => {
+      await Promise.all(
+        [approvalOwner, approverA, approverB, approverC].map((keypair) =>
+          provider.connection.requestAirdrop(
+            keypair.publicKey,
+            3 * web3.LAMPORTS_PER_SOL
+          )
+        )
+      );
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      const configAccount = await program.account.globalConfig.fetch(
+        globalConfig
+      );
+      [approvalVault] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          approvalOwner.publicKey.toBuffer(),
+          configAccount.nextCompanyId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      [approvalConfig] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("approval"), approvalVault.toBuffer()],
+        program.programId
+      );
+      [payeeMember] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          approvalVault.toBuffer(),
+          payee.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createVault(
+          "Approval Vault",
+          { company: {} },
+          [{ sol: {} }],
+          null,
+          { allocationPerBps: {} },
+          null,
+          null
+        )
+        .accountsStrict({
+          vaultAccount: approvalVault,
+          globalConfig: globalConfig,
+          payer: approvalOwner.publicKey,
+          owner: approvalOwner.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([approvalOwner])
+        .rpc();
+
+      await program.methods
+        .depositSol(new BN(2 * web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          vaultAccount: approvalVault,
+          globalConfig: globalConfig,
+          depositor: approvalOwner.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([approvalOwner])
+        .rpc();
+
+      await program.methods
+        .addMember("Contractor", 10000, null, null, null)
+        .accountsStrict({
+          payer: approvalOwner.publicKey,
+          vaultAccount: approvalVault,
+          authority: approvalOwner.publicKey,
+          delegate: null,
+          member: payeeMember,
+          wallet: payee.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([approvalOwner])
+        .rpc();
+    });
+
+    it("Should reject a threshold above the approver count", async () => {
+      try {
+        await program.methods
+          .initializeApprovers([approverA.publicKey], 2)
+          .accountsStrict({
+            payer: approvalOwner.publicKey,
+            vaultAccount: approvalVault,
+            approvalConfig: approvalConfig,
+            owner: approvalOwner.publicKey,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([approvalOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidApproverConfig");
+      }
+    });
+
+    it("Should set up 2-of-3 approvers", async () => {
+      await program.methods
+        .initializeApprovers(
+          [approverA.publicKey, approverB.publicKey, approverC.publicKey],
+          2
+        )
+        .accountsStrict({
+          payer: approvalOwner.publicKey,
+          vaultAccount: approvalVault,
+          approvalConfig: approvalConfig,
+          owner: approvalOwner.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([approvalOwner])
+        .rpc();
+
+      const config = await program.account.approvalConfig.fetch(
+        approvalConfig
+      );
+      expect(config.threshold).to.equal(2);
+      expect(config.version).to.equal(1);
+    });
+
+    it("Should block payouts until the threshold is met", async () => {
+      await createBatch(1);
+      await approveBatch(1, approverA);
+
+      try {
+        await payOut(1);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InsufficientApprovals");
+      }
+
+      try {
+        await approveBatch(1, approverA);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("AlreadyApproved");
+      }
+
+      try {
+        await approveBatch(1, payee);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("NotAnApprover");
+      }
+
+      await approveBatch(1, approverB);
+      await payOut(1);
+
+      const batch = await program.account.payrollBatch.fetch(batchFor(1));
+      expect(batch.payoutCount).to.equal(1);
+    });
+
+    it("Should apply an approver change only once approved", async () => {
+      await createBatch(2);
+      await approveBatch(2, approverA);
+      await approveBatch(2, approverB);
+
+      // Drop approver C and require both remaining approvers
+      await program.methods
+        .proposeApproverChange([approverA.publicKey, approverB.publicKey], 2)
+        .accountsStrict({
+          vaultAccount: approvalVault,
+          approvalConfig: approvalConfig,
+          owner: approvalOwner.publicKey,
+        })
+        .signers([approvalOwner])
+        .rpc();
+
+      await approveChange(approverC);
+      let config = await program.account.approvalConfig.fetch(approvalConfig);
+      expect(config.approvers.length).to.equal(3);
+      expect(config.pendingChange).to.not.be.null;
+
+      await approveChange(approverA);
+      config = await program.account.approvalConfig.fetch(approvalConfig);
+      expect(config.approvers.length).to.equal(2);
+      expect(config.version).to.equal(2);
+      expect(config.pendingChange).to.be.null;
+
+      // Approvals collected under the old set no longer count
+      try {
+        await payOut(2);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InsufficientApprovals");
+      }
+
+      try {
+        await approveBatch(2, approverC);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("NotAnApprover");
+      }
+
+      await approveBatch(2, approverA);
+      await approveBatch(2, approverB);
+      await payOut(2);
+    });
+
+    it("Should hold owner withdrawals until approvers sign off", async () => {
+      const amount = new BN(web3.LAMPORTS_PER_SOL / 100);
+      const withdraw = () =>
+        program.methods
+          .withdrawSol(amount)
+          .accountsStrict({
+            vaultAccount: approvalVault,
+            approvalConfig: approvalConfig,
+            owner: approvalOwner.publicKey,
+            authority: approvalOwner.publicKey,
+            delegate: null,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([approvalOwner])
+          .rpc();
+      const approveAction = (approver: web3.Keypair) =>
+        program.methods
+          .approveOwnerAction()
+          .accountsStrict({
+            approver: approver.publicKey,
+            vaultAccount: approvalVault,
+            approvalConfig: approvalConfig,
+          })
+          .signers([approver])
+          .rpc();
+
+      try {
+        await withdraw();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("OwnerActionNotApproved");
+      }
+
+      await program.methods
+        .proposeOwnerAction({ withdrawSol: { amount } })
+        .accountsStrict({
+          vaultAccount: approvalVault,
+          approvalConfig: approvalConfig,
+          owner: approvalOwner.publicKey,
+        })
+        .signers([approvalOwner])
+        .rpc();
+
+      await approveAction(approverA);
+      try {
+        await withdraw();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("OwnerActionNotApproved");
+      }
+
+      await approveAction(approverB);
+      const vaultBefore = await program.account.vaultAccount.fetch(
+        approvalVault
+      );
+      await withdraw();
+      const vaultAfter = await program.account.vaultAccount.fetch(
+        approvalVault
+      );
+      expect(vaultAfter.totalBalance.toString()).to.equal(
+        vaultBefore.totalBalance.sub(amount).toString()
+      );
+
+      // Each approval covers a single withdrawal
+      const config = await program.account.approvalConfig.fetch(
+        approvalConfig
+      );
+      expect(config.pendingAction).to.be.null;
+      try {
+        await withdraw();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("OwnerActionNotApproved");
+      }
+    });
+  });
 
-              // Check if member exists and is active
-              let memberData;
-              try {
-                memberData = await this.program.account.member.fetch(
-                  memberAccount
-                );
-              } catch (error) {
-                console.log(
-                  `⚠️ Member ${memberWallet
-                    .toString()
-                    .substring(0, 8)}... not found, skipping`
-                );
-                failedPayouts++;
-                continue;
-              }
+  describe("Vault Delegates", () => {
+    const clerk = web3.Keypair.generate();
+    const clerkBatchId = new BN(103);
+    // MANAGE_MEMBERS = 1, CREATE_BATCHES = 2, EXECUTE_PAYOUTS = 4, UPDATE_SCHEDULE = 8, WITHDRAW = 16
+    const CLERK_PERMISSIONS = 2 | 4;
+    let clerkDelegate: web3.PublicKey;
+    let clerkBatch: web3.PublicKey;
+
+    const setDelegate = (permissions: number) =>
+      program.methods
+        .setDelegate(permissions)
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          owner: vaultOwner.publicKey,
+          delegate: clerkDelegate,
+          delegateWallet: clerk.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
 
-              if (!memberData.isActive) {
-                console.log(
-                  `⚠️ Member ${memberWallet
-                    .toString()
-                    .substring(0, 8)}... is inactive, skipping`
-                );
-                failedPayouts++;
-                continue;
-              }
+    before(async () => {
+      await provider.connection.requestAirdrop(
+        clerk.publicKey,
+        web3.LAMPORTS_PER_SOL
+      );
+      await new Promise((resolve) => setTimeout(resolve, 1000));
 
-              // Get global config and treasury
-              const [globalConfig] = web3.PublicKey.findProgramAddressSync(
-                [Buffer.from("global_config")],
-                this.program.programId
-              );
+      [clerkDelegate] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("delegate"),
+          vaultAccount.toBuffer(),
+          clerk.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+      [clerkBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          clerkBatchId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+    });
 
-              const globalConfigData =
-                await this.program.account.globalConfig.fetch(globalConfig);
+    it("Should reject unknown permission bits", async () => {
+      try {
+        await setDelegate(1 << 5);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidDelegatePermissions");
+      }
+    });
 
-              // Process the scheduled payout with proper signer
-              await this.program.methods
-                .processScheduledPayout()
-                .accountsStrict({
-                  vaultAccount: vaultPublicKey,
-                  member: memberAccount,
-                  globalConfig: globalConfig,
-                  treasury: globalConfigData.treasury,
-                  memberWallet: memberWallet,
-                  owner: vault.owner,
-                  systemProgram: web3.SystemProgram.programId,
-                })
-                .signers([vaultOwner]) // Add the vault owner as signer
-                .rpc();
+    it("Should let a clerk create and finalize batches", async () => {
+      await setDelegate(CLERK_PERMISSIONS);
 
-              console.log(
-                `✅ Scheduled payout successful for member ${memberWallet
-                  .toString()
-                  .substring(0, 8)}...`
-              );
-              successfulPayouts++;
-            } catch (error) {
-              console.log(
-                `❌ Scheduled payout failed for member ${memberWallet
-                  .toString()
-                  .substring(0, 8)}...: ${error.message}`
-              );
-              failedPayouts++;
-            }
-          }
+      const delegate = await program.account.vaultDelegate.fetch(clerkDelegate);
+      expect(delegate.permissions).to.equal(CLERK_PERMISSIONS);
+      expect(delegate.grantedBy.toString()).to.equal(
+        vaultOwner.publicKey.toString()
+      );
 
-          console.log(
-            `📊 Scheduled payout summary: ${successfulPayouts} successful, ${failedPayouts} failed`
-          );
-        } catch (error) {
-          console.log(
-            `❌ Scheduled execution error for vault ${vaultId.substring(
-              0,
-              8
-            )}...: ${error.message}`
-          );
-        }
+      await program.methods
+        .createPayrollBatch(clerkBatchId, new BN(web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          payer: clerk.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          payrollBatch: clerkBatch,
+          authority: clerk.publicKey,
+          delegate: clerkDelegate,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([clerk])
+        .rpc();
+
+      await program.methods
+        .finalizePayrollBatch()
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          payrollBatch: clerkBatch,
+          authority: clerk.publicKey,
+          delegate: clerkDelegate,
+        })
+        .signers([clerk])
+        .rpc();
+
+      const batch = await program.account.payrollBatch.fetch(clerkBatch);
+      expect(batch.finalized).to.be.true;
+    });
+
+    it("Should keep a clerk out of schedule and withdrawals", async () => {
+      try {
+        await program.methods
+          .updatePayoutSchedule(null)
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            authority: clerk.publicKey,
+            delegate: clerkDelegate,
+          })
+          .signers([clerk])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+
+      try {
+        await program.methods
+          .withdrawSol(new BN(1))
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            approvalConfig: approvalConfigFor(vaultAccount),
+            owner: vaultOwner.publicKey,
+            authority: clerk.publicKey,
+            delegate: clerkDelegate,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([clerk])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Should revoke a delegate", async () => {
+      await program.methods
+        .removeDelegate()
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          delegate: clerkDelegate,
+          owner: vaultOwner.publicKey,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+      const [nextBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          new BN(104).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .createPayrollBatch(new BN(104), new BN(web3.LAMPORTS_PER_SOL))
+          .accountsStrict({
+            payer: clerk.publicKey,
+            vaultAccount: vaultAccount,
+            globalConfig: globalConfig,
+            payrollBatch: nextBatch,
+            authority: clerk.publicKey,
+            delegate: null,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([clerk])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+  });
+
+  describe("Emergency Pause", () => {
+    const pauseBatchId = new BN(105);
+    let pauseBatch: web3.PublicKey;
+    let member1Account: web3.PublicKey;
+
+    const setProtocolPaused = (paused: boolean) =>
+      program.methods
+        .setProtocolPaused(paused)
+        .accountsStrict({
+          globalConfig: globalConfig,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+    const setVaultPaused = (paused: boolean) =>
+      program.methods
+        .setVaultPaused(paused)
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          owner: vaultOwner.publicKey,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+    const payMember1 = () =>
+      program.methods
+        .processSolPayout(new BN(web3.LAMPORTS_PER_SOL / 100))
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          payrollBatch: pauseBatch,
+          approvalConfig: approvalConfigFor(vaultAccount),
+          member: member1Account,
+          receipt: receiptFor(pauseBatch, member1Account),
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          memberWallet: member1.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+
+    before(async () => {
+      [member1Account] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          vaultAccount.toBuffer(),
+          member1.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+      [pauseBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          pauseBatchId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createPayrollBatch(pauseBatchId, new BN(web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          payrollBatch: pauseBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc();
+    });
+
+    after(async () => {
+      await program.methods
+        .finalizePayrollBatch()
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          payrollBatch: pauseBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+        })
+        .signers([vaultOwner])
+        .rpc();
+    });
+
+    it("Should block payouts and deposits while the protocol is paused", async () => {
+      await setProtocolPaused(true);
+
+      try {
+        await payMember1();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ProtocolPaused");
+      }
+
+      try {
+        await program.methods
+          .depositSol(new BN(web3.LAMPORTS_PER_SOL / 100))
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            globalConfig: globalConfig,
+            depositor: vaultOwner.publicKey,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ProtocolPaused");
       }
 
-      // Stop a schedule
-      stopSchedule(vaultId: string): void {
-        const timer = this.timers.get(vaultId);
-        if (timer) {
-          clearTimeout(timer);
-          this.timers.delete(vaultId);
-        }
-
-        const schedule = this.schedules.get(vaultId);
-        if (schedule) {
-          schedule.isActive = false;
-        }
+      await setProtocolPaused(false);
+    });
 
-        console.log(
-          `🛑 Schedule stopped for vault ${vaultId.substring(0, 8)}...`
-        );
+    it("Should only let the config owner pause the protocol", async () => {
+      try {
+        await program.methods
+          .setProtocolPaused(true)
+          .accountsStrict({
+            globalConfig: globalConfig,
+            owner: vaultOwner.publicKey,
+          })
+          .signers([vaultOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ConstraintHasOne");
       }
+    });
 
-      // Get schedule status
-      getScheduleStatus(vaultId: string): ScheduleConfig | undefined {
-        return this.schedules.get(vaultId);
-      }
+    it("Should block payouts from a paused vault", async () => {
+      await setVaultPaused(true);
 
-      // Stop all schedules (cleanup)
-      stopAll(): void {
-        for (const vaultId of this.timers.keys()) {
-          this.stopSchedule(vaultId);
-        }
-        console.log("🧹 All schedules stopped");
+      try {
+        await payMember1();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("VaultPaused");
       }
-    }
 
-    // Initialize scheduler
-    let scheduler: PayrollScheduler;
+      await setVaultPaused(false);
+      await payMember1();
 
-    before(async () => {
-      // Create scheduler instance
-      scheduler = new PayrollScheduler(program, provider);
+      const batch = await program.account.payrollBatch.fetch(pauseBatch);
+      expect(batch.payoutCount).to.equal(1);
+    });
+  });
 
-      // Create test member for scheduling
-      schedulerMember = web3.Keypair.generate();
-      schedulingOwner = web3.Keypair.generate();
+  describe("Fee Overrides and Tiers", () => {
+    const feeOwner = web3.Keypair.generate();
+    const payee = web3.Keypair.generate();
+    const PAYOUT = new BN(web3.LAMPORTS_PER_SOL / 10);
+    let feeVault: web3.PublicKey;
+    let payeeMember: web3.PublicKey;
 
-      // Fund accounts
-      await Promise.all([
-        provider.connection.requestAirdrop(
-          schedulerMember.publicKey,
-          2 * web3.LAMPORTS_PER_SOL
-        ),
-        provider.connection.requestAirdrop(
-          schedulingOwner.publicKey,
-          20 * web3.LAMPORTS_PER_SOL
-        ),
-      ]);
+    const batchFor = (id: number) =>
+      web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          feeVault.toBuffer(),
+          new BN(id).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
 
-      await new Promise((resolve) => setTimeout(resolve, 2000));
+    // Creates a batch, pays `PAYOUT` from it and returns the fee charged
+    const payOut = async (id: number) => {
+      await program.methods
+        .createPayrollBatch(new BN(id), PAYOUT)
+        .accountsStrict({
+          payer: feeOwner.publicKey,
+          vaultAccount: feeVault,
+          globalConfig: globalConfig,
+          payrollBatch: batchFor(id),
+          authority: feeOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([feeOwner])
+        .rpc();
+
+      await program.methods
+        .processSolPayout(PAYOUT)
+        .accountsStrict({
+          payer: feeOwner.publicKey,
+          vaultAccount: feeVault,
+          payrollBatch: batchFor(id),
+          approvalConfig: approvalConfigFor(feeVault),
+          member: payeeMember,
+          receipt: receiptFor(batchFor(id), payeeMember),
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          memberWallet: payee.publicKey,
+          authority: feeOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([feeOwner])
+        .rpc();
+
+      const receipt = await program.account.payoutReceipt.fetch(
+        receiptFor(batchFor(id), payeeMember)
+      );
+      return receipt.serviceFee;
+    };
+
+    const setOverride = (feeBps: number | null) =>
+      program.methods
+        .setVaultFeeOverride(feeBps)
+        .accountsStrict({
+          globalConfig: globalConfig,
+          vaultAccount: feeVault,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+    const setTiers = (tiers: { minVolume: BN; feeBps: number }[]) =>
+      program.methods
+        .setFeeTiers(tiers)
+        .accountsStrict({
+          globalConfig: globalConfig,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+    const feeAt = (bps: number) => PAYOUT.mul(new BN(bps)).div(new BN(10000));
+
+    before(async () => {
+      await provider.connection.requestAirdrop(
+        feeOwner.publicKey,
+        3 * web3.LAMPORTS_PER_SOL
+      );
+      await new Promise((resolve) => setTimeout(resolve, 1000));
 
-      // Create a separate vault for scheduling tests
       const configAccount = await program.account.globalConfig.fetch(
         globalConfig
       );
-      const nextCompanyId = configAccount.nextCompanyId;
-
-      [schedulingVault] = web3.PublicKey.findProgramAddressSync(
+      [feeVault] = web3.PublicKey.findProgramAddressSync(
         [
           Buffer.from("vault"),
-          schedulingOwner.publicKey.toBuffer(),
-          nextCompanyId.toArrayLike(Buffer, "le", 8),
+          feeOwner.publicKey.toBuffer(),
+          configAccount.nextCompanyId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      [payeeMember] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("member"),
+          feeVault.toBuffer(),
+          payee.publicKey.toBuffer(),
         ],
         program.programId
       );
 
-      // Create the scheduling vault
       await program.methods
         .createVault(
-          "Scheduling Test Vault",
+          "Fee Vault",
           { company: {} },
           [{ sol: {} }],
           null,
           { allocationPerBps: {} },
-          "https://example.com/scheduling-vault.json",
-          "SCHEDULE"
+          null,
+          null
         )
         .accountsStrict({
-          vaultAccount: schedulingVault,
+          vaultAccount: feeVault,
           globalConfig: globalConfig,
-          payer: schedulingOwner.publicKey,
-          owner: schedulingOwner.publicKey,
+          payer: feeOwner.publicKey,
+          owner: feeOwner.publicKey,
           systemProgram: web3.SystemProgram.programId,
         })
-        .signers([schedulingOwner])
+        .signers([feeOwner])
         .rpc();
 
-      // Add member to vault
-      [schedulerMemberAccount] = web3.PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("member"),
-          schedulingVault.toBuffer(),
-          schedulerMember.publicKey.toBuffer(),
-        ],
-        program.programId
-      );
-
       await program.methods
-        .addMember(
-          "ScheduledMember",
-          5000, // 50% allocation
-          null,
-          null,
-          null
-        )
+        .depositSol(new BN(web3.LAMPORTS_PER_SOL))
         .accountsStrict({
-          payer: schedulingOwner.publicKey,
-          vaultAccount: schedulingVault,
-          owner: schedulingOwner.publicKey,
-          member: schedulerMemberAccount,
-          wallet: schedulerMember.publicKey,
+          vaultAccount: feeVault,
+          globalConfig: globalConfig,
+          depositor: feeOwner.publicKey,
           systemProgram: web3.SystemProgram.programId,
         })
-        .signers([schedulingOwner])
+        .signers([feeOwner])
         .rpc();
 
-      // Deposit funds to the scheduling vault
       await program.methods
-        .depositSol(new BN(10 * web3.LAMPORTS_PER_SOL))
+        .addMember("Contractor", 10000, null, null, null)
         .accountsStrict({
-          vaultAccount: schedulingVault,
-          depositor: schedulingOwner.publicKey,
+          payer: feeOwner.publicKey,
+          vaultAccount: feeVault,
+          authority: feeOwner.publicKey,
+          delegate: null,
+          member: payeeMember,
+          wallet: payee.publicKey,
           systemProgram: web3.SystemProgram.programId,
         })
-        .signers([schedulingOwner])
+        .signers([feeOwner])
         .rpc();
+    });
 
-      console.log("📋 Scheduling test environment initialized");
+    after(async () => {
+      // Tiers are program-wide; clear them so later tests see the default fee
+      await setTiers([]);
     });
 
-    it("Should set up vault payout schedule", async () => {
-      const currentTime = Math.floor(Date.now() / 1000);
-      const payoutSchedule = {
-        interval: new BN(5), // 5 seconds for testing
-        nextPayoutTs: new BN(currentTime + 2), // 2 seconds from now
-        active: true,
-      };
+    it("Should charge the default fee and track volume", async () => {
+      const fee = await payOut(1);
+      expect(fee.toString()).to.equal(feeAt(DEFAULT_FEE_BPS).toString());
+
+      const vault = await program.account.vaultAccount.fetch(feeVault);
+      const volume = vault.solVolume.dailyVolume.reduce(
+        (total: BN, day: BN) => total.add(day),
+        new BN(0)
+      );
+      expect(volume.toString()).to.equal(PAYOUT.toString());
+    });
+
+    it("Should apply a negotiated per-vault rate", async () => {
+      await setOverride(100);
+      const fee = await payOut(2);
+      expect(fee.toString()).to.equal(feeAt(100).toString());
+
+      await setOverride(null);
+    });
+
+    it("Should only let the config owner set an override", async () => {
+      try {
+        await program.methods
+          .setVaultFeeOverride(0)
+          .accountsStrict({
+            globalConfig: globalConfig,
+            vaultAccount: feeVault,
+            owner: feeOwner.publicKey,
+          })
+          .signers([feeOwner])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ConstraintHasOne");
+      }
+    });
+
+    it("Should reject tiers that are not ascending", async () => {
+      try {
+        await setTiers([
+          { minVolume: new BN(web3.LAMPORTS_PER_SOL), feeBps: 300 },
+          { minVolume: new BN(web3.LAMPORTS_PER_SOL / 10), feeBps: 200 },
+        ]);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidFeeTiers");
+      }
+    });
+
+    it("Should discount once the vault reaches a volume tier", async () => {
+      // The vault has paid 2 * PAYOUT this window
+      await setTiers([
+        { minVolume: PAYOUT, feeBps: 300 },
+        { minVolume: PAYOUT.muln(10), feeBps: 100 },
+      ]);
+
+      const fee = await payOut(3);
+      expect(fee.toString()).to.equal(feeAt(300).toString());
+    });
 
+    it("Should charge the fee on top of the payout in gross-up mode", async () => {
       await program.methods
-        .updatePayoutSchedule(payoutSchedule)
+        .setFeeMode({ grossUp: {} })
         .accountsStrict({
-          vaultAccount: schedulingVault,
-          owner: schedulingOwner.publicKey,
+          vaultAccount: feeVault,
+          owner: feeOwner.publicKey,
         })
-        .signers([schedulingOwner])
+        .signers([feeOwner])
         .rpc();
 
-      const vault = await program.account.vaultAccount.fetch(schedulingVault);
-      expect(vault.payoutSchedule).to.not.be.null;
-      expect(vault.payoutSchedule.active).to.be.true;
-      expect(vault.payoutSchedule.interval.toNumber()).to.equal(5);
+      const vaultBefore = await program.account.vaultAccount.fetch(feeVault);
+      const payeeBefore = await provider.connection.getBalance(payee.publicKey);
 
-      console.log("✅ Vault payout schedule configured for 5-second intervals");
+      const fee = await payOut(4);
+
+      // The member receives the full amount and the vault pays the fee
+      const payeeAfter = await provider.connection.getBalance(payee.publicKey);
+      expect(payeeAfter - payeeBefore).to.equal(PAYOUT.toNumber());
+
+      const vaultAfter = await program.account.vaultAccount.fetch(feeVault);
+      expect(
+        vaultBefore.totalBalance.sub(vaultAfter.totalBalance).toString()
+      ).to.equal(PAYOUT.add(fee).toString());
     });
 
-    it("Should simulate client-side scheduling with multiple executions", async () => {
-      // Add the vault to scheduler and register owner
-      scheduler.registerVaultOwner(schedulingVault.toString(), schedulingOwner);
-      scheduler.addSchedule(
-        schedulingVault.toString(),
-        3, // Execute every 3 seconds (faster than vault schedule for testing)
-        [schedulerMember.publicKey]
-      );
+    it("Should reserve and charge the grossed-up fee on pulled claims", async () => {
+      await program.methods
+        .createPayrollBatch(new BN(5), PAYOUT)
+        .accountsStrict({
+          payer: feeOwner.publicKey,
+          vaultAccount: feeVault,
+          globalConfig: globalConfig,
+          payrollBatch: batchFor(5),
+          authority: feeOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([feeOwner])
+        .rpc();
 
-      // Record initial balance
-      const initialMemberBalance = await provider.connection.getBalance(
-        schedulerMember.publicKey
-      );
-      console.log(
-        `💰 Initial member balance: ${
-          initialMemberBalance / web3.LAMPORTS_PER_SOL
-        } SOL`
-      );
+      const vaultBefore = await program.account.vaultAccount.fetch(feeVault);
 
-      // Wait for multiple scheduled executions
-      console.log("⏳ Waiting for scheduled payouts to execute...");
-      await new Promise((resolve) => setTimeout(resolve, 12000)); // Wait 12 seconds for multiple executions
+      await program.methods
+        .allocateClaim(PAYOUT, { sol: {} })
+        .accountsStrict({
+          payer: feeOwner.publicKey,
+          vaultAccount: feeVault,
+          payrollBatch: batchFor(5),
+          approvalConfig: approvalConfigFor(feeVault),
+          member: payeeMember,
+          receipt: receiptFor(batchFor(5), payeeMember),
+          globalConfig: globalConfig,
+          vaultTokenAccount: null,
+          authority: feeOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([feeOwner])
+        .rpc();
 
-      // Check if payouts occurred
-      const finalMemberBalance = await provider.connection.getBalance(
-        schedulerMember.publicKey
-      );
-      console.log(
-        `💰 Final member balance: ${
-          finalMemberBalance / web3.LAMPORTS_PER_SOL
-        } SOL`
+      const receipt = await program.account.payoutReceipt.fetch(
+        receiptFor(batchFor(5), payeeMember)
       );
+      expect(receipt.netAmount.toString()).to.equal(PAYOUT.toString());
+      const allocated = await program.account.vaultAccount.fetch(feeVault);
+      expect(
+        allocated.requiredBalance.sub(vaultBefore.requiredBalance).toString()
+      ).to.equal(PAYOUT.add(receipt.serviceFee).toString());
 
-      // Should have received at least one payout
-      expect(finalMemberBalance).to.be.greaterThan(initialMemberBalance);
-
-      const balanceIncrease = finalMemberBalance - initialMemberBalance;
-      const expectedPayouts = Math.floor(
-        balanceIncrease / (0.95 * web3.LAMPORTS_PER_SOL * 0.05)
-      ); // Rough estimate considering fees
+      const payeeBefore = await provider.connection.getBalance(payee.publicKey);
+      await program.methods
+        .claimSolPayment()
+        .accountsStrict({
+          claimant: payee.publicKey,
+          vaultAccount: feeVault,
+          receipt: receiptFor(batchFor(5), payeeMember),
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([payee])
+        .rpc();
 
-      console.log(
-        `📈 Balance increased by ${balanceIncrease / web3.LAMPORTS_PER_SOL} SOL`
+      // The claimant receives the full amount and the reservation is released
+      const payeeAfter = await provider.connection.getBalance(payee.publicKey);
+      expect(payeeAfter - payeeBefore).to.equal(PAYOUT.toNumber());
+      const vaultAfter = await program.account.vaultAccount.fetch(feeVault);
+      expect(vaultAfter.requiredBalance.toString()).to.equal(
+        vaultBefore.requiredBalance.toString()
       );
-      console.log(`🔄 Estimated number of payouts: ${expectedPayouts}`);
+      expect(
+        vaultBefore.totalBalance.sub(vaultAfter.totalBalance).toString()
+      ).to.equal(PAYOUT.add(receipt.serviceFee).toString());
+    });
+  });
 
-      // Verify scheduler status
-      const scheduleStatus = scheduler.getScheduleStatus(
-        schedulingVault.toString()
+  describe("Balance Sync", () => {
+    let vaultTokenAccount: web3.PublicKey;
+
+    const syncBalances = (tokenAccounts: web3.PublicKey[]) =>
+      program.methods
+        .syncBalances()
+        .accountsStrict({ vaultAccount: vaultAccount })
+        .remainingAccounts(
+          tokenAccounts.map((pubkey) => ({
+            pubkey,
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .rpc();
+
+    before(() => {
+      vaultTokenAccount = getAssociatedTokenAddressSync(
+        testMint,
+        vaultAccount,
+        true
       );
-      expect(scheduleStatus).to.not.be.undefined;
-      expect(scheduleStatus.isActive).to.be.true;
     });
 
-    it("Should handle scheduling interruption and resumption", async () => {
-      console.log("🛑 Stopping scheduler...");
-      scheduler.stopSchedule(schedulingVault.toString());
+    it("Should keep sol_balance and total_balance in step", async () => {
+      const vault = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(vault.solBalance.toString()).to.equal(
+        vault.totalBalance.toString()
+      );
+    });
 
-      const initialBalance = await provider.connection.getBalance(
-        schedulerMember.publicKey
+    it("Should pick up SOL and tokens sent to the vault directly", async () => {
+      const direct = web3.LAMPORTS_PER_SOL / 10;
+      await provider.sendAndConfirm(
+        new web3.Transaction().add(
+          web3.SystemProgram.transfer({
+            fromPubkey: provider.wallet.publicKey,
+            toPubkey: vaultAccount,
+            lamports: direct,
+          })
+        )
+      );
+      await mintTo(
+        provider.connection,
+        vaultOwner,
+        testMint,
+        vaultTokenAccount,
+        vaultOwner,
+        1_000_000
       );
 
-      // Wait a bit to ensure no payouts occur
-      await new Promise((resolve) => setTimeout(resolve, 8000));
+      const before = await program.account.vaultAccount.fetch(vaultAccount);
+      await syncBalances([vaultTokenAccount]);
+      const after = await program.account.vaultAccount.fetch(vaultAccount);
 
-      const balanceAfterStop = await provider.connection.getBalance(
-        schedulerMember.publicKey
+      const info = await provider.connection.getAccountInfo(vaultAccount);
+      const rent =
+        await provider.connection.getMinimumBalanceForRentExemption(
+          info.data.length
+        );
+      expect(after.solBalance.toNumber()).to.equal(info.lamports - rent);
+      expect(after.totalBalance.toString()).to.equal(
+        after.solBalance.toString()
+      );
+      expect(after.totalBalance.sub(before.totalBalance).toNumber()).to.equal(
+        direct
       );
 
-      // Balance should not have changed significantly (only minor fluctuations from transaction fees)
-      expect(Math.abs(balanceAfterStop - initialBalance)).to.be.lessThan(
-        0.01 * web3.LAMPORTS_PER_SOL
+      const ata = await getAccount(provider.connection, vaultTokenAccount);
+      const entry = after.splBalances.find((balance) =>
+        balance.mint.equals(testMint)
       );
-      console.log("✅ No payouts occurred while scheduler was stopped");
+      expect(entry.balance.toString()).to.equal(ata.amount.toString());
+    });
 
-      // Resume scheduling
-      console.log("▶️ Resuming scheduler...");
-      scheduler.registerVaultOwner(schedulingVault.toString(), schedulingOwner); // Re-register owner
-      scheduler.addSchedule(
-        schedulingVault.toString(),
-        4, // Different interval
-        [schedulerMember.publicKey]
+    it("Should reject token accounts that are not the vault ATA", async () => {
+      const stray = await createAccount(
+        provider.connection,
+        vaultOwner,
+        testMint,
+        vaultAccount,
+        web3.Keypair.generate()
       );
 
-      // Wait for execution
-      await new Promise((resolve) => setTimeout(resolve, 8000));
+      try {
+        await syncBalances([stray]);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidTokenAccount");
+      }
+    });
 
-      const balanceAfterResume = await provider.connection.getBalance(
-        schedulerMember.publicKey
+    it("Should still track tokens of a mint removed from the whitelist", async () => {
+      const updateAsset = (method: string, mint: web3.PublicKey) =>
+        program.methods[method]({ splToken: { mint } })
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            owner: vaultOwner.publicKey,
+          })
+          .signers([vaultOwner])
+          .rpc();
+      const delistedMint = await createMint(
+        provider.connection,
+        vaultOwner,
+        vaultOwner.publicKey,
+        null,
+        6
+      );
+      const delistedTokenAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        vaultOwner,
+        delistedMint,
+        vaultAccount,
+        undefined,
+        TOKEN_PROGRAM_ID,
+        ASSOCIATED_TOKEN_PROGRAM_ID,
+        true
+      );
+      await updateAsset("addWhitelistedAsset", delistedMint);
+      await updateAsset("removeWhitelistedAsset", delistedMint);
+      await mintTo(
+        provider.connection,
+        vaultOwner,
+        delistedMint,
+        delistedTokenAccount,
+        vaultOwner,
+        500_000
       );
 
-      expect(balanceAfterResume).to.be.greaterThan(balanceAfterStop);
-      console.log("✅ Payouts resumed after scheduler restart");
-    });
+      await syncBalances([delistedTokenAccount]);
 
-    it("Should handle multiple members in scheduled payouts", async () => {
-      // Create additional test members
-      const member2 = web3.Keypair.generate();
-      const member3 = web3.Keypair.generate();
+      const vault = await program.account.vaultAccount.fetch(vaultAccount);
+      const entry = vault.splBalances.find((balance) =>
+        balance.mint.equals(delistedMint)
+      );
+      expect(entry.balance.toNumber()).to.equal(500_000);
+      expect(
+        vault.whitelistedAssets.some(
+          (asset) => asset.splToken && asset.splToken.mint.equals(delistedMint)
+        )
+      ).to.be.false;
+    });
+  });
 
-      await Promise.all([
-        provider.connection.requestAirdrop(
-          member2.publicKey,
-          2 * web3.LAMPORTS_PER_SOL
-        ),
-        provider.connection.requestAirdrop(
-          member3.publicKey,
-          2 * web3.LAMPORTS_PER_SOL
-        ),
-      ]);
+  describe("Events", () => {
+    const eventBatchId = new BN(106);
+    let eventBatch: web3.PublicKey;
+    let member1Account: web3.PublicKey;
 
-      await new Promise((resolve) => setTimeout(resolve, 2000));
+    // Decodes the program events logged by a confirmed transaction
+    const eventsOf = async (signature: string) => {
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl)
+      );
+      return [...parser.parseLogs(tx.meta.logMessages)];
+    };
 
-      // Add members to vault
-      const [member2Account] = web3.PublicKey.findProgramAddressSync(
+    before(() => {
+      [member1Account] = web3.PublicKey.findProgramAddressSync(
         [
           Buffer.from("member"),
-          schedulingVault.toBuffer(),
-          member2.publicKey.toBuffer(),
+          vaultAccount.toBuffer(),
+          member1.publicKey.toBuffer(),
         ],
         program.programId
       );
-      const [member3Account] = web3.PublicKey.findProgramAddressSync(
+      [eventBatch] = web3.PublicKey.findProgramAddressSync(
         [
-          Buffer.from("member"),
-          schedulingVault.toBuffer(),
-          member3.publicKey.toBuffer(),
+          Buffer.from("batch"),
+          vaultAccount.toBuffer(),
+          eventBatchId.toArrayLike(Buffer, "le", 8),
         ],
         program.programId
       );
+    });
 
-      await program.methods
-        .addMember("ScheduledMember2", 2500, null, null, null)
+    it("Should emit DepositMade on deposits", async () => {
+      const amount = new BN(web3.LAMPORTS_PER_SOL / 10);
+      const signature = await program.methods
+        .depositSol(amount)
         .accountsStrict({
-          payer: schedulingOwner.publicKey,
-          vaultAccount: schedulingVault,
-          owner: schedulingOwner.publicKey,
-          member: member2Account,
-          wallet: member2.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          depositor: vaultOwner.publicKey,
           systemProgram: web3.SystemProgram.programId,
         })
-        .signers([schedulingOwner])
-        .rpc();
+        .signers([vaultOwner])
+        .rpc({ commitment: "confirmed" });
 
-      await program.methods
-        .addMember("ScheduledMember3", 2500, null, null, null)
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("depositMade");
+      expect(event.data.vault.equals(vaultAccount)).to.be.true;
+      expect(event.data.depositor.equals(vaultOwner.publicKey)).to.be.true;
+      expect(event.data.amount.toString()).to.equal(amount.toString());
+    });
+
+    it("Should emit batch, payout and fee events", async () => {
+      const created = await program.methods
+        .createPayrollBatch(eventBatchId, new BN(web3.LAMPORTS_PER_SOL))
         .accountsStrict({
-          payer: schedulingOwner.publicKey,
-          vaultAccount: schedulingVault,
-          owner: schedulingOwner.publicKey,
-          member: member3Account,
-          wallet: member3.publicKey,
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          globalConfig: globalConfig,
+          payrollBatch: eventBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
           systemProgram: web3.SystemProgram.programId,
         })
-        .signers([schedulingOwner])
-        .rpc();
+        .signers([vaultOwner])
+        .rpc({ commitment: "confirmed" });
+      const [batchCreated] = await eventsOf(created);
+      expect(batchCreated.name).to.equal("batchCreated");
+      expect(batchCreated.data.batchId.toString()).to.equal(
+        eventBatchId.toString()
+      );
 
-      // Stop previous schedule and start multi-member schedule
-      scheduler.stopSchedule(schedulingVault.toString());
+      const amount = new BN(web3.LAMPORTS_PER_SOL / 100);
+      const paid = await program.methods
+        .processSolPayout(amount)
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          payrollBatch: eventBatch,
+          approvalConfig: approvalConfigFor(vaultAccount),
+          member: member1Account,
+          receipt: receiptFor(eventBatch, member1Account),
+          globalConfig: globalConfig,
+          treasury: treasury.publicKey,
+          memberWallet: member1.publicKey,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([vaultOwner])
+        .rpc({ commitment: "confirmed" });
+      const payoutEvents = await eventsOf(paid);
+      const fee = payoutEvents.find((event) => event.name === "feeCollected");
+      const payout = payoutEvents.find(
+        (event) => event.name === "payoutExecuted"
+      );
+      expect(payout.data.recipient.equals(member1.publicKey)).to.be.true;
+      expect(payout.data.amount.toString()).to.equal(amount.toString());
+      expect(payout.data.batchId.toString()).to.equal(eventBatchId.toString());
+      expect(payout.data.source).to.have.property("batch");
+      expect(
+        payout.data.netAmount.add(payout.data.serviceFee).toString()
+      ).to.equal(amount.toString());
+      expect(fee.data.amount.toString()).to.equal(
+        payout.data.serviceFee.toString()
+      );
 
-      // Add more funds to vault
-      await program.methods
-        .depositSol(new BN(5 * web3.LAMPORTS_PER_SOL))
+      const finalized = await program.methods
+        .finalizePayrollBatch()
         .accountsStrict({
-          vaultAccount: schedulingVault,
-          depositor: schedulingOwner.publicKey,
+          vaultAccount: vaultAccount,
+          payrollBatch: eventBatch,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+        })
+        .signers([vaultOwner])
+        .rpc({ commitment: "confirmed" });
+      const [batchFinalized] = await eventsOf(finalized);
+      expect(batchFinalized.name).to.equal("batchFinalized");
+      expect(batchFinalized.data.payoutCount).to.equal(1);
+      expect(batchFinalized.data.paidAmount.toString()).to.equal(
+        amount.toString()
+      );
+    });
+
+    it("Should emit delegate events on grant and removal", async () => {
+      const auditor = web3.Keypair.generate();
+      const [auditorDelegate] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("delegate"),
+          vaultAccount.toBuffer(),
+          auditor.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const granted = await program.methods
+        .setDelegate(1)
+        .accountsStrict({
+          payer: vaultOwner.publicKey,
+          vaultAccount: vaultAccount,
+          owner: vaultOwner.publicKey,
+          delegate: auditorDelegate,
+          delegateWallet: auditor.publicKey,
           systemProgram: web3.SystemProgram.programId,
         })
-        .signers([schedulingOwner])
-        .rpc();
+        .signers([vaultOwner])
+        .rpc({ commitment: "confirmed" });
+      const [delegateSet] = await eventsOf(granted);
+      expect(delegateSet.name).to.equal("delegateSet");
+      expect(delegateSet.data.delegate.equals(auditor.publicKey)).to.be.true;
+      expect(delegateSet.data.permissions).to.equal(1);
+
+      const removed = await program.methods
+        .removeDelegate()
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          delegate: auditorDelegate,
+          owner: vaultOwner.publicKey,
+        })
+        .signers([vaultOwner])
+        .rpc({ commitment: "confirmed" });
+      const [delegateRemoved] = await eventsOf(removed);
+      expect(delegateRemoved.name).to.equal("delegateRemoved");
+      expect(delegateRemoved.data.delegate.equals(auditor.publicKey)).to.be
+        .true;
+    });
 
-      console.log("👥 Starting multi-member scheduled payouts...");
-      scheduler.registerVaultOwner(schedulingVault.toString(), schedulingOwner); // Re-register owner
-      scheduler.addSchedule(
-        schedulingVault.toString(),
-        6, // 6-second intervals
-        [schedulerMember.publicKey, member2.publicKey, member3.publicKey]
+    it("Should emit BalancesSynced with the synced SOL balance", async () => {
+      const signature = await program.methods
+        .syncBalances()
+        .accountsStrict({ vaultAccount: vaultAccount })
+        .rpc({ commitment: "confirmed" });
+
+      const [event] = await eventsOf(signature);
+      const vault = await program.account.vaultAccount.fetch(vaultAccount);
+      expect(event.name).to.equal("balancesSynced");
+      expect(event.data.solBalance.toString()).to.equal(
+        vault.solBalance.toString()
       );
+    });
 
-      // Record initial balances
-      const initialBalances = await Promise.all([
-        provider.connection.getBalance(schedulerMember.publicKey),
-        provider.connection.getBalance(member2.publicKey),
-        provider.connection.getBalance(member3.publicKey),
-      ]);
+    it("Should emit events for vault settings and member updates", async () => {
+      const setVaultPaused = (paused: boolean) =>
+        program.methods
+          .setVaultPaused(paused)
+          .accountsStrict({
+            vaultAccount: vaultAccount,
+            owner: vaultOwner.publicKey,
+          })
+          .signers([vaultOwner])
+          .rpc({ commitment: "confirmed" });
 
-      // Wait for executions
-      await new Promise((resolve) => setTimeout(resolve, 15000));
+      const [paused] = await eventsOf(await setVaultPaused(true));
+      expect(paused.name).to.equal("vaultPaused");
+      expect(paused.data.paused).to.be.true;
+      const [resumed] = await eventsOf(await setVaultPaused(false));
+      expect(resumed.data.paused).to.be.false;
 
-      // Check final balances
-      const finalBalances = await Promise.all([
-        provider.connection.getBalance(schedulerMember.publicKey),
-        provider.connection.getBalance(member2.publicKey),
-        provider.connection.getBalance(member3.publicKey),
-      ]);
+      const { feeMode } = await program.account.vaultAccount.fetch(
+        vaultAccount
+      );
+      const feeModeSet = await program.methods
+        .setFeeMode(feeMode)
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          owner: vaultOwner.publicKey,
+        })
+        .signers([vaultOwner])
+        .rpc({ commitment: "confirmed" });
+      const [setting] = await eventsOf(feeModeSet);
+      expect(setting.name).to.equal("vaultSettingUpdated");
+      expect(setting.data.setting.feeMode).to.not.be.undefined;
 
-      // All members should have received payouts
-      for (let i = 0; i < 3; i++) {
-        expect(finalBalances[i]).to.be.greaterThan(initialBalances[i]);
-        console.log(
-          `💰 Member ${i + 1} balance increase: ${
-            (finalBalances[i] - initialBalances[i]) / web3.LAMPORTS_PER_SOL
-          } SOL`
-        );
-      }
+      const { allocationBps } = await program.account.member.fetch(
+        member1Account
+      );
+      const allocationSet = await program.methods
+        .updateMemberAllocation(allocationBps)
+        .accountsStrict({
+          vaultAccount: vaultAccount,
+          member: member1Account,
+          authority: vaultOwner.publicKey,
+          delegate: null,
+        })
+        .signers([vaultOwner])
+        .rpc({ commitment: "confirmed" });
+      const [memberUpdated] = await eventsOf(allocationSet);
+      expect(memberUpdated.name).to.equal("memberUpdated");
+      expect(memberUpdated.data.member.equals(member1Account)).to.be.true;
+      expect(memberUpdated.data.allocationBps).to.equal(allocationBps);
+    });
+  });
 
-      console.log("✅ Multi-member scheduled payouts working correctly");
+  describe("Close Vault", () => {
+    const closingOwner = web3.Keypair.generate();
+    const closingMember = web3.Keypair.generate();
+    const closingBatchId = new BN(1);
+    let closingVault: web3.PublicKey;
+    let closingMemberAccount: web3.PublicKey;
+    let closingBatch: web3.PublicKey;
+
+    // The vault ATA for testMint was never created, so the sweep skips it
+    const sweepAccounts = () => [
+      { pubkey: testMint, isWritable: false, isSigner: false },
+      { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      {
+        pubkey: getAssociatedTokenAddressSync(testMint, closingVault, true),
+        isWritable: true,
+        isSigner: false,
+      },
+      {
+        pubkey: getAssociatedTokenAddressSync(
+          testMint,
+          closingOwner.publicKey
+        ),
+        isWritable: true,
+        isSigner: false,
+      },
+    ];
+
+    // Force closes take the remaining member accounts first
+    const memberMeta = () => ({
+      pubkey: closingMemberAccount,
+      isWritable: true,
+      isSigner: false,
     });
 
-    it("Should demonstrate scheduling with error handling", async () => {
-      // Create a member and then deactivate them to test error handling
-      const errorTestMember = web3.Keypair.generate();
+    const closeVault = (force: boolean, remaining = sweepAccounts()) =>
+      program.methods
+        .closeVault(force)
+        .accountsStrict({
+          vaultAccount: closingVault,
+          approvalConfig: approvalConfigFor(closingVault),
+          owner: closingOwner.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .remainingAccounts(remaining)
+        .signers([closingOwner])
+        .rpc();
+
+    before(async () => {
       await provider.connection.requestAirdrop(
-        errorTestMember.publicKey,
-        2 * web3.LAMPORTS_PER_SOL
+        closingOwner.publicKey,
+        5 * web3.LAMPORTS_PER_SOL
       );
-      await new Promise((resolve) => setTimeout(resolve, 2000));
+      await new Promise((resolve) => setTimeout(resolve, 1000));
 
-      const [errorMemberAccount] = web3.PublicKey.findProgramAddressSync(
+      const configAccount = await program.account.globalConfig.fetch(
+        globalConfig
+      );
+      [closingVault] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          closingOwner.publicKey.toBuffer(),
+          configAccount.nextCompanyId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      [closingMemberAccount] = web3.PublicKey.findProgramAddressSync(
         [
           Buffer.from("member"),
-          schedulingVault.toBuffer(),
-          errorTestMember.publicKey.toBuffer(),
+          closingVault.toBuffer(),
+          closingMember.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+      [closingBatch] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("batch"),
+          closingVault.toBuffer(),
+          closingBatchId.toArrayLike(Buffer, "le", 8),
         ],
         program.programId
       );
 
-      // Add member
       await program.methods
-        .addMember("ErrorTestMember", 1000, null, null, null)
+        .createVault(
+          "Closing Vault",
+          { company: {} },
+          [{ sol: {} }, { splToken: { mint: testMint } }],
+          null,
+          { allocationPerBps: {} },
+          null,
+          null
+        )
         .accountsStrict({
-          payer: schedulingOwner.publicKey,
-          vaultAccount: schedulingVault,
-          owner: schedulingOwner.publicKey,
-          member: errorMemberAccount,
-          wallet: errorTestMember.publicKey,
+          vaultAccount: closingVault,
+          globalConfig: globalConfig,
+          payer: closingOwner.publicKey,
+          owner: closingOwner.publicKey,
           systemProgram: web3.SystemProgram.programId,
         })
-        .signers([schedulingOwner])
+        .signers([closingOwner])
         .rpc();
 
-      // Deactivate the member
       await program.methods
-        .toggleMemberActiveStatus()
+        .depositSol(new BN(web3.LAMPORTS_PER_SOL))
         .accountsStrict({
-          vaultAccount: schedulingVault,
-          member: errorMemberAccount,
-          owner: schedulingOwner.publicKey,
+          vaultAccount: closingVault,
+          globalConfig: globalConfig,
+          depositor: closingOwner.publicKey,
+          systemProgram: web3.SystemProgram.programId,
         })
-        .signers([schedulingOwner])
+        .signers([closingOwner])
         .rpc();
 
-      // Stop current schedule and create one that includes the inactive member
-      scheduler.stopSchedule(schedulingVault.toString());
-
-      console.log(
-        "🚨 Testing scheduler error handling with inactive member..."
-      );
-      scheduler.registerVaultOwner(schedulingVault.toString(), schedulingOwner); // Re-register owner
-      scheduler.addSchedule(
-        schedulingVault.toString(),
-        5,
-        [schedulerMember.publicKey, errorTestMember.publicKey] // Include inactive member
-      );
-
-      // Wait for executions - scheduler should skip inactive member gracefully
-      await new Promise((resolve) => setTimeout(resolve, 12000));
+      await program.methods
+        .addMember("Closer", 10000, null, null, null)
+        .accountsStrict({
+          payer: closingOwner.publicKey,
+          vaultAccount: closingVault,
+          authority: closingOwner.publicKey,
+          delegate: null,
+          member: closingMemberAccount,
+          wallet: closingMember.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([closingOwner])
+        .rpc();
 
-      // The active member should still receive payouts despite the error with the inactive member
-      console.log(
-        "✅ Scheduler handled errors gracefully and continued processing active members"
-      );
+      await program.methods
+        .createPayrollBatch(closingBatchId, new BN(web3.LAMPORTS_PER_SOL))
+        .accountsStrict({
+          payer: closingOwner.publicKey,
+          vaultAccount: closingVault,
+          globalConfig: globalConfig,
+          payrollBatch: closingBatch,
+          authority: closingOwner.publicKey,
+          delegate: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([closingOwner])
+        .rpc();
     });
 
-    it("Should demonstrate schedule monitoring and metrics", async () => {
-      console.log("\n📊 SCHEDULING METRICS SUMMARY");
-      console.log("============================");
-
-      // Get current schedule status
-      const status = scheduler.getScheduleStatus(schedulingVault.toString());
-      if (status) {
-        console.log(`⏰ Schedule Interval: ${status.intervalSeconds} seconds`);
-        console.log(
-          `👥 Members in Schedule: ${status.membersToProcess.length}`
-        );
-        console.log(`🟢 Schedule Active: ${status.isActive}`);
-        console.log(
-          `⏭️ Next Run: ${new Date(
-            status.nextRunTime * 1000
-          ).toLocaleTimeString()}`
-        );
-      }
-
-      // Get vault information
-      const vault = await program.account.vaultAccount.fetch(schedulingVault);
-      console.log(
-        `💰 Vault Balance: ${vault.totalBalance.toString()} lamports (${
-          vault.totalBalance.toNumber() / web3.LAMPORTS_PER_SOL
-        } SOL)`
-      );
-
-      if (vault.payoutSchedule) {
-        console.log(
-          `🏦 Vault Schedule Interval: ${vault.payoutSchedule.interval} seconds`
-        );
-        console.log(
-          `🏦 Vault Next Payout: ${new Date(
-            vault.payoutSchedule.nextPayoutTs.toNumber() * 1000
-          ).toLocaleTimeString()}`
-        );
-        console.log(`🏦 Vault Schedule Active: ${vault.payoutSchedule.active}`);
-      }
-
-      console.log("============================\n");
-
-      // Verify we can read the schedule status
-      expect(status).to.not.be.undefined;
-      if (status) {
-        expect(status.intervalSeconds).to.be.greaterThan(0);
-        expect(status.membersToProcess.length).to.be.greaterThan(0);
+    it("Should refuse to close while members remain", async () => {
+      try {
+        await closeVault(false);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("MembersRemaining");
       }
     });
 
-    after(async () => {
-      // Cleanup: Stop all schedulers
-      console.log("🧹 Cleaning up schedulers...");
-      scheduler.stopAll();
-
-      // Wait a moment to ensure cleanup
-      await new Promise((resolve) => setTimeout(resolve, 1000));
-
-      console.log("✅ Scheduling simulation tests completed");
-    });
-  });
-
-  describe("Claim Offchain", () => {
-    // since i still cant find a best practice to doit onchain so i do it offchain
-
-    // Types for the claiming system
-    interface ClaimCode {
-      code: string;
-      vaultId: string;
-      amount: BN;
-      assetType: "SOL" | "SPL";
-      mint?: web3.PublicKey; // For SPL tokens
-      isUsed: boolean;
-      createdAt: Date;
-      expiresAt?: Date;
-      claimedBy?: string;
-      claimedAt?: Date;
-    }
-
-    interface ClaimDatabase {
-      codes: Map<string, ClaimCode>;
-      vaultConfigs: Map<string, VaultConfig>;
-    }
-
-    interface VaultConfig {
-      vaultAddress: web3.PublicKey;
-      ownerKeypair: web3.Keypair;
-      globalConfig: web3.PublicKey;
-      treasury: web3.PublicKey;
-      allowedAssets: AssetType[];
-    }
-
-    type AssetType = { sol: {} } | { splToken: { mint: web3.PublicKey } };
-
-    class OffchainClaimingSystem {
-      private program: Program<PayermintP>;
-      private provider: anchor.AnchorProvider;
-      private database: ClaimDatabase;
-
-      constructor(
-        program: Program<PayermintP>,
-        provider: anchor.AnchorProvider
-      ) {
-        this.program = program;
-        this.provider = provider;
-        this.database = {
-          codes: new Map(),
-          vaultConfigs: new Map(),
-        };
+    it("Should refuse to close with an open payroll batch", async () => {
+      try {
+        await closeVault(true);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("OpenBatchesRemaining");
       }
 
-      // (Keep your existing registerVault, generateClaimCode, createSolClaimCodes, and validateClaimCode methods as they are)
-      registerVault(
-        vaultId: string,
-        vaultAddress: web3.PublicKey,
-        ownerKeypair: web3.Keypair,
-        globalConfig: web3.PublicKey,
-        treasury: web3.PublicKey,
-        allowedAssets: AssetType[] = [{ sol: {} }]
-      ): void {
-        this.database.vaultConfigs.set(vaultId, {
-          vaultAddress,
-          ownerKeypair,
-          globalConfig,
-          treasury,
-          allowedAssets,
-        });
-        console.log(`✅ Vault registered for claiming: ${vaultId}`);
-      }
-      private generateClaimCode(): string {
-        const crypto = require("crypto");
-        const randomBytes = crypto.randomBytes(16);
-        return `CLAIM-${randomBytes
-          .toString("hex")
-          .toUpperCase()
-          .substring(0, 12)}`;
-      }
-      createSolClaimCodes(
-        vaultId: string,
-        amounts: BN[],
-        expirationHours: number = 24
-      ): string[] {
-        const vaultConfig = this.database.vaultConfigs.get(vaultId);
-        if (!vaultConfig) throw new Error(`Vault ${vaultId} not registered`);
-        const codes: string[] = [];
-        const expiresAt = new Date();
-        expiresAt.setHours(expiresAt.getHours() + expirationHours);
-        for (const amount of amounts) {
-          const code = this.generateClaimCode();
-          const claimCode: ClaimCode = {
-            code,
-            vaultId,
-            amount,
-            assetType: "SOL",
-            isUsed: false,
-            createdAt: new Date(),
-            expiresAt,
-          };
-          this.database.codes.set(code, claimCode);
-          codes.push(code);
-        }
-        console.log(
-          `🎫 Created ${codes.length} SOL claim codes for vault ${vaultId}`
-        );
-        return codes;
-      }
-      validateClaimCode(code: string): {
-        valid: boolean;
-        error?: string;
-        claimData?: ClaimCode;
-      } {
-        const claimCode = this.database.codes.get(code);
-        if (!claimCode) return { valid: false, error: "Invalid claim code" };
-        if (claimCode.isUsed)
-          return {
-            valid: false,
-            error: `Code already claimed by ${claimCode.claimedBy}`,
-          };
-        if (claimCode.expiresAt && new Date() > claimCode.expiresAt)
-          return { valid: false, error: "Claim code has expired" };
-        if (!this.database.vaultConfigs.get(claimCode.vaultId))
-          return { valid: false, error: "Vault configuration not found" };
-        return { valid: true, claimData: claimCode };
-      }
-
-      /**
-       * STEP 1: Signed by the VAULT OWNER to prepare the claim.
-       * This sets up the temporary member and the batch.
-       */
-      async prepareSolClaim(
-        code: string,
-        claimerWallet: web3.PublicKey
-      ): Promise<{
-        success: boolean;
-        error?: string;
-        tempMemberAccount?: web3.PublicKey;
-        payrollBatch?: web3.PublicKey;
-      }> {
-        const validation = this.validateClaimCode(code);
-        if (!validation.valid)
-          return { success: false, error: validation.error };
-
-        const claimData = validation.claimData!;
-        const vaultConfig = this.database.vaultConfigs.get(claimData.vaultId)!;
-
-        const [tempMemberAccount] = web3.PublicKey.findProgramAddressSync(
-          [
-            Buffer.from("member"),
-            vaultConfig.vaultAddress.toBuffer(),
-            claimerWallet.toBuffer(),
-          ],
-          this.program.programId
-        );
-
-        // Add member
-        await this.program.methods
-          .addMember("TempClaimer", null, claimData.amount, null, null)
-          .accountsStrict({
-            payer: vaultConfig.ownerKeypair.publicKey,
-            vaultAccount: vaultConfig.vaultAddress,
-            owner: vaultConfig.ownerKeypair.publicKey,
-            member: tempMemberAccount,
-            wallet: claimerWallet,
-            systemProgram: web3.SystemProgram.programId,
-          })
-          .signers([vaultConfig.ownerKeypair])
-          .rpc();
-
-        // Create batch
-        const batchId = new BN(Date.now());
-        const [payrollBatch] = web3.PublicKey.findProgramAddressSync(
-          [
-            Buffer.from("batch"),
-            vaultConfig.vaultAddress.toBuffer(),
-            batchId.toArrayLike(Buffer, "le", 8),
-          ],
-          this.program.programId
-        );
-
-        await this.program.methods
-          .createPayrollBatch(batchId, claimData.amount)
-          .accountsStrict({
-            payer: vaultConfig.ownerKeypair.publicKey,
-            vaultAccount: vaultConfig.vaultAddress,
-            globalConfig: vaultConfig.globalConfig,
-            payrollBatch: payrollBatch,
-            owner: vaultConfig.ownerKeypair.publicKey,
-            systemProgram: web3.SystemProgram.programId,
-          })
-          .signers([vaultConfig.ownerKeypair])
-          .rpc();
+      await program.methods
+        .finalizePayrollBatch()
+        .accountsStrict({
+          vaultAccount: closingVault,
+          payrollBatch: closingBatch,
+          authority: closingOwner.publicKey,
+          delegate: null,
+        })
+        .signers([closingOwner])
+        .rpc();
+    });
 
-        return { success: true, tempMemberAccount, payrollBatch };
+    it("Should refuse to close while a finalized batch remains", async () => {
+      try {
+        await closeVault(true, [memberMeta(), ...sweepAccounts()]);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("VaultAccountsRemaining");
       }
 
-      /**
-       * STEP 2: Signed by the CLAIMER to execute the payout.
-       */
-      async executeSolClaim(
-        code: string,
-        claimerKeypair: web3.Keypair,
-        tempMemberAccount: web3.PublicKey,
-        payrollBatch: web3.PublicKey
-      ): Promise<{ success: boolean; txSignature?: string; error?: string }> {
-        try {
-          const validation = this.validateClaimCode(code);
-          if (!validation.valid)
-            return { success: false, error: validation.error };
-
-          const claimData = validation.claimData!;
-          const vaultConfig = this.database.vaultConfigs.get(
-            claimData.vaultId
-          )!;
-
-          // Only the vault owner signs - member_wallet is just AccountInfo, not Signer
-          const txSignature = await this.program.methods
-            .processSolPayout(claimData.amount)
-            .accountsStrict({
-              vaultAccount: vaultConfig.vaultAddress,
-              payrollBatch: payrollBatch,
-              member: tempMemberAccount,
-              globalConfig: vaultConfig.globalConfig,
-              treasury: vaultConfig.treasury,
-              memberWallet: claimerKeypair.publicKey, // This is just an AccountInfo, not a signer
-              owner: vaultConfig.ownerKeypair.publicKey,
-              systemProgram: web3.SystemProgram.programId,
-            })
-            .signers([vaultConfig.ownerKeypair]) // Only vault owner signs
-            .rpc();
-
-          // Mark code as used
-          claimData.isUsed = true;
-          claimData.claimedBy = claimerKeypair.publicKey.toString();
-          claimData.claimedAt = new Date();
+      await program.methods
+        .closePayrollBatch()
+        .accountsStrict({
+          vaultAccount: closingVault,
+          payrollBatch: closingBatch,
+          payer: closingOwner.publicKey,
+          authority: closingOwner.publicKey,
+          delegate: null,
+        })
+        .signers([closingOwner])
+        .rpc();
 
-          // Cleanup - remove the temporary member
-          await this.program.methods
-            .removeMember()
-            .accountsStrict({
-              vaultAccount: vaultConfig.vaultAddress,
-              member: tempMemberAccount,
-              owner: vaultConfig.ownerKeypair.publicKey,
-            })
-            .signers([vaultConfig.ownerKeypair])
-            .rpc();
+      const vault = await program.account.vaultAccount.fetch(closingVault);
+      expect(vault.batchCount).to.equal(0);
+    });
 
-          return { success: true, txSignature };
-        } catch (error) {
-          console.error(
-            "DETAILED CLAIM ERROR:",
-            JSON.stringify(error, null, 2)
-          );
-          return {
-            success: false,
-            error: error instanceof Error ? error.message : "Unknown error",
-          };
-        }
+    it("Should reject a sweep under the wrong token program", async () => {
+      const [mint, , vaultAta, ownerAta] = sweepAccounts();
+      try {
+        await closeVault(true, [
+          memberMeta(),
+          mint,
+          {
+            pubkey: TOKEN_2022_PROGRAM_ID,
+            isWritable: false,
+            isSigner: false,
+          },
+          vaultAta,
+          ownerAta,
+        ]);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidTokenAccount");
       }
-    }
+    });
 
-    it("should handle the full lifecycle of creating, claiming, and validating codes", async () => {
-      // --- SETUP ---
-      console.log("\n--- Running Offchain Claim Test ---");
-      const claimingSystem = new OffchainClaimingSystem(program, provider);
-      const configAccount = await program.account.globalConfig.fetch(
-        globalConfig
+    it("Should refuse to close while a delegate remains", async () => {
+      const [closingDelegate] = web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("delegate"),
+          closingVault.toBuffer(),
+          closingMember.publicKey.toBuffer(),
+        ],
+        program.programId
       );
-      const vaultData = await program.account.vaultAccount.fetch(vaultAccount);
-      const TEST_VAULT_ID = "main-test-vault";
-
-      // --- Ensure vault has sufficient funds ---
       await program.methods
-        .depositSol(new BN(5 * web3.LAMPORTS_PER_SOL))
+        .setDelegate(1)
         .accountsStrict({
-          vaultAccount,
-          depositor: vaultOwner.publicKey,
+          payer: closingOwner.publicKey,
+          vaultAccount: closingVault,
+          owner: closingOwner.publicKey,
+          delegate: closingDelegate,
+          delegateWallet: closingMember.publicKey,
           systemProgram: web3.SystemProgram.programId,
         })
-        .signers([vaultOwner])
+        .signers([closingOwner])
         .rpc();
 
-      // 1. Register vault
-      claimingSystem.registerVault(
-        TEST_VAULT_ID,
-        vaultAccount,
-        vaultOwner,
-        globalConfig,
-        configAccount.treasury,
-        vaultData.whitelistedAssets
-      );
-
-      // 2. Create codes
-      const claimAmounts = [new BN(1 * web3.LAMPORTS_PER_SOL)];
-      const claimCodes = claimingSystem.createSolClaimCodes(
-        TEST_VAULT_ID,
-        claimAmounts,
-        1
-      );
-      const codeToClaim = claimCodes[0];
+      try {
+        await closeVault(true, [memberMeta(), ...sweepAccounts()]);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("VaultAccountsRemaining");
+      }
 
-      // 3. Setup a claimer wallet
-      const claimer = web3.Keypair.generate();
-      await provider.connection.requestAirdrop(
-        claimer.publicKey,
-        2 * web3.LAMPORTS_PER_SOL
-      );
-      await new Promise((resolve) => setTimeout(resolve, 500));
-      const initialBalance = await provider.connection.getBalance(
-        claimer.publicKey
-      );
+      await program.methods
+        .removeDelegate()
+        .accountsStrict({
+          vaultAccount: closingVault,
+          delegate: closingDelegate,
+          owner: closingOwner.publicKey,
+        })
+        .signers([closingOwner])
+        .rpc();
+    });
 
-      // 4. PREPARE the claim (Owner's action)
-      console.log("\nStep 1: Preparing the claim as vault owner...");
-      const prepResult = await claimingSystem.prepareSolClaim(
-        codeToClaim,
-        claimer.publicKey
-      );
-      expect(prepResult.success, `Preparation failed: ${prepResult.error}`).to
-        .be.true;
-      expect(prepResult.tempMemberAccount).to.exist;
-      expect(prepResult.payrollBatch).to.exist;
-      console.log("✅ Claim prepared successfully.");
+    it("Should require every whitelisted mint to be swept", async () => {
+      try {
+        await closeVault(true, [memberMeta()]);
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("TokenAccountNotSwept");
+      }
+    });
 
-      // 5. EXECUTE the claim (Claimer's action)
-      console.log("\nStep 2: Executing the claim as the claimer...");
-      const execResult = await claimingSystem.executeSolClaim(
-        codeToClaim,
-        claimer, // Pass the full keypair for signing
-        prepResult.tempMemberAccount!,
-        prepResult.payrollBatch!
+    it("Should force close the vault with its members and return its SOL to the owner", async () => {
+      const vaultLamports = await provider.connection.getBalance(closingVault);
+      const ownerBefore = await provider.connection.getBalance(
+        closingOwner.publicKey
       );
 
-      // Assertions for successful execution
-      expect(execResult.success, `Execution failed: ${execResult.error}`).to.be
-        .true;
-      expect(execResult.txSignature).to.be.a("string");
+      await closeVault(true, [memberMeta(), ...sweepAccounts()]);
 
-      const finalBalance = await provider.connection.getBalance(
-        claimer.publicKey
-      );
-      // Balance check is tricky due to gas fees, but it should be higher.
-      expect(finalBalance).to.be.greaterThan(
-        initialBalance - web3.LAMPORTS_PER_SOL * 0.001
-      ); // Account for gas
-      console.log(
-        `✅ Claim executed successfully. Tx: ${execResult.txSignature}`
+      const vaultInfo = await provider.connection.getAccountInfo(closingVault);
+      expect(vaultInfo).to.be.null;
+      const memberInfo = await provider.connection.getAccountInfo(
+        closingMemberAccount
       );
+      expect(memberInfo).to.be.null;
 
-      // 6. Validate code is now used
-      const validationAfterClaim =
-        claimingSystem.validateClaimCode(codeToClaim);
-      expect(validationAfterClaim.valid).to.be.false;
-      expect(validationAfterClaim.error).to.include("Code already claimed");
+      // The owner also pays the transaction fee
+      const ownerAfter = await provider.connection.getBalance(
+        closingOwner.publicKey
+      );
+      expect(ownerAfter).to.be.greaterThan(ownerBefore + vaultLamports - 10000);
     });
   });
 